@@ -1,11 +1,73 @@
+use crate::infra::anki::{self, AddOutcome, AnkiConnect, NoteContent};
+use crate::infra::change_detection::ChangeDetector;
 use crate::infra::dictionary::DictionaryAdapter;
 use crate::infra::dictionary::{LookupError, LookupResult};
-use crate::infra::manga_ocr;
+use crate::infra::history::{self, CaptureEntry, NewCapture, NewLookup};
+use crate::infra::hotkeys::{HotkeyAction, HotkeyRegistry};
+use crate::infra::ocr::{OcrEngine, OcrOptions, run_ocr};
+use crate::infra::preprocess::{PreprocessPipeline, PreprocessPreset};
+use crate::infra::profiles::RegionProfile;
+use crate::infra::screenshot::window::{self, WindowArea, WindowTarget};
+use crate::infra::screenshot::{IMAGE_EXTENSIONS, ScreenshotService, load_image_file};
+use crate::infra::settings::{FieldError, HotkeySettings, Settings};
+use crate::infra::words::{self, ImportCounts, ImportedWord, NewWord, WordStatus, WordToken};
+use crate::infra::{self, MangaOcrEngine};
+use crate::shared::models::ocr::{CaptureSource, OcrResult, OverlayResult, ScreenRect};
 use crate::state::AppState;
 use crate::ui::reactive_overlay::OCROverlayController;
-use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use screenshots::image::RgbaImage;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, WebviewUrl, WebviewWindowBuilder,
+};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
 
-pub mod region_selection; //winit - softbuffer screencapture
+pub mod region_selection; // drag-to-select overlay for screen capture
+
+/// What watch mode keeps capturing.
+enum WatchSource {
+    Profile(RegionProfile, &'static dyn OcrEngine, OcrOptions),
+    Region(ScreenRect),
+}
+
+impl WatchSource {
+    fn capture(&self, app: &AppHandle) -> Result<(RgbaImage, (i32, i32)), String> {
+        match self {
+            WatchSource::Profile(profile, ..) => AppMediator::capture_profile_image(app, profile),
+            WatchSource::Region(rect) => ScreenshotService
+                .capture_region(rect.x, rect.y, rect.w, rect.h)
+                .map(|image| (image, (rect.x, rect.y)))
+                .map_err(|e| format!("{e:#}")),
+        }
+    }
+
+    fn process(&self, app: &AppHandle, image: RgbaImage, origin: (i32, i32)) {
+        let source = CaptureSource::Watch;
+        match self {
+            WatchSource::Profile(_, engine, options) => {
+                AppMediator::process_capture_with(app, *engine, image, origin, options, source)
+            }
+            WatchSource::Region(_) => AppMediator::process_capture(app, image, origin, source),
+        }
+    }
+}
+
+/// Time for a destroyed overlay to disappear from the screen before capturing.
+const OVERLAY_CLOSE_DELAY: Duration = Duration::from_millis(150);
+
+/// How often the settings file is checked for edits made outside the app.
+const SETTINGS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often new OCR cache entries are written to disk.
+const OCR_CACHE_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Always-on-top windows that show results. While watch mode runs they are
+/// left out of screen captures, so showing a result over the watched region
+/// never counts as a change of its content.
+const WATCH_EXCLUDED_WINDOWS: [&str; 2] = ["reactive-overlay", "dictionary-lookup"];
 
 pub struct AppMediator {
     // later: pub note_service: NoteService,
@@ -24,19 +86,219 @@ impl AppMediator {
         // later: maybe preload dictionary, etc.
     }
 
-    pub fn send_file_path(app: &AppHandle, path: String) {
-        let image_file = std::fs::read(path).expect("Failed to read test image");
-        let text = manga_ocr(&image_file).expect("Manga OCR failed");
-        println!("Extracted Text: {}", text);
+    /// Handle a finished capture. `origin` is the screen position of the
+    /// capture's top-left corner, `source` what was captured.
+    pub fn process_capture(
+        app: &AppHandle,
+        image: RgbaImage,
+        origin: (i32, i32),
+        source: CaptureSource,
+    ) {
+        let options = app.state::<AppState>().ocr_options();
+        Self::process_capture_with(app, Self::ocr_engine(app), image, origin, &options, source);
+    }
+
+    /// `process_capture` with an explicit engine and OCR settings.
+    pub fn process_capture_with(
+        app: &AppHandle,
+        engine: &dyn OcrEngine,
+        image: RgbaImage,
+        origin: (i32, i32),
+        options: &OcrOptions,
+        source: CaptureSource,
+    ) {
+        Self::keep_capture(app, &image);
+        Self::ocr_image_with(app, engine, image, origin, options, source);
+    }
+
+    /// Handle the captures of a multi-region selection: every region is OCR'd
+    /// on its own and the overlay shows one result per region, in the order
+    /// given (reading order, unless the user reordered the regions).
+    pub fn process_captures(
+        app: &AppHandle,
+        captures: Vec<(RgbaImage, (i32, i32))>,
+        source: CaptureSource,
+    ) {
+        let options = app.state::<AppState>().ocr_options();
+        let engine = Self::ocr_engine(app);
+
+        let (results, recognized): (Vec<_>, Vec<_>) = captures
+            .into_iter()
+            .filter_map(|(image, origin)| {
+                Self::keep_capture(app, &image);
+                let shown = Self::recognize(app, engine, &image, origin, &options)?;
+                Some((shown, (image, origin)))
+            })
+            .unzip();
+
+        if !results.is_empty() {
+            Self::show_and_record(app, results, recognized, source);
+        }
+    }
+
+    /// Opt-in: keep a copy of every capture.
+    fn keep_capture(app: &AppHandle, image: &RgbaImage) {
+        let captures_dir = app.state::<AppState>().captures_dir.lock().unwrap().clone();
+
+        if let Some(dir) = captures_dir {
+            match ScreenshotService.save_capture(image, &dir) {
+                Ok(path) => println!("Saved capture to {}", path.display()),
+                Err(e) => eprintln!("Failed to save capture: {e}"),
+            }
+        }
+    }
+
+    /// OCR an image and show the result in the reactive overlay.
+    pub fn ocr_image(app: &AppHandle, image: RgbaImage, origin: (i32, i32), source: CaptureSource) {
+        let options = app.state::<AppState>().ocr_options();
+        Self::ocr_image_with(app, Self::ocr_engine(app), image, origin, &options, source);
+    }
+
+    /// Engine chosen in the settings.
+    fn ocr_engine(app: &AppHandle) -> &'static dyn OcrEngine {
+        let name = app.state::<AppState>().settings.get().ocr.engine;
+        infra::ocr_engine(&name).unwrap_or(&MangaOcrEngine)
+    }
+
+    /// `ocr_image` with an explicit engine and OCR settings.
+    pub fn ocr_image_with(
+        app: &AppHandle,
+        engine: &dyn OcrEngine,
+        image: RgbaImage,
+        origin: (i32, i32),
+        options: &OcrOptions,
+        source: CaptureSource,
+    ) {
+        if let Some(shown) = Self::recognize(app, engine, &image, origin, options) {
+            Self::show_and_record(app, vec![shown], vec![(image, origin)], source);
+        }
+    }
+
+    /// Run OCR and log the outcome.
+    fn recognize(
+        app: &AppHandle,
+        engine: &dyn OcrEngine,
+        image: &RgbaImage,
+        origin: (i32, i32),
+        options: &OcrOptions,
+    ) -> Option<OverlayResult> {
+        let state = app.state::<AppState>();
+        let result = match run_ocr(engine, Some(&state.ocr_cache), image, origin, options) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("OCR failed: {e:#}");
+                return None;
+            }
+        };
+        println!(
+            "Extracted Text ({}, {} blocks, {} ms): {}",
+            result.engine,
+            result.blocks.len(),
+            result.elapsed_ms,
+            result.text
+        );
 
         //let _result = Self::coordinate_lookup(text.as_str());
         //println!("{:#?}", _result);
 
-        Self::open_ocr_overlay(app, text.as_str());
+        Some(OverlayResult::new(result, None, state.next_result_key()))
+    }
+
+    /// Show OCR results, then add their `captures` (image and origin, in the
+    /// same order) to the history on a background thread, so encoding and
+    /// storing the images never delays the overlay. Watch mode captures are
+    /// only recorded when the history settings ask for it.
+    fn show_and_record(
+        app: &AppHandle,
+        results: Vec<OverlayResult>,
+        captures: Vec<(RgbaImage, (i32, i32))>,
+        source: CaptureSource,
+    ) {
+        Self::open_ocr_results(app, &results);
+
+        let record_watch = app.state::<AppState>().settings.get().history.record_watch;
+        if source == CaptureSource::Watch && !record_watch {
+            return;
+        }
+
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let state = app.state::<AppState>();
+            for (shown, (image, origin)) in results.iter().zip(&captures) {
+                if let Some(id) = Self::record_capture(&app, image, *origin, &shown.result, &source)
+                {
+                    state.set_recorded_capture(shown.key, id);
+                }
+            }
+            Self::prune_history(&app);
+        });
     }
 
-    pub fn coordinate_lookup(text: &str) -> Result<LookupResult, LookupError> {
-        let adapter = DictionaryAdapter::new();
+    /// Add a capture and its OCR result to the history. Returns the
+    /// capture's history id.
+    fn record_capture(
+        app: &AppHandle,
+        image: &RgbaImage,
+        origin: (i32, i32),
+        result: &OcrResult,
+        source: &CaptureSource,
+    ) -> Option<i64> {
+        let state = app.state::<AppState>();
+        state
+            .history
+            .add_capture(&NewCapture {
+                created_at: history::unix_now(),
+                source,
+                origin,
+                image,
+                result,
+            })
+            .map_err(|e| eprintln!("Capture not added to the history: {e:#}"))
+            .ok()
+    }
+
+    /// OCR the image currently on the clipboard.
+    pub fn ocr_clipboard_image(app: &AppHandle) -> Result<(), String> {
+        let clip = app.clipboard().read_image().map_err(|e| e.to_string())?;
+        let image = RgbaImage::from_raw(clip.width(), clip.height(), clip.rgba().to_vec())
+            .ok_or("Clipboard image has an invalid size")?;
+
+        let app = app.clone();
+        std::thread::spawn(move || Self::ocr_image(&app, image, (0, 0), CaptureSource::Clipboard));
+        Ok(())
+    }
+
+    /// OCR a PNG/JPEG/WebP file from disk.
+    pub fn ocr_image_file(app: &AppHandle, path: &Path) -> Result<(), String> {
+        let image = load_image_file(path).map_err(|e| e.to_string())?;
+        let source = CaptureSource::File {
+            path: path.display().to_string(),
+        };
+
+        let app = app.clone();
+        std::thread::spawn(move || Self::ocr_image(&app, image, (0, 0), source));
+        Ok(())
+    }
+
+    /// Let the user pick an image file, then OCR it.
+    pub fn pick_image_file(app: &AppHandle) {
+        let app_handle = app.clone();
+        app.dialog()
+            .file()
+            .add_filter("Images", &IMAGE_EXTENSIONS)
+            .pick_file(move |file| {
+                let Some(path) = file.and_then(|f| f.into_path().ok()) else {
+                    return;
+                };
+                if let Err(e) = Self::ocr_image_file(&app_handle, &path) {
+                    eprintln!("Image OCR failed: {e}");
+                }
+            });
+    }
+
+    pub fn coordinate_lookup(app: &AppHandle, text: &str) -> Result<LookupResult, LookupError> {
+        let endpoint = app.state::<AppState>().settings.get().dictionary.endpoint;
+        let adapter = DictionaryAdapter::new(endpoint);
         let result = adapter.lookup(text)?;
 
         Ok(result)
@@ -46,6 +308,25 @@ impl AppMediator {
         let state = app.state::<AppState>();
         state.enter_selecting_region();
 
+        let monitors = app.available_monitors().unwrap_or_default();
+        let desktop = Self::desktop_rect(&monitors);
+
+        // frozen mode: grab the screen before the overlay exists, so neither
+        // moving content nor the overlay itself ends up in the capture
+        let snapshot = match desktop.filter(|_| state.freeze_screen()) {
+            Some(area) => match ScreenshotService.capture_region(area.x, area.y, area.w, area.h) {
+                Ok(image) => Some(region_selection::Snapshot {
+                    image,
+                    origin: (area.x, area.y),
+                }),
+                Err(e) => {
+                    eprintln!("Snapshot failed, selecting on the live screen: {e:#}");
+                    None
+                }
+            },
+            None => None,
+        };
+
         let win = WebviewWindowBuilder::new(
             app,
             "region-overlay",
@@ -56,18 +337,789 @@ impl AppMediator {
         .background_color(tauri::webview::Color(0, 0, 0, 0)) //remove white flash
         .visible(false)
         .resizable(false)
-        .always_on_top(false)
-        .fullscreen(true)
+        .always_on_top(monitors.len() > 1)
+        .skip_taskbar(true)
+        .fullscreen(monitors.len() <= 1)
         .build()
         .expect("failed to build window");
 
-        win.manage(region_selection::RSController::new());
+        // several displays: span the whole virtual desktop instead of one screen
+        if monitors.len() > 1
+            && let Some(area) = desktop
+        {
+            let _ = win.set_position(PhysicalPosition::new(area.x, area.y));
+            let _ = win.set_size(PhysicalSize::new(area.w, area.h));
+        }
+
+        win.manage(match snapshot {
+            Some(snapshot) => region_selection::RSController::frozen(snapshot),
+            None => region_selection::RSController::new(),
+        });
+    }
+
+    /// Bounding box of all displays, in physical desktop pixels.
+    fn desktop_rect(monitors: &[tauri::Monitor]) -> Option<ScreenRect> {
+        let left = monitors.iter().map(|m| m.position().x).min()?;
+        let top = monitors.iter().map(|m| m.position().y).min()?;
+        let right = monitors
+            .iter()
+            .map(|m| m.position().x + m.size().width as i32)
+            .max()?;
+        let bottom = monitors
+            .iter()
+            .map(|m| m.position().y + m.size().height as i32)
+            .max()?;
+
+        Some(ScreenRect {
+            x: left,
+            y: top,
+            w: (right - left) as u32,
+            h: (bottom - top) as u32,
+        })
+    }
+
+    /// Capture the regions of the last confirmed selection again, without the
+    /// selection overlay.
+    pub fn repeat_last_region(app: &AppHandle) -> Result<(), String> {
+        let state = app.state::<AppState>();
+        if state.is_selecting_region() {
+            return Err("Region selection is active".into());
+        }
+        let rects = state.last_regions();
+        if rects.is_empty() {
+            return Err("No region has been captured yet".into());
+        }
+
+        // the overlay may sit on top of the region; get it out of the shot
+        OCROverlayController::close_overlay(app);
+
+        let app = app.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(OVERLAY_CLOSE_DELAY);
+            let captures = rects
+                .iter()
+                .map(|r| {
+                    let image = ScreenshotService.capture_region(r.x, r.y, r.w, r.h)?;
+                    Ok((image, (r.x, r.y)))
+                })
+                .collect::<anyhow::Result<Vec<_>>>();
+
+            match captures {
+                Ok(mut captures) if captures.len() == 1 => {
+                    let (image, origin) = captures.remove(0);
+                    Self::process_capture(&app, image, origin, CaptureSource::Region)
+                }
+                Ok(captures) => Self::process_captures(&app, captures, CaptureSource::Region),
+                Err(e) => eprintln!("Screenshot failed: {e:#}"),
+            }
+        });
+        Ok(())
+    }
+
+    /// Capture a saved profile with the profile's own OCR settings.
+    pub fn capture_profile(app: &AppHandle, name: &str) -> Result<(), String> {
+        let state = app.state::<AppState>();
+        if state.is_selecting_region() {
+            return Err("Region selection is active".into());
+        }
+        let profile = state
+            .profiles
+            .get(name)
+            .ok_or_else(|| format!("No profile named \"{name}\""))?;
+        let (engine, options) = Self::profile_ocr(&profile)?;
+
+        *state.last_profile.lock().unwrap() = Some(profile.name.clone());
+        OCROverlayController::close_overlay(app);
+
+        let app = app.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(OVERLAY_CLOSE_DELAY);
+
+            match Self::capture_profile_image(&app, &profile) {
+                Ok((image, origin)) => {
+                    let source = CaptureSource::Profile {
+                        name: profile.name.clone(),
+                    };
+                    Self::process_capture_with(&app, engine, image, origin, &options, source)
+                }
+                Err(e) => eprintln!("Profile \"{}\" capture failed: {e}", profile.name),
+            }
+        });
+        Ok(())
+    }
+
+    /// Engine and OCR settings of a profile.
+    fn profile_ocr(
+        profile: &RegionProfile,
+    ) -> Result<(&'static dyn OcrEngine, OcrOptions), String> {
+        let engine = infra::ocr_engine(&profile.engine)
+            .ok_or_else(|| format!("Unknown OCR engine \"{}\"", profile.engine))?;
+        if !engine.languages().contains(&profile.language.as_str()) {
+            return Err(format!(
+                "{} cannot read language \"{}\"",
+                engine.name(),
+                profile.language
+            ));
+        }
+
+        let options = OcrOptions {
+            preprocess: profile.preprocess.clone(),
+            split_blocks: profile.split_blocks,
+        };
+        Ok((engine, options))
     }
 
-    /// Opens the OCR overlay window and injects the text
-    pub fn open_ocr_overlay(app: &AppHandle, text: &str) {
+    /// Capture a profile's window or region. Returns the image and its origin.
+    fn capture_profile_image(
+        app: &AppHandle,
+        profile: &RegionProfile,
+    ) -> Result<(RgbaImage, (i32, i32)), String> {
+        match &profile.window {
+            Some(target) => window::capture_window(target)
+                .map(|(image, bounds)| (image, (bounds.x, bounds.y)))
+                .map_err(|e| format!("{e:#}")),
+            None => {
+                let rect = Self::profile_region(app, profile)?;
+                ScreenshotService
+                    .capture_region(rect.x, rect.y, rect.w, rect.h)
+                    .map(|image| (image, (rect.x, rect.y)))
+                    .map_err(|e| format!("{e:#}"))
+            }
+        }
+    }
+
+    /// The profile's region in desktop coordinates.
+    fn profile_region(app: &AppHandle, profile: &RegionProfile) -> Result<ScreenRect, String> {
+        let Some(name) = &profile.monitor else {
+            return Ok(profile.bounds);
+        };
+
+        let monitor = app
+            .available_monitors()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|m| m.name() == Some(name))
+            .ok_or_else(|| format!("Display \"{name}\" is not connected"))?;
+
+        let origin = monitor.position();
+        Ok(ScreenRect {
+            x: origin.x + profile.bounds.x,
+            y: origin.y + profile.bounds.y,
+            ..profile.bounds
+        })
+    }
+
+    /// Save the last selected region as a new profile with the current OCR
+    /// settings. The region is stored relative to the display it starts on.
+    /// Profiles hold one region, so a multi-region selection is refused.
+    pub fn save_region_profile(app: &AppHandle) -> Result<RegionProfile, String> {
+        let state = app.state::<AppState>();
+        let rect = state.last_region()?;
+        let mut profile = RegionProfile::new(state.profiles.unused_name("Region"), rect);
+
+        let monitor = app
+            .available_monitors()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|m| {
+                let (pos, size) = (m.position(), m.size());
+                (pos.x..pos.x + size.width as i32).contains(&rect.x)
+                    && (pos.y..pos.y + size.height as i32).contains(&rect.y)
+            });
+        if let Some(monitor) = monitor {
+            if let Some(name) = monitor.name() {
+                profile.monitor = Some(name.clone());
+                profile.bounds.x -= monitor.position().x;
+                profile.bounds.y -= monitor.position().y;
+            }
+        }
+
+        Self::save_profile(app, profile)
+    }
+
+    /// Save the current window capture target as a new profile.
+    pub fn save_window_profile(app: &AppHandle) -> Result<RegionProfile, String> {
+        let state = app.state::<AppState>();
+        let target = state
+            .window_target
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("No window selected for capture")?;
+
+        let mut profile = RegionProfile::new(
+            state.profiles.unused_name(&target.title),
+            ScreenRect::default(),
+        );
+        profile.window = Some(target);
+
+        Self::save_profile(app, profile)
+    }
+
+    fn save_profile(app: &AppHandle, mut profile: RegionProfile) -> Result<RegionProfile, String> {
+        let options = app.state::<AppState>().ocr_options();
+        profile.preprocess = options.preprocess;
+        profile.preprocess.debug_dir = None;
+        profile.split_blocks = options.split_blocks;
+
+        Self::store_profile(app, &profile)?;
+        println!("Saved profile \"{}\"", profile.name);
+        Ok(profile)
+    }
+
+    /// Bind profile `name` to `hotkey`, or unbind it when `hotkey` is empty.
+    pub fn set_profile_hotkey(
+        app: &AppHandle,
+        name: &str,
+        hotkey: &str,
+    ) -> Result<RegionProfile, String> {
+        let mut profile = app
+            .state::<AppState>()
+            .profiles
+            .get(name)
+            .ok_or_else(|| format!("No profile named \"{name}\""))?;
+        let hotkey = hotkey.trim();
+        profile.hotkey = (!hotkey.is_empty()).then(|| hotkey.to_string());
+
+        Self::store_profile(app, &profile)?;
+        Ok(profile)
+    }
+
+    /// Add or replace `profile` in the profile store and move the global
+    /// shortcut of the profile it replaces to its hotkey. A hotkey that is
+    /// invalid or already taken leaves the store and shortcuts unchanged.
+    fn store_profile(app: &AppHandle, profile: &RegionProfile) -> Result<(), String> {
+        let state = app.state::<AppState>();
+        let old = state
+            .profiles
+            .get(&profile.name)
+            .and_then(|p| p.hotkey)
+            .and_then(|h| h.parse::<Shortcut>().ok());
+        let new = match &profile.hotkey {
+            Some(hotkey) => Some(
+                hotkey
+                    .parse::<Shortcut>()
+                    .map_err(|e| format!("Invalid hotkey \"{hotkey}\": {e}"))?,
+            ),
+            None => None,
+        };
+
+        let shortcuts = app.global_shortcut();
+        let register = new.filter(|shortcut| Some(*shortcut) != old);
+        if let Some(shortcut) = register {
+            let hotkey = profile.hotkey.as_deref().unwrap_or_default();
+            if let Some(action) = state.hotkeys.lock().unwrap().action(&shortcut) {
+                return Err(format!(
+                    "Hotkey \"{hotkey}\" is already used by \"{}\"",
+                    action.label()
+                ));
+            }
+            if let Some(other) = Self::profile_for_shortcut(app, &shortcut) {
+                return Err(format!(
+                    "Hotkey \"{hotkey}\" is already used by profile \"{other}\""
+                ));
+            }
+            shortcuts.register(shortcut).map_err(|e| {
+                format!("Hotkey \"{hotkey}\" may be taken by another application: {e}")
+            })?;
+        }
+
+        if let Err(e) = state.profiles.save(profile.clone()) {
+            if let Some(shortcut) = register {
+                let _ = shortcuts.unregister(shortcut);
+            }
+            return Err(e.to_string());
+        }
+
+        if let Some(shortcut) = old
+            && new != old
+        {
+            let _ = shortcuts.unregister(shortcut);
+        }
+        Ok(())
+    }
+
+    /// Register the hotkeys of all profiles. Invalid or already taken
+    /// hotkeys are reported and skipped.
+    pub fn register_profile_hotkeys(app: &AppHandle) {
+        for profile in app.state::<AppState>().profiles.list() {
+            let Some(hotkey) = &profile.hotkey else {
+                continue;
+            };
+
+            let registered = hotkey
+                .parse::<Shortcut>()
+                .map_err(|e| e.to_string())
+                .and_then(|shortcut| {
+                    app.global_shortcut()
+                        .register(shortcut)
+                        .map_err(|e| e.to_string())
+                });
+            if let Err(e) = registered {
+                eprintln!(
+                    "Hotkey \"{hotkey}\" for profile \"{}\" not registered: {e}",
+                    profile.name
+                );
+            }
+        }
+    }
+
+    /// Name of the profile bound to `shortcut`, if any.
+    pub fn profile_for_shortcut(app: &AppHandle, shortcut: &Shortcut) -> Option<String> {
+        app.state::<AppState>()
+            .profiles
+            .list()
+            .into_iter()
+            .find(|p| {
+                p.hotkey
+                    .as_deref()
+                    .and_then(|h| h.parse::<Shortcut>().ok())
+                    .is_some_and(|h| &h == shortcut)
+            })
+            .map(|p| p.name)
+    }
+
+    /// Keep capturing the last captured profile (or else the last region, if
+    /// it was a single one) and OCR it whenever its content changes. Stopped
+    /// by `stop_watch`.
+    pub fn start_watch(app: &AppHandle) -> Result<(), String> {
+        let state = app.state::<AppState>();
+        let last_profile = state.last_profile.lock().unwrap().clone();
+
+        let source = match last_profile {
+            Some(name) => {
+                let profile = state
+                    .profiles
+                    .get(&name)
+                    .ok_or_else(|| format!("No profile named \"{name}\""))?;
+                let (engine, options) = Self::profile_ocr(&profile)?;
+                WatchSource::Profile(profile, engine, options)
+            }
+            None if state.last_regions().is_empty() => {
+                return Err("Capture a region or profile first".into());
+            }
+            None => WatchSource::Region(state.last_region()?),
+        };
+
+        let generation = state.begin_watch();
+        let mut detector = ChangeDetector::new(*state.watch_options.lock().unwrap());
+        Self::exclude_from_captures(app, true);
+        println!("Watch mode started");
+        let _ = app.emit("watch-changed", true);
+
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let state = app.state::<AppState>();
+            // only report a failure once until capturing works again
+            let mut last_error = None;
+
+            while state.watch_active(generation) {
+                match source.capture(&app) {
+                    Ok((image, origin)) => {
+                        last_error = None;
+                        if detector.update(&image) {
+                            source.process(&app, image, origin);
+                        }
+                    }
+                    Err(e) => {
+                        if last_error.as_ref() != Some(&e) {
+                            eprintln!("Watch capture failed: {e}");
+                        }
+                        last_error = Some(e);
+                    }
+                }
+
+                let interval = state.watch_options.lock().unwrap().interval_ms;
+                std::thread::sleep(Duration::from_millis(interval));
+            }
+            println!("Watch mode stopped");
+        });
+        Ok(())
+    }
+
+    pub fn stop_watch(app: &AppHandle) {
+        app.state::<AppState>().end_watch();
+        Self::exclude_from_captures(app, false);
+        let _ = app.emit("watch-changed", false);
+    }
+
+    /// Hide the open `WATCH_EXCLUDED_WINDOWS` from screen captures, or show
+    /// them again. Windows opened later take this from `is_watching`.
+    fn exclude_from_captures(app: &AppHandle, excluded: bool) {
+        for label in WATCH_EXCLUDED_WINDOWS {
+            if let Some(win) = app.get_webview_window(label) {
+                let _ = win.set_content_protected(excluded);
+            }
+        }
+    }
+
+    /// Validate, persist and apply new settings. Hotkeys that cannot be
+    /// registered (e.g. taken by another application) reject the update.
+    pub fn update_settings(
+        app: &AppHandle,
+        settings: Settings,
+    ) -> Result<Settings, Vec<FieldError>> {
+        let state = app.state::<AppState>();
+        let errors = settings.validate();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let current = state.settings.get();
+        let rebind = current.hotkeys != settings.hotkeys;
+        if rebind {
+            Self::rebind_hotkeys(app, &settings.hotkeys)?;
+        }
+
+        let old = state.settings.update(settings.clone()).inspect_err(|_| {
+            if rebind {
+                let _ = Self::rebind_hotkeys(app, &current.hotkeys);
+            }
+        })?;
+        Self::apply_settings(app, &old, &settings);
+        Ok(settings)
+    }
+
+    /// Change some settings and apply them, e.g. from a tray toggle.
+    pub fn change_settings(
+        app: &AppHandle,
+        change: impl FnOnce(&mut Settings),
+    ) -> Result<Settings, Vec<FieldError>> {
+        let mut settings = app.state::<AppState>().settings.get();
+        change(&mut settings);
+        Self::update_settings(app, settings)
+    }
+
+    /// Bring the running app in line with changed settings. Hotkeys are
+    /// rebound by the callers; everything else (endpoint, engine, overlay
+    /// look, window sizes) is read whenever it is used and needs nothing here.
+    fn apply_settings(app: &AppHandle, old: &Settings, new: &Settings) {
+        let state = app.state::<AppState>();
+
+        if old.ocr.device != new.ocr.device {
+            println!("OCR device change takes effect after a restart");
+        }
+        if old.ocr.preprocess != new.ocr.preprocess {
+            state.set_preprocess_preset(new.ocr.preprocess);
+            println!("Preprocessing preset: {}", new.ocr.preprocess.label());
+        }
+        state.set_split_text_blocks(new.ocr.split_blocks);
+        if old.captures_dir != new.captures_dir {
+            match &new.captures_dir {
+                Some(dir) => println!("Saving captures to {}", dir.display()),
+                None => println!("Captures are no longer saved"),
+            }
+        }
+        *state.captures_dir.lock().unwrap() = new.captures_dir.clone();
+
+        let _ = app.emit("settings-changed", new);
+    }
+
+    /// Re-apply the settings file whenever it is edited outside the app.
+    pub fn watch_settings(app: &AppHandle) {
+        let app = app.clone();
+
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(SETTINGS_POLL_INTERVAL);
+
+                match app.state::<AppState>().settings.reload_if_changed() {
+                    Ok(Some((old, new))) => {
+                        println!("Settings reloaded");
+                        if old.hotkeys != new.hotkeys {
+                            if let Err(errors) = Self::rebind_hotkeys(&app, &new.hotkeys) {
+                                for e in errors {
+                                    eprintln!("Hotkeys not changed: {}: {}", e.field, e.message);
+                                }
+                            }
+                        }
+                        Self::apply_settings(&app, &old, &new);
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Settings file not applied: {e:#}"),
+                }
+            }
+        });
+    }
+
+    /// Write the OCR cache to disk every `OCR_CACHE_FLUSH_INTERVAL` while it
+    /// has changes; quitting from the tray writes the rest.
+    pub fn flush_ocr_cache_periodically(app: &AppHandle) {
+        let app = app.clone();
+
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(OCR_CACHE_FLUSH_INTERVAL);
+                app.state::<AppState>().ocr_cache.flush();
+            }
+        });
+    }
+
+    /// Register the global hotkeys from the settings at startup. A hotkey
+    /// that fails is logged and skipped.
+    pub fn register_hotkeys(app: &AppHandle, hotkeys: &HotkeySettings) {
+        let (registry, errors) = HotkeyRegistry::parse(hotkeys);
+        for e in errors {
+            eprintln!("Hotkey not registered: {}: {}", e.field, e.message);
+        }
+
+        for (action, shortcut) in registry.bindings() {
+            if let Err(e) = app.global_shortcut().register(shortcut) {
+                eprintln!("Hotkey for \"{}\" not registered: {e}", action.label());
+            }
+        }
+        *app.state::<AppState>().hotkeys.lock().unwrap() = registry;
+    }
+
+    /// Replace the registered hotkeys with `hotkeys`. Either all of them are
+    /// registered or the previous ones are restored and every conflict (with
+    /// each other, a profile hotkey or another application) is returned.
+    fn rebind_hotkeys(app: &AppHandle, hotkeys: &HotkeySettings) -> Result<(), Vec<FieldError>> {
+        let (registry, mut errors) = HotkeyRegistry::parse(hotkeys);
+        for (action, shortcut) in registry.bindings() {
+            if let Some(name) = Self::profile_for_shortcut(app, &shortcut) {
+                errors.push(FieldError::new(
+                    action.field(),
+                    format!("already used by profile \"{name}\""),
+                ));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let shortcuts = app.global_shortcut();
+        let state = app.state::<AppState>();
+        let mut current = state.hotkeys.lock().unwrap();
+        for (_, shortcut) in current.bindings() {
+            let _ = shortcuts.unregister(shortcut);
+        }
+
+        let mut registered = Vec::new();
+        for (action, shortcut) in registry.bindings() {
+            match shortcuts.register(shortcut) {
+                Ok(()) => registered.push(shortcut),
+                Err(e) => errors.push(FieldError::new(
+                    action.field(),
+                    format!("could not be registered, it may be taken by another application: {e}"),
+                )),
+            }
+        }
+
+        if errors.is_empty() {
+            *current = registry;
+            return Ok(());
+        }
+
+        for shortcut in registered {
+            let _ = shortcuts.unregister(shortcut);
+        }
+        for (_, shortcut) in current.bindings() {
+            let _ = shortcuts.register(shortcut);
+        }
+        Err(errors)
+    }
+
+    /// Action bound to `shortcut` in the hotkey settings.
+    pub fn hotkey_action(app: &AppHandle, shortcut: &Shortcut) -> Option<HotkeyAction> {
+        app.state::<AppState>()
+            .hotkeys
+            .lock()
+            .unwrap()
+            .action(shortcut)
+    }
+
+    pub fn run_hotkey_action(app: &AppHandle, action: HotkeyAction) {
+        let state = app.state::<AppState>();
+
+        let result = match action {
+            HotkeyAction::Capture => {
+                // allow user to enter RS if they are not in it
+                if !state.is_selecting_region() {
+                    OCROverlayController::close_overlay(app);
+                    state.enter_selecting_region();
+                    println!("Region Selection activated...");
+
+                    Self::start_region_capture(app);
+                }
+                Ok(())
+            }
+            HotkeyAction::RepeatLastRegion => Self::repeat_last_region(app),
+            HotkeyAction::ToggleWatch if state.is_watching() => {
+                Self::stop_watch(app);
+                Ok(())
+            }
+            HotkeyAction::ToggleWatch => Self::start_watch(app),
+            HotkeyAction::OpenHistory => {
+                Self::open_history_window(app);
+                Ok(())
+            }
+            HotkeyAction::CloseOverlays => {
+                Self::close_overlays(app);
+                Ok(())
+            }
+            HotkeyAction::LookupClipboard => Self::lookup_clipboard_text(app),
+        };
+
+        if let Err(e) = result {
+            eprintln!("{} failed: {e}", action.label());
+        }
+    }
+
+    /// Close the OCR overlays and the lookup window.
+    pub fn close_overlays(app: &AppHandle) {
+        OCROverlayController::close_overlay(app);
+        if let Some(win) = app.get_webview_window("dictionary-lookup") {
+            let _ = win.destroy();
+        }
+    }
+
+    /// Settings window, opened from the tray.
+    pub fn open_settings_window(app: &AppHandle) {
+        if let Some(win) = app.get_webview_window("settings") {
+            let _ = win.set_focus();
+            return;
+        }
+
+        let _ = WebviewWindowBuilder::new(
+            app,
+            "settings",
+            WebviewUrl::App("settings/settings.html".into()),
+        )
+        .decorations(true)
+        .resizable(true)
+        .maximizable(false)
+        .inner_size(480.0, 640.0)
+        .title("UMOD Settings")
+        .build()
+        .map_err(|e| eprintln!("Failed to open settings: {e}"));
+    }
+
+    /// Window list for picking a capture target.
+    pub fn open_window_picker(app: &AppHandle) {
+        if let Some(win) = app.get_webview_window("window-picker") {
+            let _ = win.set_focus();
+            return;
+        }
+
+        let _ = WebviewWindowBuilder::new(
+            app,
+            "window-picker",
+            WebviewUrl::App("overlays/window_picker.html".into()),
+        )
+        .decorations(true)
+        .always_on_top(true)
+        .resizable(true)
+        .maximizable(false)
+        .inner_size(420.0, 480.0)
+        .title("Capture Window")
+        .build()
+        .map_err(|e| eprintln!("Failed to open window picker: {e}"));
+    }
+
+    /// Make `id` the window capture target. With `pick_area`, the region
+    /// selection overlay opens first and the selected rectangle becomes the
+    /// captured part of the window.
+    pub fn select_capture_window(app: &AppHandle, id: u32, pick_area: bool) -> Result<(), String> {
+        let info = window::find_window(id).map_err(|e| e.to_string())?;
+        let target = WindowTarget {
+            id,
+            title: info.title,
+            area: None,
+        };
+        let state = app.state::<AppState>();
+
+        if pick_area {
+            if state.is_selecting_region() {
+                return Err("Region selection is already active".into());
+            }
+            *state.pending_window_area.lock().unwrap() = Some(target);
+            OCROverlayController::close_overlay(app);
+            Self::start_region_capture(app);
+            return Ok(());
+        }
+
+        *state.window_target.lock().unwrap() = Some(target);
+        Self::capture_window_target(app)
+    }
+
+    /// Called when the region selection finishes while a sub-area of
+    /// `target` is being picked: store `rect` relative to that window and
+    /// capture it.
+    pub fn finish_window_area(app: &AppHandle, mut target: WindowTarget, rect: ScreenRect) {
+        let state = app.state::<AppState>();
+        let bounds = match window::find_window(target.id) {
+            Ok(info) => info.bounds,
+            Err(e) => {
+                eprintln!("Window area not saved: {e}");
+                return;
+            }
+        };
+
+        target.area = Some(WindowArea {
+            x: (rect.x - bounds.x).max(0) as u32,
+            y: (rect.y - bounds.y).max(0) as u32,
+            w: rect.w,
+            h: rect.h,
+        });
+        println!("Window capture target: \"{}\" {:?}", target.title, target.area);
+        *state.window_target.lock().unwrap() = Some(target);
+
+        // the selection overlay was just closed; keep it out of the shot
+        std::thread::sleep(OVERLAY_CLOSE_DELAY);
+        if let Err(e) = Self::capture_window_target(app) {
+            eprintln!("Window capture failed: {e}");
+        }
+    }
+
+    /// Capture the current window target (wherever it moved) and OCR it.
+    pub fn capture_window_target(app: &AppHandle) -> Result<(), String> {
+        let target = app
+            .state::<AppState>()
+            .window_target
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("No window selected for capture")?;
+
+        let app = app.clone();
+        std::thread::spawn(move || match window::capture_window(&target) {
+            Ok((image, bounds)) => {
+                let source = CaptureSource::Window {
+                    title: target.title.clone(),
+                };
+                Self::process_capture(&app, image, (bounds.x, bounds.y), source)
+            }
+            Err(e) => eprintln!("Window capture failed: {e:#}"),
+        });
+        Ok(())
+    }
+
+    /// Opens the OCR overlay window and injects the result of history
+    /// capture `capture_id`. An overlay that is already open is refreshed in
+    /// place.
+    pub fn open_ocr_overlay(app: &AppHandle, result: OcrResult, capture_id: Option<i64>) {
+        let key = app.state::<AppState>().next_result_key();
+        Self::open_ocr_results(app, &[OverlayResult::new(result, capture_id, key)]);
+    }
+
+    /// `open_ocr_overlay` for several results, shown one section per region.
+    pub fn open_ocr_results(app: &AppHandle, results: &[OverlayResult]) {
+        let text = results
+            .iter()
+            .map(|r| r.result.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
         let js_safe_text = text.replace('`', "\\`");
+        let json = serde_json::to_string(results).expect("Failed to serialize OcrResult");
 
+        if let Some(win) = app.get_webview_window("reactive-overlay") {
+            let _ = win.eval(&format!("window.__setOcrResults({json});"));
+            let _ = win.show();
+            return;
+        }
+
+        let size = app.state::<AppState>().settings.get().windows.ocr_overlay;
         let win = WebviewWindowBuilder::new(
             app,
             "reactive-overlay",
@@ -81,22 +1133,37 @@ impl AppMediator {
         .maximizable(false)
         .fullscreen(false)
         .always_on_top(true)
-        .inner_size(594.0, 121.0)
+        .content_protected(app.state::<AppState>().is_watching())
+        .inner_size(size.width as f64, size.height as f64)
         .position(0.0, 0.0)
         .title("Captured Text")
-        .initialization_script(&format!(r#"window.__OCR_TEXT = `{}`;"#, js_safe_text))
+        .initialization_script(&format!(
+            r#"window.__OCR_TEXT = `{}`; window.__OCR_RESULTS = {json};"#,
+            js_safe_text
+        ))
         .build()
         .expect("Failed to create OCR overlay");
 
         let app_handle = app.clone();
 
-        win.on_window_event(move |event| {
-            if matches!(event, tauri::WindowEvent::Destroyed) {
+        win.on_window_event(move |event| match event {
+            tauri::WindowEvent::Destroyed => {
                 //close active lookup
                 if let Some(win) = app_handle.get_webview_window("dictionary-lookup") {
                     let _ = win.close();
                 }
             }
+
+            // image dropped onto the overlay -> OCR it into the same overlay
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                if let Some(path) = paths.first() {
+                    if let Err(e) = Self::ocr_image_file(&app_handle, path) {
+                        eprintln!("Image OCR failed: {e}");
+                    }
+                }
+            }
+
+            _ => {}
         });
         win.manage(OCROverlayController::new());
     }
@@ -110,6 +1177,8 @@ impl AppMediator {
     "#
         );
 
+        let size = app.state::<AppState>().settings.get().windows.lookup;
+
         // This is the query that this window is showing.
         let window_query = lookup.term_entries.query.clone();
 
@@ -129,10 +1198,11 @@ impl AppMediator {
         .transparent(true)
         .decorations(true)
         .always_on_top(true)
+        .content_protected(app.state::<AppState>().is_watching())
         .resizable(false)
         .maximizable(false)
         .fullscreen(false)
-        .inner_size(420.0, 520.0)
+        .inner_size(size.width as f64, size.height as f64)
         .title("Results")
         .initialization_script(&init);
 
@@ -176,10 +1246,413 @@ impl AppMediator {
         });
     }
 
-    /// One-shot: lookup and open UI window
-    pub fn lookup_and_open(app: &AppHandle, text: &str) -> Result<(), LookupError> {
-        let result = Self::coordinate_lookup(text)?;
+    /// Look up `text` in the lookup window, unless that window already shows
+    /// it. `sentence` and `source` describe where the text came from for the
+    /// lookup history; `capture` is the history id of the capture it was
+    /// read from, for the card's screenshot.
+    pub fn lookup_text(
+        app: &AppHandle,
+        text: String,
+        sentence: Option<String>,
+        source: String,
+        capture: Option<i64>,
+    ) {
+        let state = app.state::<AppState>();
+
+        // Decide what to do (and update state) under lock,
+        // but do NOT close windows while holding the lock.
+        let should_close_existing = {
+            let mut current = state.current_lookup.lock().unwrap();
+
+            // Same lookup -> do nothing (or focus existing window)
+            if current.as_deref() == Some(text.as_str()) {
+                // Optional: just focus existing window
+                if let Some(win) = app.get_webview_window("dictionary-lookup") {
+                    let _ = win.set_focus();
+                    let _ = win.show();
+                }
+                return;
+            }
+
+            // New lookup -> set current now (prevents duplicate lookups immediately)
+            *current = Some(text.clone());
+            true
+        };
+
+        if should_close_existing {
+            if let Some(win) = app.get_webview_window("dictionary-lookup") {
+                let _ = win.close();
+            }
+        }
+
+        // fire-and-forget
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let _ = Self::lookup_and_open(&app, &text, sentence.as_deref(), &source, capture);
+        });
+    }
+
+    /// Look up a selection from the OCR overlay, made in the text `context`
+    /// of history capture `capture`, or of the shown result `result_key`
+    /// whose capture was recorded after the overlay opened.
+    pub fn lookup_overlay_text(
+        app: &AppHandle,
+        text: String,
+        context: Option<&str>,
+        capture: Option<i64>,
+        result_key: Option<usize>,
+    ) {
+        let state = app.state::<AppState>();
+        let capture = capture.or_else(|| result_key.and_then(|key| state.recorded_capture(key)));
+        let sentence = context.and_then(|c| history::sentence_around(c, &text));
+        let source = capture
+            .and_then(|id| state.history.capture(id).ok().flatten())
+            .map_or_else(
+                || "OCR overlay".to_string(),
+                |entry| entry.source.to_string(),
+            );
+        Self::lookup_text(app, text, sentence, source, capture);
+    }
+
+    /// Look up the text on the clipboard.
+    pub fn lookup_clipboard_text(app: &AppHandle) -> Result<(), String> {
+        let text = app.clipboard().read_text().map_err(|e| e.to_string())?;
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("Clipboard has no text".into());
+        }
+
+        Self::lookup_text(app, text.to_string(), None, "Clipboard text".into(), None);
+        Ok(())
+    }
+
+    /// Add a finished lookup to the history and prune old entries.
+    fn record_lookup(
+        app: &AppHandle,
+        query: &str,
+        result: &LookupResult,
+        sentence: Option<&str>,
+        source: &str,
+        capture: Option<i64>,
+    ) {
+        let state = app.state::<AppState>();
+        let recorded = serde_json::to_string(result)
+            .map_err(anyhow::Error::from)
+            .and_then(|result_json| {
+                state.history.add_lookup(&NewLookup {
+                    created_at: history::unix_now(),
+                    query,
+                    headword: result.headword(),
+                    sentence,
+                    source,
+                    result_json: &result_json,
+                    capture_id: capture,
+                })
+            });
+        if let Err(e) = recorded {
+            eprintln!("Lookup not added to the history: {e:#}");
+        }
+        Self::prune_history(app);
+    }
+
+    /// Apply the history retention settings.
+    pub fn prune_history(app: &AppHandle) {
+        let state = app.state::<AppState>();
+        let limits = state.settings.get().history;
+        if let Err(e) = state.history.prune(&limits, history::unix_now()) {
+            eprintln!("History not pruned: {e:#}");
+        }
+    }
+
+    /// Open a lookup from the history again, as it was looked up.
+    pub fn reopen_lookup(app: &AppHandle, id: i64) -> Result<(), String> {
+        let state = app.state::<AppState>();
+        let json = state
+            .history
+            .lookup_result(id)
+            .map_err(|e| format!("{e:#}"))?
+            .ok_or("Lookup not found in the history")?;
+        let result: LookupResult = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        let entry = state
+            .history
+            .lookup(id)
+            .map_err(|e| format!("{e:#}"))?
+            .ok_or("Lookup not found in the history")?;
+
+        if let Some(win) = app.get_webview_window("dictionary-lookup") {
+            let _ = win.destroy();
+        }
+        Self::set_lookup_note(app, &result, entry.sentence.as_deref(), entry.capture_id);
+        *state.current_lookup.lock().unwrap() = Some(result.term_entries.query.clone());
+        Self::open_dictionary_lookup_window(app, &result);
+        Ok(())
+    }
+
+    /// Show a capture from the history in the OCR overlay again, with its
+    /// (possibly edited) text.
+    pub fn reopen_capture(app: &AppHandle, id: i64) -> Result<(), String> {
+        let state = app.state::<AppState>();
+        let result = state
+            .history
+            .capture_result(id)
+            .map_err(|e| format!("{e:#}"))?
+            .ok_or("Capture not found in the history")?;
+
+        Self::open_ocr_overlay(app, result, Some(id));
+        Ok(())
+    }
+
+    /// OCR a capture from the history again with other settings, store the
+    /// new result in place of the old one and show it in the OCR overlay.
+    /// Blocks while OCR runs.
+    pub fn rerun_capture(
+        app: &AppHandle,
+        id: i64,
+        engine: &str,
+        preset: PreprocessPreset,
+        split_blocks: bool,
+    ) -> Result<CaptureEntry, String> {
+        let engine =
+            infra::ocr_engine(engine).ok_or_else(|| format!("Unknown OCR engine \"{engine}\""))?;
+        let state = app.state::<AppState>();
+        let entry = state
+            .history
+            .capture(id)
+            .map_err(|e| format!("{e:#}"))?
+            .ok_or("Capture not found in the history")?;
+        let image = state
+            .history
+            .capture_image(id)
+            .map_err(|e| format!("{e:#}"))?
+            .ok_or("Capture not found in the history")?;
+
+        let options = OcrOptions {
+            preprocess: PreprocessPipeline::from_preset(preset),
+            split_blocks,
+        };
+        let origin = (entry.region.x, entry.region.y);
+        let result = run_ocr(engine, Some(&state.ocr_cache), &image, origin, &options)
+            .map_err(|e| format!("OCR failed: {e:#}"))?;
+
+        state
+            .history
+            .set_capture_result(id, &result)
+            .map_err(|e| format!("{e:#}"))?;
+        Self::open_ocr_overlay(app, result.clone(), Some(id));
+
+        Ok(CaptureEntry {
+            engine: result.engine,
+            text: result.text,
+            edited: false,
+            ..entry
+        })
+    }
+
+    /// History window, opened from the tray or its hotkey.
+    pub fn open_history_window(app: &AppHandle) {
+        if let Some(win) = app.get_webview_window("history") {
+            let _ = win.set_focus();
+            return;
+        }
+
+        let _ = WebviewWindowBuilder::new(
+            app,
+            "history",
+            WebviewUrl::App("history/history.html".into()),
+        )
+        .decorations(true)
+        .resizable(true)
+        .maximizable(true)
+        .inner_size(640.0, 560.0)
+        .title("UMOD History")
+        .build()
+        .map_err(|e| eprintln!("Failed to open history: {e}"));
+    }
+
+    /// One-shot: lookup and open UI window, and add the lookup to the history
+    pub fn lookup_and_open(
+        app: &AppHandle,
+        text: &str,
+        sentence: Option<&str>,
+        source: &str,
+        capture: Option<i64>,
+    ) -> Result<(), LookupError> {
+        let result = Self::coordinate_lookup(app, text)?;
+        Self::record_lookup(app, text, &result, sentence, source, capture);
+        Self::set_lookup_note(app, &result, sentence, capture);
         Self::open_dictionary_lookup_window(app, &result);
         Ok(())
     }
+
+    /// Keep what an Anki card of `result` would show, and the word to save
+    /// to the word list.
+    fn set_lookup_note(
+        app: &AppHandle,
+        result: &LookupResult,
+        sentence: Option<&str>,
+        capture: Option<i64>,
+    ) {
+        let state = app.state::<AppState>();
+        let note = Self::note_content(app, result, sentence, capture);
+        *state.lookup_note.lock().unwrap() = note;
+
+        let headword = result
+            .term_entries
+            .entries
+            .first()
+            .and_then(|e| e.headwords.first());
+        let word = headword.and_then(|headword| {
+            Some(NewWord {
+                term: headword.term.clone(),
+                reading: headword.reading.clone(),
+                sentence: sentence.map(str::to_string),
+                entries_json: serde_json::to_string(&result.term_entries).ok()?,
+            })
+        });
+        *state.lookup_word.lock().unwrap() = word;
+    }
+
+    /// Anki card content of `result`, with the image of history capture
+    /// `capture` as its screenshot when it is still there.
+    fn note_content(
+        app: &AppHandle,
+        result: &LookupResult,
+        sentence: Option<&str>,
+        capture: Option<i64>,
+    ) -> Option<NoteContent> {
+        let state = app.state::<AppState>();
+        NoteContent::from_lookup(result, sentence).map(|note| NoteContent {
+            screenshot: capture.and_then(|id| state.history.capture_png(id).ok().flatten()),
+            ..note
+        })
+    }
+
+    /// Add a card for the lookup in the lookup window to Anki. Blocks on
+    /// AnkiConnect.
+    pub fn add_anki_card(app: &AppHandle) -> Result<AddOutcome, String> {
+        let state = app.state::<AppState>();
+        let note = state
+            .lookup_note
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or("No dictionary entry to make a card from")?;
+        let settings = state.settings.get().anki;
+
+        AnkiConnect::new(settings.url.as_str())
+            .add_card(&settings, &note)
+            .map_err(|e| format!("{e:#}"))
+    }
+
+    /// Save the word in the lookup window to the word list. Returns whether
+    /// it was not in the list yet.
+    pub fn save_lookup_word(app: &AppHandle) -> Result<bool, String> {
+        let state = app.state::<AppState>();
+        let word = state.lookup_word.lock().unwrap();
+        let word = word.as_ref().ok_or("No dictionary entry to save")?;
+        state
+            .words
+            .save_word(word, history::unix_now())
+            .map_err(|e| format!("{e:#}"))
+    }
+
+    /// Word review window, opened from the tray.
+    pub fn open_review_window(app: &AppHandle) {
+        if let Some(win) = app.get_webview_window("review") {
+            let _ = win.set_focus();
+            return;
+        }
+
+        let _ =
+            WebviewWindowBuilder::new(app, "review", WebviewUrl::App("review/review.html".into()))
+                .decorations(true)
+                .resizable(true)
+                .maximizable(true)
+                .inner_size(560.0, 640.0)
+                .title("UMOD Review")
+                .build()
+                .map_err(|e| eprintln!("Failed to open review: {e}"));
+    }
+
+    /// Import the words of the word list file at `path`, with `status` for
+    /// words the file gives none.
+    pub fn import_word_list(
+        app: &AppHandle,
+        path: &Path,
+        status: WordStatus,
+    ) -> Result<ImportCounts, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let words = words::parse_word_list(&text, status);
+        app.state::<AppState>()
+            .words
+            .import_words(&words, history::unix_now())
+            .map_err(|e| format!("{e:#}"))
+    }
+
+    /// Import the words studied in Anki: field `field` of the cards matching
+    /// `query`, known once their interval is long enough. Blocks on
+    /// AnkiConnect.
+    pub fn import_anki_words(
+        app: &AppHandle,
+        query: &str,
+        field: &str,
+    ) -> Result<ImportCounts, String> {
+        let state = app.state::<AppState>();
+        let url = state.settings.get().anki.url;
+        let words: Vec<_> = AnkiConnect::new(url)
+            .studied_words(query, field)
+            .map_err(|e| format!("{e:#}"))?
+            .into_iter()
+            .map(|(term, days)| ImportedWord::studied(term, days))
+            .collect();
+        state
+            .words
+            .import_words(&words, history::unix_now())
+            .map_err(|e| format!("{e:#}"))
+    }
+
+    /// `text` cut into words, each with the status of its lemma in the word
+    /// list, for coloring the OCR overlay. Blocks on the dictionary backend.
+    pub fn word_tokens(app: &AppHandle, text: &str) -> Result<Vec<WordToken>, String> {
+        let state = app.state::<AppState>();
+        let endpoint = state.settings.get().dictionary.endpoint;
+        let segments = DictionaryAdapter::new(endpoint)
+            .segment(text, &state.lemmas)
+            .map_err(|e| format!("Dictionary backend: {e}"))?;
+        state
+            .words
+            .word_tokens(segments)
+            .map_err(|e| format!("{e:#}"))
+    }
+
+    /// Write cards for the history lookups `ids` to an Anki deck package at
+    /// `path`. Lookups that found nothing are left out. Returns how many
+    /// notes were written.
+    pub fn export_apkg(app: &AppHandle, ids: &[i64], path: &Path) -> Result<usize, String> {
+        let state = app.state::<AppState>();
+        let mut notes = Vec::new();
+
+        for &id in ids {
+            let entry = state.history.lookup(id).map_err(|e| format!("{e:#}"))?;
+            let json = state
+                .history
+                .lookup_result(id)
+                .map_err(|e| format!("{e:#}"))?;
+            let (Some(entry), Some(json)) = (entry, json) else {
+                continue;
+            };
+            let result: LookupResult = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            notes.extend(Self::note_content(
+                app,
+                &result,
+                entry.sentence.as_deref(),
+                entry.capture_id,
+            ));
+        }
+
+        if notes.is_empty() {
+            return Err("None of the selected lookups has a dictionary entry".into());
+        }
+        anki::write_apkg(path, &state.settings.get().anki, &notes).map_err(|e| format!("{e:#}"))
+    }
 }
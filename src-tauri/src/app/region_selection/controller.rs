@@ -1,9 +1,11 @@
-use tauri::{AppHandle, Emitter, Manager, State};
-use winit::event::{ElementState, MouseButton};
+use screenshots::image::imageops;
+use tauri::ipc::Response;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow};
 
-use super::fsm::SelectionPhase;
+use super::fsm::{ArrowKey, ButtonState, MouseButton, SelectionPhase, WindowSize};
 use super::RSController;
 use crate::app::AppMediator;
+use crate::shared::models::ocr::{CaptureSource, ScreenRect};
 use crate::state::AppState;
 
 #[tauri::command]
@@ -17,24 +19,26 @@ pub fn rs_cursor(app: AppHandle, rs: State<'_, RSController>, x: f64, y: f64) {
 
 #[tauri::command]
 pub fn rs_mousedown(app: AppHandle, rs: State<'_, RSController>, button: String) {
-    if button == "left" {
-        {
-            let mut fsm = rs.fsm.lock().unwrap();
-            fsm.handle_mouse(MouseButton::Left, ElementState::Pressed);
-        }
-        let _ = app.emit("rs-update", ());
-    }
+    rs_button(app, rs, &button, ButtonState::Pressed);
 }
 
 #[tauri::command]
 pub fn rs_mouseup(app: AppHandle, rs: State<'_, RSController>, button: String) {
-    if button == "left" {
-        {
-            let mut fsm = rs.fsm.lock().unwrap();
-            fsm.handle_mouse(MouseButton::Left, ElementState::Released);
-        }
-        let _ = app.emit("rs-update", ());
+    rs_button(app, rs, &button, ButtonState::Released);
+}
+
+fn rs_button(app: AppHandle, rs: State<'_, RSController>, button: &str, state: ButtonState) {
+    let button = match button {
+        "left" => MouseButton::Left,
+        "right" => MouseButton::Right,
+        "middle" => MouseButton::Middle,
+        _ => return,
+    };
+    {
+        let mut fsm = rs.fsm.lock().unwrap();
+        fsm.handle_mouse(button, state);
     }
+    let _ = app.emit("rs-update", ());
 }
 
 /// ENTER key triggers capture
@@ -44,6 +48,17 @@ pub async fn rs_key_enter(app: AppHandle, rs: State<'_, RSController>) -> Result
     println!("rs-key-enter");
 
     if let SelectionPhase::Confirmed = fsm.phase {
+        // a window sub-area is a single rect
+        let picking_window_area = app
+            .state::<AppState>()
+            .pending_window_area
+            .lock()
+            .unwrap()
+            .is_some();
+        if picking_window_area && fsm.all_bounds().len() > 1 {
+            return Err("Select one area of the window, remove the other regions".into());
+        }
+
         fsm.phase = SelectionPhase::Capturing;
         drop(fsm);
 
@@ -70,6 +85,7 @@ pub async fn rs_key_escape(app: AppHandle, rs: State<'_, RSController>) -> Resul
 
         // -------- allow keybind again --------
         app.state::<AppState>().exit_selecting_region();
+        app.state::<AppState>().pending_window_area.lock().unwrap().take();
 
         return Ok(());
     }
@@ -78,11 +94,54 @@ pub async fn rs_key_escape(app: AppHandle, rs: State<'_, RSController>) -> Resul
     Ok(())
 }
 
+/// Arrow keys nudge a confirmed box; with Alt they resize it, with Shift the
+/// step is larger.
+#[tauri::command]
+pub fn rs_key_arrow(
+    app: AppHandle,
+    rs: State<'_, RSController>,
+    key: String,
+    shift: bool,
+    alt: bool,
+) -> Result<(), String> {
+    let key = match key.as_str() {
+        "left" => ArrowKey::Left,
+        "right" => ArrowKey::Right,
+        "up" => ArrowKey::Up,
+        "down" => ArrowKey::Down,
+        other => return Err(format!("Unknown arrow key: {other}")),
+    };
+    {
+        let mut fsm = rs.fsm.lock().unwrap();
+        fsm.handle_arrow(key, alt, shift);
+    }
+    let _ = app.emit("rs-update", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn rs_toggle_aspect_lock(app: AppHandle, rs: State<'_, RSController>) {
+    {
+        let mut fsm = rs.fsm.lock().unwrap();
+        fsm.toggle_aspect_lock();
+    }
+    let _ = app.emit("rs-update", ());
+}
+
+/// Move the active region `delta` places in the capture order.
+#[tauri::command]
+pub fn rs_reorder(app: AppHandle, rs: State<'_, RSController>, delta: isize) {
+    {
+        let mut fsm = rs.fsm.lock().unwrap();
+        fsm.move_active(delta);
+    }
+    let _ = app.emit("rs-update", ());
+}
+
 #[tauri::command]
 pub fn rs_set_window_size(rs: State<'_, RSController>, width: u32, height: u32) {
-    use winit::dpi::PhysicalSize;
     let mut fsm = rs.fsm.lock().unwrap();
-    fsm.window_size = PhysicalSize::new(width, height);
+    fsm.window_size = WindowSize::new(width, height);
 }
 
 #[derive(serde::Serialize)]
@@ -97,6 +156,13 @@ pub struct Bounds {
 pub struct RSStateResponse {
     phase: String,
     bounds: Option<Bounds>,
+    /// Handle being dragged, or under the cursor of a confirmed box.
+    handle: Option<&'static str>,
+    aspect_locked: bool,
+    /// Every region, active one included, in capture order.
+    regions: Vec<Bounds>,
+    /// Index of `bounds` in `regions`.
+    active: Option<usize>,
 }
 
 #[tauri::command]
@@ -108,6 +174,7 @@ pub fn rs_get_state(rs: State<'_, RSController>) -> RSStateResponse {
         SelectionPhase::Drawing => "Drawing",
         SelectionPhase::Confirmed => "Confirmed",
         SelectionPhase::Moving { .. } => "Moving",
+        SelectionPhase::Resizing { .. } => "Resizing",
         SelectionPhase::Capturing => "Capturing",
     }
     .to_string();
@@ -115,30 +182,105 @@ pub fn rs_get_state(rs: State<'_, RSController>) -> RSStateResponse {
     let bounds = fsm
         .selection_bounds()
         .map(|(x, y, w, h)| Bounds { x, y, w, h });
+    let regions = fsm
+        .all_bounds()
+        .into_iter()
+        .map(|(x, y, w, h)| Bounds { x, y, w, h })
+        .collect();
 
-    RSStateResponse { phase, bounds }
+    let handle = match fsm.phase {
+        SelectionPhase::Resizing { edge } => Some(edge),
+        SelectionPhase::Confirmed => fsm.handle_at(fsm.cursor_pos.0, fsm.cursor_pos.1),
+        _ => None,
+    }
+    .map(|edge| edge.as_str());
+
+    RSStateResponse {
+        phase,
+        bounds,
+        handle,
+        aspect_locked: fsm.aspect_ratio.is_some(),
+        regions,
+        active: fsm.active_index(),
+    }
 }
 
 #[tauri::command]
 pub async fn rs_do_capture(app: AppHandle, rs: State<'_, RSController>) -> Result<(), String> {
     let mut fsm = rs.fsm.lock().unwrap();
-    let path_opt = { fsm.capture(&rs.screenshot)? };
 
-    let Some(path) = path_opt else {
+    let win = app
+        .get_webview_window("region-overlay")
+        .ok_or("Region overlay is not open")?;
+
+    let mapping = ScreenMapping::of(&win)?;
+    let rects: Vec<ScreenRect> = fsm
+        .all_bounds()
+        .into_iter()
+        .map(|bounds| mapping.to_screen_rect(bounds))
+        .collect();
+    if rects.is_empty() {
         return Err("No bounds to capture".into());
-    };
+    }
 
-    // -------- close overlay window --------
-    if let Some(win) = app.webview_windows().get("region-overlay") {
+    // picking the area of a window target: remember it and capture the
+    // window, without touching the last regions
+    let pending = app
+        .state::<AppState>()
+        .pending_window_area
+        .lock()
+        .unwrap()
+        .take();
+    if let Some(target) = pending {
+        let [rect] = rects[..] else {
+            return Err("A window area is a single region".into());
+        };
         win.close().map_err(|e| e.to_string())?;
+        fsm.reset();
+
+        std::thread::spawn(move || {
+            app.state::<AppState>().exit_selecting_region();
+            AppMediator::finish_window_area(&app, target, rect);
+        });
+        return Ok(());
     }
 
+    // one screenshot covering every region, cropped per region
+    let area = bounding_rect(&rects);
+    let image = rs.capture(area)?;
+    println!(
+        "Captured screenshot {}x{} at ({}, {}), {} region(s)",
+        area.w,
+        area.h,
+        area.x,
+        area.y,
+        rects.len()
+    );
+
+    let mut captures: Vec<_> = rects
+        .iter()
+        .map(|r| {
+            let (x, y) = ((r.x - area.x) as u32, (r.y - area.y) as u32);
+            let region = imageops::crop_imm(&image, x, y, r.w, r.h).to_image();
+            (region, (r.x, r.y))
+        })
+        .collect();
+
+    // -------- close overlay window --------
+    win.close().map_err(|e| e.to_string())?;
+
     // -------- send result back to main app --------
     // (Maybe AppMediator listens for this)
     std::thread::spawn(move || {
         // -------- allow keybind again --------
         app.state::<AppState>().exit_selecting_region();
-        AppMediator::send_file_path(&app, path);
+        app.state::<AppState>().set_last_regions(rects);
+        if captures.len() == 1 {
+            let (image, origin) = captures.remove(0);
+            AppMediator::process_capture(&app, image, origin, CaptureSource::Region);
+        } else {
+            AppMediator::process_captures(&app, captures, CaptureSource::Region);
+        }
     });
 
     // -------- reset fsm --------
@@ -146,9 +288,139 @@ pub async fn rs_do_capture(app: AppHandle, rs: State<'_, RSController>) -> Resul
     Ok(())
 }
 
+/// Maps overlay CSS pixels to physical desktop pixels. The webview has a
+/// single scale factor even when the overlay spans mixed-DPI displays, so
+/// one origin and one scale cover every rect; splitting per display happens
+/// in physical space when capturing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScreenMapping {
+    /// Overlay's client-area top-left corner in physical desktop pixels.
+    origin: (i32, i32),
+    scale: f64,
+}
+
+impl ScreenMapping {
+    fn of(win: &WebviewWindow) -> Result<Self, String> {
+        let scale = win.scale_factor().map_err(|e| e.to_string())?;
+        let origin = win.inner_position().map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            origin: (origin.x, origin.y),
+            scale,
+        })
+    }
+
+    /// Convert FSM bounds (webview CSS pixels, relative to the overlay) into
+    /// physical desktop pixels.
+    fn to_screen_rect(&self, (x, y, w, h): (usize, usize, usize, usize)) -> ScreenRect {
+        let px = |v: usize| (v as f64 * self.scale).round();
+
+        ScreenRect {
+            x: self.origin.0 + px(x) as i32,
+            y: self.origin.1 + px(y) as i32,
+            w: px(w) as u32,
+            h: px(h) as u32,
+        }
+    }
+}
+
+/// Smallest rect containing all of `rects`, which must not be empty.
+fn bounding_rect(rects: &[ScreenRect]) -> ScreenRect {
+    let x0 = rects.iter().map(|r| r.x).min().unwrap_or(0);
+    let y0 = rects.iter().map(|r| r.y).min().unwrap_or(0);
+    let x1 = rects.iter().map(|r| r.x + r.w as i32).max().unwrap_or(0);
+    let y1 = rects.iter().map(|r| r.y + r.h as i32).max().unwrap_or(0);
+
+    ScreenRect {
+        x: x0,
+        y: y0,
+        w: (x1 - x0) as u32,
+        h: (y1 - y0) as u32,
+    }
+}
+
+/// Frozen background for the overlay as a PNG. Empty when the overlay shows
+/// the live screen.
+#[tauri::command]
+pub fn rs_snapshot(rs: State<'_, RSController>) -> Result<Response, String> {
+    match &rs.snapshot {
+        Some(snapshot) => snapshot.to_png().map(Response::new),
+        None => Ok(Response::new(Vec::new())),
+    }
+}
+
 #[tauri::command]
 pub fn rs_ready(app: AppHandle) {
     if let Some(win) = app.webview_windows().get("region-overlay") {
         let _ = win.show();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1920x1080 at 100% and 2560x1440 at 150% right of it; the overlay covers
+    // both from the physical origin
+    #[test]
+    fn rects_are_mapped_with_the_overlay_scale() {
+        let mapping = ScreenMapping {
+            origin: (0, 0),
+            scale: 1.0,
+        };
+
+        // on the secondary display, not shifted by its own scale
+        assert_eq!(
+            mapping.to_screen_rect((2000, 100, 200, 100)),
+            ScreenRect {
+                x: 2000,
+                y: 100,
+                w: 200,
+                h: 100
+            }
+        );
+        // straddling both displays, split later when capturing
+        assert_eq!(
+            mapping.to_screen_rect((1800, 100, 400, 100)),
+            ScreenRect {
+                x: 1800,
+                y: 100,
+                w: 400,
+                h: 100
+            }
+        );
+    }
+
+    #[test]
+    fn rects_follow_the_overlay_origin_and_scale() {
+        // the same layout with the overlay at the secondary display's 150%
+        let mapping = ScreenMapping {
+            origin: (0, 0),
+            scale: 1.5,
+        };
+
+        assert_eq!(
+            mapping.to_screen_rect((1400, 100, 200, 100)),
+            ScreenRect {
+                x: 2100,
+                y: 150,
+                w: 300,
+                h: 150
+            }
+        );
+
+        let shifted = ScreenMapping {
+            origin: (-1920, 0),
+            ..mapping
+        };
+        assert_eq!(
+            shifted.to_screen_rect((1400, 100, 200, 100)),
+            ScreenRect {
+                x: 180,
+                y: 150,
+                w: 300,
+                h: 150
+            }
+        );
+    }
+}
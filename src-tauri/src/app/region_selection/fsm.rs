@@ -1,11 +1,24 @@
-use winit::dpi::PhysicalSize;
-use winit::event::{ElementState, MouseButton};
+// src-tauri/src/app/region_selection/fsm.rs
+//
+// Pure state machine behind the region selection overlay. It only knows about
+// the plain input types below, so it can be driven (and tested) without a
+// window; the controller translates webview events into them.
 
-use crate::infra::screenshot::ScreenshotService;
+use crate::infra::text_detection;
 
 const MIN_BOX_SIZE: usize = 25;
 
-#[derive(Debug, Default, Clone)]
+/// How close (px) the cursor must be to an edge or corner to grab its handle.
+const HANDLE_RADIUS: f64 = 8.0;
+
+/// Arrow key step, and the step with the large-step modifier held.
+const NUDGE_STEP: f64 = 1.0;
+const NUDGE_STEP_LARGE: f64 = 10.0;
+
+/// A box as `(left, top, right, bottom)`, without rounding.
+type Rect = (f64, f64, f64, f64);
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub enum SelectionPhase {
     #[default]
     Idle,
@@ -14,17 +27,118 @@ pub enum SelectionPhase {
     Moving {
         offset: (f64, f64),
     },
+    Resizing {
+        edge: Edge,
+    },
     Capturing,
 }
 
+/// One of the eight resize handles of a confirmed box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 8] = [
+        Edge::Top,
+        Edge::Bottom,
+        Edge::Left,
+        Edge::Right,
+        Edge::TopLeft,
+        Edge::TopRight,
+        Edge::BottomLeft,
+        Edge::BottomRight,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Edge::Top => "top",
+            Edge::Bottom => "bottom",
+            Edge::Left => "left",
+            Edge::Right => "right",
+            Edge::TopLeft => "top_left",
+            Edge::TopRight => "top_right",
+            Edge::BottomLeft => "bottom_left",
+            Edge::BottomRight => "bottom_right",
+        }
+    }
+
+    fn moves_left(&self) -> bool {
+        matches!(self, Edge::Left | Edge::TopLeft | Edge::BottomLeft)
+    }
+
+    fn moves_right(&self) -> bool {
+        matches!(self, Edge::Right | Edge::TopRight | Edge::BottomRight)
+    }
+
+    fn moves_top(&self) -> bool {
+        matches!(self, Edge::Top | Edge::TopLeft | Edge::TopRight)
+    }
+
+    fn moves_bottom(&self) -> bool {
+        matches!(self, Edge::Bottom | Edge::BottomLeft | Edge::BottomRight)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonState {
+    Pressed,
+    Released,
+}
+
+/// Overlay size in webview (CSS) pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowSize {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrowKey {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
 #[derive(Clone)]
 pub struct RegionSelectionState {
     pub start: Option<(f64, f64)>,
     pub end: Option<(f64, f64)>,
     pub cursor_pos: (f64, f64),
-    pub window_size: PhysicalSize<u32>,
+    pub window_size: WindowSize,
     pub phase: SelectionPhase,
     pub capture_debounce: bool,
+    /// Width / height kept while resizing, when the aspect ratio is locked.
+    pub aspect_ratio: Option<f64>,
+    /// The other finished boxes of this session, in drawing order. The
+    /// active box (`start` / `end`) sits at `active_slot` in that order.
+    regions: Vec<Rect>,
+    active_slot: usize,
+    /// Set once the user moves a box; from then on the capture order is the
+    /// order of `regions` instead of reading order.
+    reordered: bool,
 }
 
 impl Default for RegionSelectionState {
@@ -34,16 +148,22 @@ impl Default for RegionSelectionState {
             end: None,
             phase: SelectionPhase::Idle,
             cursor_pos: (0.0, 0.0),
-            window_size: PhysicalSize::new(1, 1),
+            window_size: WindowSize::new(1, 1),
             capture_debounce: false,
+            aspect_ratio: None,
+            regions: Vec::new(),
+            active_slot: 0,
+            reordered: false,
         }
     }
 }
 
 impl RegionSelectionState {
-    pub fn update_cursor(&mut self, mut x: f64, mut y: f64) {
-        x = x.max(0.0);
-        y = y.max(0.0);
+    pub fn update_cursor(&mut self, x: f64, y: f64) {
+        // the pointer can leave the overlay while a button is held
+        let (win_w, win_h) = self.window_extent();
+        let x = x.clamp(0.0, win_w);
+        let y = y.clamp(0.0, win_h);
 
         self.cursor_pos = (x, y);
 
@@ -82,71 +202,348 @@ impl RegionSelectionState {
                 }
             }
 
+            SelectionPhase::Resizing { edge } => self.resize_to(edge, x, y),
+
             _ => {}
         }
     }
 
+    /// Bounds of the active box.
     pub fn selection_bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        self.rect().map(to_bounds)
+    }
+
+    /// Bounds of every box of the session, active one included, in capture
+    /// order.
+    pub fn all_bounds(&self) -> Vec<(usize, usize, usize, usize)> {
+        self.capture_order().0.into_iter().map(to_bounds).collect()
+    }
+
+    /// Position of the active box in `all_bounds`.
+    pub fn active_index(&self) -> Option<usize> {
+        self.capture_order().1
+    }
+
+    /// Every box, active one included, and the position of the active one:
+    /// in reading order until the user reorders them, then in their order.
+    fn capture_order(&self) -> (Vec<Rect>, Option<usize>) {
+        let mut all = self.regions.clone();
+        let active = self.rect().map(|rect| {
+            let slot = self.active_slot.min(all.len());
+            all.insert(slot, rect);
+            slot
+        });
+        if self.reordered {
+            return (all, active);
+        }
+
+        let bounds: Vec<_> = all
+            .iter()
+            .map(|&r| {
+                let (x, y, w, h) = to_bounds(r);
+                (x as u32, y as u32, w as u32, h as u32)
+            })
+            .collect();
+        let order = text_detection::reading_order(&bounds);
+        let active = active.and_then(|slot| order.iter().position(|&i| i == slot));
+        (order.into_iter().map(|i| all[i]).collect(), active)
+    }
+
+    fn rect(&self) -> Option<Rect> {
         let (sx, sy) = self.start?;
         let (ex, ey) = self.end?;
+        Some((sx.min(ex), sy.min(ey), sx.max(ex), sy.max(ey)))
+    }
+
+    fn set_rect(&mut self, (x0, y0, x1, y1): Rect) {
+        self.start = Some((x0, y0));
+        self.end = Some((x1, y1));
+    }
 
-        let x = sx.min(ex) as usize;
-        let y = sy.min(ey) as usize;
-        let w = (sx - ex).abs() as usize;
-        let h = (sy - ey).abs() as usize;
+    /// Resize handle under `(x, y)`, if any. Corners win over edges.
+    pub fn handle_at(&self, x: f64, y: f64) -> Option<Edge> {
+        let (x0, y0, x1, y1) = self.rect()?;
+        let near = |a: f64, b: f64| (a - b).abs() <= HANDLE_RADIUS;
 
-        Some((x, y, w, h))
+        let inside_x = x >= x0 - HANDLE_RADIUS && x <= x1 + HANDLE_RADIUS;
+        let inside_y = y >= y0 - HANDLE_RADIUS && y <= y1 + HANDLE_RADIUS;
+        if !inside_x || !inside_y {
+            return None;
+        }
+
+        let (left, right) = (near(x, x0), !near(x, x0) && near(x, x1));
+        let (top, bottom) = (near(y, y0), !near(y, y0) && near(y, y1));
+
+        match (left, right, top, bottom) {
+            (true, _, true, _) => Some(Edge::TopLeft),
+            (_, true, true, _) => Some(Edge::TopRight),
+            (true, _, _, true) => Some(Edge::BottomLeft),
+            (_, true, _, true) => Some(Edge::BottomRight),
+            (true, ..) => Some(Edge::Left),
+            (_, true, ..) => Some(Edge::Right),
+            (_, _, true, _) => Some(Edge::Top),
+            (.., true) => Some(Edge::Bottom),
+            _ => None,
+        }
+    }
+
+    /// Drag `edge` to `(x, y)`. The opposite side stays put, the box never
+    /// gets smaller than `MIN_BOX_SIZE` and never leaves the window.
+    fn resize_to(&mut self, edge: Edge, x: f64, y: f64) {
+        let Some((mut x0, mut y0, mut x1, mut y1)) = self.rect() else {
+            return;
+        };
+        let (win_w, win_h) = self.window_extent();
+        let (x, y) = (x.clamp(0.0, win_w), y.clamp(0.0, win_h));
+        let min = MIN_BOX_SIZE as f64;
+
+        // the window edge wins over the minimum size
+        if edge.moves_left() {
+            x0 = x.min(x1 - min).max(0.0);
+        }
+        if edge.moves_right() {
+            x1 = x.max(x0 + min).min(win_w);
+        }
+        if edge.moves_top() {
+            y0 = y.min(y1 - min).max(0.0);
+        }
+        if edge.moves_bottom() {
+            y1 = y.max(y0 + min).min(win_h);
+        }
+
+        let rect = match self.aspect_ratio {
+            Some(ratio) => self.keep_aspect(edge, ratio, (x0, y0, x1, y1)),
+            None => (x0, y0, x1, y1),
+        };
+        self.set_rect(rect);
+    }
+
+    /// Fix up a resized box so that width / height equals `ratio`. Top and
+    /// bottom handles drive the height, every other handle drives the width;
+    /// the other dimension grows away from the fixed side.
+    fn keep_aspect(&self, edge: Edge, ratio: f64, (x0, y0, x1, y1): Rect) -> Rect {
+        let (win_w, win_h) = self.window_extent();
+        let (mut w, mut h) = (x1 - x0, y1 - y0);
+        if matches!(edge, Edge::Top | Edge::Bottom) {
+            w = h * ratio;
+        } else {
+            h = w / ratio;
+        }
+
+        // shrink both sides evenly if the box would leave the window
+        let room_w = if edge.moves_left() { x1 } else { win_w - x0 };
+        let room_h = if edge.moves_top() { y1 } else { win_h - y0 };
+        let scale = (room_w / w).min(room_h / h).min(1.0);
+        let (w, h) = (w * scale, h * scale);
+
+        let (x0, x1) = if edge.moves_left() {
+            (x1 - w, x1)
+        } else {
+            (x0, x0 + w)
+        };
+        let (y0, y1) = if edge.moves_top() {
+            (y1 - h, y1)
+        } else {
+            (y0, y0 + h)
+        };
+        (x0, y0, x1, y1)
+    }
+
+    fn window_extent(&self) -> (f64, f64) {
+        (
+            self.window_size.width as f64,
+            self.window_size.height as f64,
+        )
+    }
+
+    /// Arrow keys on a confirmed box: move it, or with `resize` grow/shrink
+    /// it from the right/bottom edge. `large` moves in bigger steps.
+    pub fn handle_arrow(&mut self, key: ArrowKey, resize: bool, large: bool) {
+        if self.phase != SelectionPhase::Confirmed {
+            return;
+        }
+        let Some((x0, y0, x1, y1)) = self.rect() else {
+            return;
+        };
+
+        let step = if large { NUDGE_STEP_LARGE } else { NUDGE_STEP };
+        let (dx, dy) = match key {
+            ArrowKey::Left => (-step, 0.0),
+            ArrowKey::Right => (step, 0.0),
+            ArrowKey::Up => (0.0, -step),
+            ArrowKey::Down => (0.0, step),
+        };
+
+        if resize {
+            let edge = if dx != 0.0 { Edge::Right } else { Edge::Bottom };
+            self.resize_to(edge, x1 + dx, y1 + dy);
+            return;
+        }
+
+        let (win_w, win_h) = self.window_extent();
+        let (w, h) = (x1 - x0, y1 - y0);
+        let x = (x0 + dx).clamp(0.0, (win_w - w).max(0.0));
+        let y = (y0 + dy).clamp(0.0, (win_h - h).max(0.0));
+        self.set_rect((x, y, x + w, y + h));
+    }
+
+    /// Lock the aspect ratio to the current box, or unlock it.
+    pub fn toggle_aspect_lock(&mut self) {
+        self.aspect_ratio = match (self.aspect_ratio, self.rect()) {
+            (None, Some((x0, y0, x1, y1))) if y1 > y0 => Some((x1 - x0) / (y1 - y0)),
+            _ => None,
+        };
     }
 
     /// True hit-test against the rectangle
     pub fn hit_test(&self, x: f64, y: f64) -> bool {
-        if let Some((bx, by, bw, bh)) = self.selection_bounds() {
-            x >= bx as f64 && y >= by as f64 && x <= (bx + bw) as f64 && y <= (by + bh) as f64
+        self.selection_bounds()
+            .is_some_and(|bounds| contains(bounds, x, y))
+    }
+
+    /// Index into `regions` of the topmost other box under `(x, y)`.
+    fn region_at(&self, x: f64, y: f64) -> Option<usize> {
+        self.regions
+            .iter()
+            .rposition(|r| contains(to_bounds(*r), x, y))
+    }
+
+    /// Move the active box `delta` places in the capture order.
+    pub fn move_active(&mut self, delta: isize) {
+        if self.phase != SelectionPhase::Confirmed {
+            return;
+        }
+        if !self.reordered {
+            // move within the reading order shown so far, and keep it
+            let (mut all, active) = self.capture_order();
+            if let Some(active) = active {
+                all.remove(active);
+                self.regions = all;
+                self.active_slot = active;
+            }
+            self.reordered = true;
+        }
+        let slot = self.active_slot as isize + delta;
+        self.active_slot = slot.clamp(0, self.regions.len() as isize) as usize;
+    }
+
+    /// Put the active box back into the list and start drawing a new one at
+    /// the cursor, at the end of the capture order.
+    fn start_region(&mut self) {
+        if let Some(active) = self.rect() {
+            self.regions.insert(self.active_slot, active);
+        }
+        self.active_slot = self.regions.len();
+        self.start = Some(self.cursor_pos);
+        self.end = Some(self.cursor_pos);
+        self.aspect_ratio = None;
+        self.phase = SelectionPhase::Drawing;
+    }
+
+    /// Make `regions[index]` the active box, keeping the capture order.
+    fn activate_region(&mut self, index: usize) {
+        let Some(active) = self.rect() else {
+            return;
+        };
+        let mut all = std::mem::take(&mut self.regions);
+        all.insert(self.active_slot, active);
+
+        let slot = if index < self.active_slot {
+            index
         } else {
-            false
+            index + 1
+        };
+        let clicked = all.remove(slot);
+        self.regions = all;
+        self.active_slot = slot;
+
+        self.set_rect(clicked);
+        self.aspect_ratio = None;
+    }
+
+    /// Drop the active box. The box before it (or after it, if it was first)
+    /// becomes active; with no boxes left the session goes back to idle.
+    fn discard_active(&mut self) {
+        self.start = None;
+        self.end = None;
+        self.aspect_ratio = None;
+
+        if self.regions.is_empty() {
+            self.active_slot = 0;
+            self.phase = SelectionPhase::Idle;
+            return;
+        }
+
+        let index = self
+            .active_slot
+            .saturating_sub(1)
+            .min(self.regions.len() - 1);
+        let rect = self.regions.remove(index);
+        self.set_rect(rect);
+        self.active_slot = index;
+        self.phase = SelectionPhase::Confirmed;
+    }
+
+    /// Right click: remove the box under the cursor.
+    fn remove_at_cursor(&mut self) {
+        if self.phase != SelectionPhase::Confirmed {
+            return;
+        }
+        let (cx, cy) = self.cursor_pos;
+
+        if self.hit_test(cx, cy) {
+            self.discard_active();
+        } else if let Some(index) = self.region_at(cx, cy) {
+            self.regions.remove(index);
+            if index < self.active_slot {
+                self.active_slot -= 1;
+            }
         }
     }
 
-    pub fn handle_mouse(&mut self, button: MouseButton, state: ElementState) {
+    pub fn handle_mouse(&mut self, button: MouseButton, state: ButtonState) {
+        if button == MouseButton::Right && state == ButtonState::Pressed {
+            self.remove_at_cursor();
+            return;
+        }
         if button != MouseButton::Left {
             return;
         }
 
         match (&self.phase, state) {
             // start drag
-            (SelectionPhase::Idle, ElementState::Pressed) => {
-                self.start = Some(self.cursor_pos);
-                self.end = Some(self.cursor_pos);
-                self.phase = SelectionPhase::Drawing;
-            }
+            (SelectionPhase::Idle, ButtonState::Pressed) => self.start_region(),
 
             // finish drag (confirm or cancel)
-            (SelectionPhase::Drawing, ElementState::Released) => {
-                if let Some((_, _, w, h)) = self.selection_bounds() {
-                    if w < MIN_BOX_SIZE || h < MIN_BOX_SIZE {
-                        self.reset();
-                    } else {
-                        self.phase = SelectionPhase::Confirmed;
-                    }
-                } else {
-                    self.reset();
+            (SelectionPhase::Drawing, ButtonState::Released) => match self.selection_bounds() {
+                Some((_, _, w, h)) if w >= MIN_BOX_SIZE && h >= MIN_BOX_SIZE => {
+                    self.phase = SelectionPhase::Confirmed;
                 }
-            }
+                _ => self.discard_active(),
+            },
 
-            // click inside confirmed box → start moving
-            (SelectionPhase::Confirmed, ElementState::Pressed) => {
+            // click on a handle → start resizing, inside the box → start moving,
+            // on another box → make it active, elsewhere → draw one more box
+            (SelectionPhase::Confirmed, ButtonState::Pressed) => {
                 let (cx, cy) = self.cursor_pos;
 
-                if self.hit_test(cx, cy) {
-                    if let Some((x, y, _w, _h)) = self.selection_bounds() {
-                        let offset = (cx - x as f64, cy - y as f64);
-                        self.phase = SelectionPhase::Moving { offset };
-                    }
+                if let Some(edge) = self.handle_at(cx, cy) {
+                    self.phase = SelectionPhase::Resizing { edge };
+                } else if self.hit_test(cx, cy)
+                    && let Some((x, y, _w, _h)) = self.selection_bounds()
+                {
+                    let offset = (cx - x as f64, cy - y as f64);
+                    self.phase = SelectionPhase::Moving { offset };
+                } else if let Some(index) = self.region_at(cx, cy) {
+                    self.activate_region(index);
+                } else {
+                    self.start_region();
                 }
             }
 
-            // release after moving
-            (SelectionPhase::Moving { .. }, ElementState::Released) => {
+            // release after moving / resizing
+            (SelectionPhase::Moving { .. }, ButtonState::Released)
+            | (SelectionPhase::Resizing { .. }, ButtonState::Released) => {
                 self.phase = SelectionPhase::Confirmed;
             }
 
@@ -158,22 +555,533 @@ impl RegionSelectionState {
         self.start = None;
         self.end = None;
         self.phase = SelectionPhase::Idle;
+        self.aspect_ratio = None;
+        self.regions.clear();
+        self.active_slot = 0;
+        self.reordered = false;
+    }
+}
+
+fn to_bounds((x0, y0, x1, y1): Rect) -> (usize, usize, usize, usize) {
+    (
+        x0 as usize,
+        y0 as usize,
+        (x1 - x0) as usize,
+        (y1 - y0) as usize,
+    )
+}
+
+fn contains((bx, by, bw, bh): (usize, usize, usize, usize), x: f64, y: f64) -> bool {
+    x >= bx as f64 && y >= by as f64 && x <= (bx + bw) as f64 && y <= (by + bh) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Idle selection in an 800x600 window.
+    fn window() -> RegionSelectionState {
+        RegionSelectionState {
+            window_size: WindowSize::new(800, 600),
+            ..Default::default()
+        }
+    }
+
+    /// Confirmed 200x100 box at (100, 100) in an 800x600 window.
+    fn confirmed() -> RegionSelectionState {
+        let mut fsm = window();
+        drag(&mut fsm, (100.0, 100.0), (300.0, 200.0));
+        assert_eq!(fsm.phase, SelectionPhase::Confirmed);
+        fsm
+    }
+
+    fn drag(fsm: &mut RegionSelectionState, from: (f64, f64), to: (f64, f64)) {
+        fsm.update_cursor(from.0, from.1);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+        fsm.update_cursor(to.0, to.1);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Released);
+    }
+
+    /// Cursor position on the handle of `edge` of the `confirmed()` box.
+    fn handle_pos(edge: Edge) -> (f64, f64) {
+        match edge {
+            Edge::Top => (200.0, 100.0),
+            Edge::Bottom => (200.0, 200.0),
+            Edge::Left => (100.0, 150.0),
+            Edge::Right => (300.0, 150.0),
+            Edge::TopLeft => (100.0, 100.0),
+            Edge::TopRight => (300.0, 100.0),
+            Edge::BottomLeft => (100.0, 200.0),
+            Edge::BottomRight => (300.0, 200.0),
+        }
+    }
+
+    #[test]
+    fn idle_press_starts_drawing() {
+        let mut fsm = window();
+        fsm.update_cursor(10.0, 10.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+        assert_eq!(fsm.phase, SelectionPhase::Drawing);
+
+        fsm.update_cursor(60.0, 40.0);
+        assert_eq!(fsm.selection_bounds(), Some((10, 10, 50, 30)));
+    }
+
+    #[test]
+    fn drawing_release_confirms_or_cancels() {
+        let fsm = confirmed();
+        assert_eq!(fsm.selection_bounds(), Some((100, 100, 200, 100)));
+
+        let min = MIN_BOX_SIZE as f64;
+        let mut smallest = window();
+        drag(&mut smallest, (100.0, 100.0), (100.0 + min, 100.0 + min));
+        assert_eq!(smallest.phase, SelectionPhase::Confirmed);
+        assert_eq!(
+            smallest.selection_bounds(),
+            Some((100, 100, MIN_BOX_SIZE, MIN_BOX_SIZE))
+        );
+
+        // one pixel short in either direction
+        for to in [(min - 1.0, min), (min, min - 1.0)] {
+            let mut tiny = window();
+            drag(&mut tiny, (100.0, 100.0), (100.0 + to.0, 100.0 + to.1));
+            assert_eq!(tiny.phase, SelectionPhase::Idle, "{to:?}");
+            assert_eq!(tiny.selection_bounds(), None, "{to:?}");
+        }
+    }
+
+    #[test]
+    fn press_inside_moves_and_release_confirms() {
+        let mut fsm = confirmed();
+        fsm.update_cursor(200.0, 150.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+        assert!(matches!(fsm.phase, SelectionPhase::Moving { .. }));
+
+        fsm.update_cursor(250.0, 170.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Released);
+        assert_eq!(fsm.phase, SelectionPhase::Confirmed);
+        assert_eq!(fsm.selection_bounds(), Some((150, 120, 200, 100)));
+    }
+
+    #[test]
+    fn every_handle_starts_resizing() {
+        for edge in Edge::ALL {
+            let mut fsm = confirmed();
+            let (x, y) = handle_pos(edge);
+            fsm.update_cursor(x, y);
+            fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+            assert_eq!(fsm.phase, SelectionPhase::Resizing { edge }, "{edge:?}");
+
+            fsm.handle_mouse(MouseButton::Left, ButtonState::Released);
+            assert_eq!(fsm.phase, SelectionPhase::Confirmed, "{edge:?}");
+        }
     }
 
-    pub fn capture(&self, screenshot: &ScreenshotService) -> Result<Option<String>, String> {
-        // Ensure we have a valid selection region
-        let (x, y, w, h) = match self.selection_bounds() {
-            Some(b) => b,
-            None => return Ok(None),
+    #[test]
+    fn resizing_moves_only_the_grabbed_sides() {
+        let expected = [
+            (Edge::Top, (100, 80, 200, 120)),
+            (Edge::Bottom, (100, 100, 200, 120)),
+            (Edge::Left, (80, 100, 220, 100)),
+            (Edge::Right, (100, 100, 220, 100)),
+            (Edge::TopLeft, (80, 80, 220, 120)),
+            (Edge::TopRight, (100, 80, 220, 120)),
+            (Edge::BottomLeft, (80, 100, 220, 120)),
+            (Edge::BottomRight, (100, 100, 220, 120)),
+        ];
+
+        for (edge, bounds) in expected {
+            let mut fsm = confirmed();
+            let (x, y) = handle_pos(edge);
+            fsm.update_cursor(x, y);
+            fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+
+            // pull every grabbed side 20px outwards
+            let dx = if edge.moves_left() { -20.0 } else { 20.0 };
+            let dy = if edge.moves_top() { -20.0 } else { 20.0 };
+            fsm.update_cursor(x + dx, y + dy);
+            fsm.handle_mouse(MouseButton::Left, ButtonState::Released);
+
+            assert_eq!(fsm.selection_bounds(), Some(bounds), "{edge:?}");
+        }
+    }
+
+    #[test]
+    fn resizing_keeps_minimum_size_and_window() {
+        let mut fsm = confirmed();
+        fsm.update_cursor(300.0, 200.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+
+        // crossing over the opposite corner does not flip the box
+        fsm.update_cursor(0.0, 0.0);
+        assert_eq!(fsm.selection_bounds(), Some((100, 100, 25, 25)));
+
+        fsm.update_cursor(5000.0, 5000.0);
+        assert_eq!(fsm.selection_bounds(), Some((100, 100, 700, 500)));
+    }
+
+    #[test]
+    fn aspect_lock_keeps_ratio() {
+        let mut fsm = confirmed();
+        fsm.toggle_aspect_lock();
+        assert_eq!(fsm.aspect_ratio, Some(2.0));
+
+        fsm.update_cursor(300.0, 150.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+        fsm.update_cursor(400.0, 150.0);
+        assert_eq!(fsm.selection_bounds(), Some((100, 100, 300, 150)));
+
+        // height-driven handle, limited by the window's bottom
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Released);
+        fsm.update_cursor(200.0, 250.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+        fsm.update_cursor(200.0, 900.0);
+        let (_, _, w, h) = fsm.selection_bounds().unwrap();
+        assert_eq!((w, h), (700, 350));
+
+        fsm.toggle_aspect_lock();
+        assert_eq!(fsm.aspect_ratio, None);
+    }
+
+    #[test]
+    fn arrows_nudge_and_clamp() {
+        let mut fsm = confirmed();
+        fsm.handle_arrow(ArrowKey::Right, false, false);
+        fsm.handle_arrow(ArrowKey::Down, false, true);
+        assert_eq!(fsm.selection_bounds(), Some((101, 110, 200, 100)));
+
+        for _ in 0..100 {
+            fsm.handle_arrow(ArrowKey::Left, false, true);
+        }
+        assert_eq!(fsm.selection_bounds(), Some((0, 110, 200, 100)));
+    }
+
+    #[test]
+    fn arrows_resize_with_modifier() {
+        let mut fsm = confirmed();
+        fsm.handle_arrow(ArrowKey::Right, true, true);
+        fsm.handle_arrow(ArrowKey::Up, true, false);
+        assert_eq!(fsm.selection_bounds(), Some((100, 100, 210, 99)));
+
+        for _ in 0..100 {
+            fsm.handle_arrow(ArrowKey::Left, true, true);
+        }
+        assert_eq!(fsm.selection_bounds(), Some((100, 100, 25, 99)));
+    }
+
+    #[test]
+    fn arrows_only_act_on_confirmed_box() {
+        let mut fsm = RegionSelectionState::default();
+        fsm.handle_arrow(ArrowKey::Right, false, false);
+        assert_eq!(fsm.selection_bounds(), None);
+
+        let mut fsm = confirmed();
+        fsm.update_cursor(200.0, 150.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+        fsm.handle_arrow(ArrowKey::Right, false, true);
+        assert_eq!(fsm.selection_bounds(), Some((100, 100, 200, 100)));
+    }
+
+    #[test]
+    fn reset_returns_to_idle() {
+        let mut fsm = confirmed();
+        fsm.toggle_aspect_lock();
+        fsm.reset();
+        assert_eq!(fsm.phase, SelectionPhase::Idle);
+        assert_eq!(fsm.selection_bounds(), None);
+        assert_eq!(fsm.aspect_ratio, None);
+    }
+
+    #[test]
+    fn cursor_is_clamped_to_window() {
+        let mut fsm = RegionSelectionState {
+            window_size: WindowSize::new(800, 600),
+            ..Default::default()
         };
+        fsm.update_cursor(-50.0, 900.0);
+        assert_eq!(fsm.cursor_pos, (0.0, 600.0));
 
-        // Perform screenshot
-        match screenshot.capture_region(x as i32, y as i32, w as u32, h as u32) {
-            Ok(path) => {
-                println!("Captured screenshot {w}x{h} at: {}", path);
-                Ok(Some(path))
+        drag(&mut fsm, (700.0, 500.0), (2000.0, -40.0));
+        assert_eq!(fsm.selection_bounds(), Some((700, 0, 100, 500)));
+    }
+
+    #[test]
+    fn min_box_size_applies_per_side() {
+        let min = MIN_BOX_SIZE as f64;
+        let mut fsm = RegionSelectionState {
+            window_size: WindowSize::new(800, 600),
+            ..Default::default()
+        };
+
+        drag(&mut fsm, (10.0, 10.0), (300.0, 10.0 + min - 1.0));
+        assert_eq!(fsm.phase, SelectionPhase::Idle);
+
+        drag(&mut fsm, (10.0, 10.0), (10.0 + min, 10.0 + min));
+        assert_eq!(fsm.phase, SelectionPhase::Confirmed);
+    }
+
+    #[test]
+    fn drawing_backwards_normalizes_bounds() {
+        let mut fsm = RegionSelectionState {
+            window_size: WindowSize::new(800, 600),
+            ..Default::default()
+        };
+        drag(&mut fsm, (300.0, 200.0), (100.0, 100.0));
+        assert_eq!(fsm.selection_bounds(), Some((100, 100, 200, 100)));
+    }
+
+    #[test]
+    fn hit_test_covers_box_and_border() {
+        let fsm = confirmed();
+        assert!(fsm.hit_test(200.0, 150.0));
+        assert!(fsm.hit_test(100.0, 100.0));
+        assert!(fsm.hit_test(300.0, 200.0));
+        assert!(!fsm.hit_test(99.0, 150.0));
+        assert!(!fsm.hit_test(200.0, 201.0));
+        assert!(!RegionSelectionState::default().hit_test(0.0, 0.0));
+    }
+
+    #[test]
+    fn moving_keeps_grab_offset_and_stays_in_window() {
+        let mut fsm = confirmed();
+        fsm.update_cursor(130.0, 110.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+        assert_eq!(
+            fsm.phase,
+            SelectionPhase::Moving {
+                offset: (30.0, 10.0)
+            }
+        );
+
+        fsm.update_cursor(430.0, 310.0);
+        assert_eq!(fsm.selection_bounds(), Some((400, 300, 200, 100)));
+
+        // pushed against the bottom-right corner
+        fsm.update_cursor(800.0, 600.0);
+        assert_eq!(fsm.selection_bounds(), Some((600, 500, 200, 100)));
+
+        // and the top-left one
+        fsm.update_cursor(0.0, 0.0);
+        assert_eq!(fsm.selection_bounds(), Some((0, 0, 200, 100)));
+    }
+
+    #[test]
+    fn press_outside_confirmed_box_starts_another() {
+        let mut fsm = confirmed();
+        fsm.update_cursor(600.0, 500.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+        assert_eq!(fsm.phase, SelectionPhase::Drawing);
+        assert_eq!(fsm.all_bounds()[0], (100, 100, 200, 100));
+    }
+
+    #[test]
+    fn other_buttons_are_ignored() {
+        let mut fsm = RegionSelectionState::default();
+        for button in [MouseButton::Right, MouseButton::Middle] {
+            fsm.handle_mouse(button, ButtonState::Pressed);
+            assert_eq!(fsm.phase, SelectionPhase::Idle);
+        }
+
+        let mut fsm = confirmed();
+        fsm.update_cursor(200.0, 150.0);
+        fsm.handle_mouse(MouseButton::Middle, ButtonState::Pressed);
+        assert_eq!(fsm.phase, SelectionPhase::Confirmed);
+    }
+
+    const FIRST: (usize, usize, usize, usize) = (100, 100, 200, 100);
+    const SECOND: (usize, usize, usize, usize) = (400, 300, 100, 50);
+    const THIRD: (usize, usize, usize, usize) = (100, 400, 100, 50);
+
+    /// `confirmed()` plus two more boxes drawn after it.
+    fn three_regions() -> RegionSelectionState {
+        let mut fsm = confirmed();
+        drag(&mut fsm, (400.0, 300.0), (500.0, 350.0));
+        drag(&mut fsm, (100.0, 400.0), (200.0, 450.0));
+        fsm
+    }
+
+    #[test]
+    fn dragging_outside_adds_regions_in_order() {
+        let fsm = three_regions();
+        assert_eq!(fsm.phase, SelectionPhase::Confirmed);
+        assert_eq!(fsm.all_bounds(), vec![FIRST, SECOND, THIRD]);
+        assert_eq!(fsm.selection_bounds(), Some(THIRD));
+        assert_eq!(fsm.active_index(), Some(2));
+    }
+
+    #[test]
+    fn too_small_extra_box_restores_previous() {
+        let mut fsm = confirmed();
+        drag(&mut fsm, (600.0, 500.0), (610.0, 510.0));
+        assert_eq!(fsm.phase, SelectionPhase::Confirmed);
+        assert_eq!(fsm.all_bounds(), vec![FIRST]);
+        assert_eq!(fsm.selection_bounds(), Some(FIRST));
+    }
+
+    #[test]
+    fn clicking_another_region_activates_it() {
+        let mut fsm = three_regions();
+        fsm.update_cursor(450.0, 320.0);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Pressed);
+        fsm.handle_mouse(MouseButton::Left, ButtonState::Released);
+
+        assert_eq!(fsm.phase, SelectionPhase::Confirmed);
+        assert_eq!(fsm.selection_bounds(), Some(SECOND));
+        assert_eq!(fsm.active_index(), Some(1));
+        assert_eq!(fsm.all_bounds(), vec![FIRST, SECOND, THIRD]);
+    }
+
+    #[test]
+    fn right_click_removes_region() {
+        let mut fsm = three_regions();
+
+        // an inactive box
+        fsm.update_cursor(450.0, 320.0);
+        fsm.handle_mouse(MouseButton::Right, ButtonState::Pressed);
+        assert_eq!(fsm.all_bounds(), vec![FIRST, THIRD]);
+        assert_eq!(fsm.active_index(), Some(1));
+
+        // the active box: the one before it takes over
+        fsm.update_cursor(150.0, 420.0);
+        fsm.handle_mouse(MouseButton::Right, ButtonState::Pressed);
+        assert_eq!(fsm.all_bounds(), vec![FIRST]);
+        assert_eq!(fsm.selection_bounds(), Some(FIRST));
+        assert_eq!(fsm.phase, SelectionPhase::Confirmed);
+
+        // the last box
+        fsm.update_cursor(200.0, 150.0);
+        fsm.handle_mouse(MouseButton::Right, ButtonState::Pressed);
+        assert_eq!(fsm.phase, SelectionPhase::Idle);
+        assert!(fsm.all_bounds().is_empty());
+    }
+
+    #[test]
+    fn move_active_reorders() {
+        let mut fsm = three_regions();
+        fsm.move_active(-1);
+        assert_eq!(fsm.all_bounds(), vec![FIRST, THIRD, SECOND]);
+        fsm.move_active(-5);
+        assert_eq!(fsm.all_bounds(), vec![THIRD, FIRST, SECOND]);
+        fsm.move_active(1);
+        assert_eq!(fsm.all_bounds(), vec![FIRST, THIRD, SECOND]);
+        assert_eq!(fsm.active_index(), Some(1));
+    }
+
+    #[test]
+    fn regions_are_captured_in_reading_order() {
+        // two columns, the left one drawn first
+        let left = (100, 100, 50, 200);
+        let right = (300, 100, 50, 200);
+        let mut fsm = RegionSelectionState {
+            window_size: WindowSize::new(800, 600),
+            ..Default::default()
+        };
+        drag(&mut fsm, (100.0, 100.0), (150.0, 300.0));
+        drag(&mut fsm, (300.0, 100.0), (350.0, 300.0));
+
+        assert_eq!(fsm.all_bounds(), vec![right, left]);
+        assert_eq!(fsm.active_index(), Some(0));
+
+        // reordering by hand starts from the order shown and sticks
+        fsm.move_active(1);
+        assert_eq!(fsm.all_bounds(), vec![left, right]);
+        assert_eq!(fsm.active_index(), Some(1));
+        drag(&mut fsm, (500.0, 100.0), (550.0, 300.0));
+        assert_eq!(fsm.all_bounds(), vec![left, right, (500, 100, 50, 200)]);
+    }
+
+    #[test]
+    fn reset_drops_all_regions() {
+        let mut fsm = three_regions();
+        fsm.reset();
+        assert!(fsm.all_bounds().is_empty());
+        assert_eq!(fsm.active_index(), None);
+    }
+
+    /* ---------------- Property tests ---------------- */
+
+    #[derive(Debug, Clone)]
+    enum Input {
+        Cursor(f64, f64),
+        Mouse(MouseButton, ButtonState),
+        Arrow(ArrowKey, bool, bool),
+        ToggleAspect,
+        Reorder(isize),
+    }
+
+    fn input() -> impl Strategy<Value = Input> {
+        let button = prop_oneof![
+            4 => Just(MouseButton::Left),
+            1 => Just(MouseButton::Right),
+        ];
+        let state = prop_oneof![Just(ButtonState::Pressed), Just(ButtonState::Released)];
+        let arrow = prop_oneof![
+            Just(ArrowKey::Left),
+            Just(ArrowKey::Right),
+            Just(ArrowKey::Up),
+            Just(ArrowKey::Down),
+        ];
+
+        prop_oneof![
+            4 => (-200.0..3000.0f64, -200.0..3000.0f64).prop_map(|(x, y)| Input::Cursor(x, y)),
+            2 => (button, state).prop_map(|(b, s)| Input::Mouse(b, s)),
+            1 => (arrow, any::<bool>(), any::<bool>()).prop_map(|(k, r, l)| Input::Arrow(k, r, l)),
+            1 => Just(Input::ToggleAspect),
+            1 => (-2isize..=2).prop_map(Input::Reorder),
+        ]
+    }
+
+    fn apply(fsm: &mut RegionSelectionState, input: &Input) {
+        match *input {
+            Input::Cursor(x, y) => fsm.update_cursor(x, y),
+            Input::Mouse(button, state) => fsm.handle_mouse(button, state),
+            Input::Arrow(key, resize, large) => fsm.handle_arrow(key, resize, large),
+            Input::ToggleAspect => fsm.toggle_aspect_lock(),
+            Input::Reorder(delta) => fsm.move_active(delta),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn bounds_stay_inside_window(
+            width in 50u32..2500,
+            height in 50u32..2500,
+            inputs in prop::collection::vec(input(), 0..200),
+        ) {
+            let mut fsm = RegionSelectionState {
+                window_size: WindowSize::new(width, height),
+                ..Default::default()
+            };
+
+            for input in &inputs {
+                apply(&mut fsm, input);
+
+                for (x, y, w, h) in fsm.all_bounds() {
+                    prop_assert!(x + w <= width as usize, "x + w = {} after {:?}", x + w, input);
+                    prop_assert!(y + h <= height as usize, "y + h = {} after {:?}", y + h, input);
+                }
+                if fsm.phase != SelectionPhase::Idle {
+                    prop_assert!(fsm.selection_bounds().is_some());
+                }
             }
-            Err(err) => Err(format!("Screenshot failed: {err}")),
+        }
+
+        #[test]
+        fn reset_always_returns_to_idle(inputs in prop::collection::vec(input(), 0..100)) {
+            let mut fsm = RegionSelectionState {
+                window_size: WindowSize::new(800, 600),
+                ..Default::default()
+            };
+            for input in &inputs {
+                apply(&mut fsm, input);
+            }
+
+            fsm.reset();
+            prop_assert_eq!(&fsm.phase, &SelectionPhase::Idle);
+            prop_assert_eq!(fsm.selection_bounds(), None);
+            prop_assert!(fsm.all_bounds().is_empty());
+            prop_assert_eq!(fsm.aspect_ratio, None);
         }
     }
 }
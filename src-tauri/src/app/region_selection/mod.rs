@@ -4,12 +4,37 @@ pub mod controller;
 pub mod fsm;
 
 use crate::infra::screenshot::ScreenshotService;
+use crate::shared::models::ocr::ScreenRect;
 use fsm::RegionSelectionState;
+use screenshots::image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use screenshots::image::{ColorType, ImageEncoder, RgbaImage, imageops};
 use std::sync::Mutex;
 
+/// Screen grabbed before a frozen selection overlay opened.
+pub struct Snapshot {
+    pub image: RgbaImage,
+    /// Desktop position of the image's top-left corner, in physical pixels.
+    pub origin: (i32, i32),
+}
+
+impl Snapshot {
+    /// The image as a PNG for the overlay to paint. A whole desktop is tens
+    /// of MB as raw pixels, so it is compressed, favouring speed over size
+    /// since the overlay waits for it.
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let (w, h) = self.image.dimensions();
+        let mut png = Vec::new();
+        PngEncoder::new_with_quality(&mut png, CompressionType::Fast, FilterType::Sub)
+            .write_image(self.image.as_raw(), w, h, ColorType::Rgba8)
+            .map_err(|e| format!("Failed to encode the snapshot: {e}"))?;
+        Ok(png)
+    }
+}
+
 pub struct RSController {
     pub fsm: Mutex<RegionSelectionState>,
     screenshot: ScreenshotService,
+    snapshot: Option<Snapshot>,
 }
 
 impl RSController {
@@ -17,12 +42,46 @@ impl RSController {
         Self {
             fsm: Mutex::new(RegionSelectionState::default()),
             screenshot: ScreenshotService,
+            snapshot: None,
+        }
+    }
+
+    /// Selection on a still image: the overlay shows `snapshot` and captures
+    /// are cropped from it.
+    pub fn frozen(snapshot: Snapshot) -> Self {
+        Self {
+            snapshot: Some(snapshot),
+            ..Self::new()
         }
     }
+
+    /// `area` (physical desktop pixels) from the snapshot when frozen,
+    /// otherwise from the live screen.
+    fn capture(&self, area: ScreenRect) -> Result<RgbaImage, String> {
+        let Some(snapshot) = &self.snapshot else {
+            return self
+                .screenshot
+                .capture_region(area.x, area.y, area.w, area.h)
+                .map_err(|e| format!("Screenshot failed: {e}"));
+        };
+
+        let x = area.x - snapshot.origin.0;
+        let y = area.y - snapshot.origin.1;
+        let (w, h) = snapshot.image.dimensions();
+        if x < 0 || y < 0 || x as u32 >= w || y as u32 >= h {
+            return Err(format!(
+                "Region ({}, {}) is outside the frozen snapshot",
+                area.x, area.y
+            ));
+        }
+
+        Ok(imageops::crop_imm(&snapshot.image, x as u32, y as u32, area.w, area.h).to_image())
+    }
 }
 
 // Re-export commands
 pub use controller::{
-    rs_cursor, rs_do_capture, rs_get_state, rs_key_enter, rs_key_escape, rs_mousedown, rs_mouseup,
-    rs_ready, rs_set_window_size,
+    rs_cursor, rs_do_capture, rs_get_state, rs_key_arrow, rs_key_enter, rs_key_escape,
+    rs_mousedown, rs_mouseup, rs_ready, rs_reorder, rs_set_window_size, rs_snapshot,
+    rs_toggle_aspect_lock,
 };
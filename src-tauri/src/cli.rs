@@ -0,0 +1,383 @@
+// src-tauri/src/cli.rs
+//
+// Headless batch OCR: `umod ocr <dir> [options]`
+// Runs the same OCR pipeline as region capture over a folder of images,
+// without starting Tauri, and writes one result file per image. Engine,
+// device and preprocessing come from the OCR settings unless overridden.
+
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::infra;
+use crate::infra::ocr::cache::{self, OcrCache};
+use crate::infra::ocr::{OcrEngine, OcrOptions, run_ocr};
+use crate::infra::preprocess::{PreprocessPipeline, PreprocessPreset};
+use crate::infra::screenshot::{IMAGE_EXTENSIONS, load_image_file};
+use crate::infra::settings::{OcrSettings, SettingsStore};
+use crate::shared::models::ocr::OcrResult;
+
+const USAGE: &str = "\
+Usage: umod ocr <input-dir> [options]
+
+Options:
+  --out <dir>         Output directory (default: <input-dir>/umod-ocr)
+  --format <fmt>      json | tsv | both (default: json)
+  --engine <name>     OCR engine: manga-ocr
+  --preset <id>       Preprocessing preset: none, default, light_on_dark, busy_background
+  --split             Detect and OCR text blocks separately
+  --no-cache          Do not read or write the OCR cache
+  --gpu               Let Manga OCR use CUDA if available
+  --cpu               Run Manga OCR on the CPU
+
+Engine, preset, --split and the device default to the OCR settings.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Json,
+    Tsv,
+    Both,
+}
+
+struct BatchArgs {
+    input: PathBuf,
+    out: PathBuf,
+    format: OutputFormat,
+    engine: &'static dyn OcrEngine,
+    options: OcrOptions,
+    use_cache: bool,
+    force_cpu: bool,
+}
+
+/// Entry point for `umod ocr ...`; `args` excludes the binary name and `ocr`.
+/// Returns the process exit code.
+pub fn run_ocr_command(args: &[String]) -> i32 {
+    #[cfg(windows)]
+    attach_parent_console();
+
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{USAGE}");
+        return 0;
+    }
+
+    let settings = SettingsStore::open_default().get().ocr;
+    let parsed = match parse_args(args, &settings) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return 2;
+        }
+    };
+
+    match run_batch(&parsed) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("umod ocr: {e:#}");
+            1
+        }
+    }
+}
+
+/// Release builds use the Windows GUI subsystem and start without a console;
+/// attach to the console `umod ocr` was run from so its output shows up.
+#[cfg(windows)]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    // SAFETY: AttachConsole takes no pointers; when there is no parent
+    // console (e.g. started from Explorer) it fails and nothing changes.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Parse the `ocr` arguments; options not given come from `settings`.
+fn parse_args(args: &[String], settings: &OcrSettings) -> Result<BatchArgs> {
+    let mut input = None;
+    let mut out = None;
+    let mut format = OutputFormat::Json;
+    let mut engine = settings.engine.clone();
+    let mut options = OcrOptions {
+        preprocess: PreprocessPipeline::from_preset(settings.preprocess),
+        split_blocks: settings.split_blocks,
+    };
+    let mut use_cache = true;
+    let mut force_cpu = settings.device.force_cpu();
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--out" => out = Some(PathBuf::from(it.next().context("--out needs a value")?)),
+            "--format" => {
+                format = match it.next().map(String::as_str) {
+                    Some("json") => OutputFormat::Json,
+                    Some("tsv") => OutputFormat::Tsv,
+                    Some("both") => OutputFormat::Both,
+                    other => bail!("unknown format: {}", other.unwrap_or("<none>")),
+                }
+            }
+            "--engine" => engine = it.next().context("--engine needs a value")?.clone(),
+            "--preset" => {
+                let id = it.next().context("--preset needs a value")?;
+                let preset = PreprocessPreset::from_id(id)
+                    .with_context(|| format!("unknown preset: {id}"))?;
+                options.preprocess = PreprocessPipeline::from_preset(preset);
+            }
+            "--split" => options.split_blocks = true,
+            "--no-cache" => use_cache = false,
+            "--gpu" => force_cpu = false,
+            "--cpu" => force_cpu = true,
+            flag if flag.starts_with("--") => bail!("unknown option: {flag}"),
+            path => {
+                if input.replace(PathBuf::from(path)).is_some() {
+                    bail!("only one input directory is supported");
+                }
+            }
+        }
+    }
+
+    let input = input.context("missing input directory")?;
+    let out = out.unwrap_or_else(|| input.join("umod-ocr"));
+    let engine =
+        infra::ocr_engine(&engine).with_context(|| format!("unknown OCR engine: {engine}"))?;
+
+    Ok(BatchArgs {
+        input,
+        out,
+        format,
+        engine,
+        options,
+        use_cache,
+        force_cpu,
+    })
+}
+
+fn run_batch(args: &BatchArgs) -> Result<()> {
+    let images = list_images(&args.input)?;
+    if images.is_empty() {
+        bail!("no images found in {}", args.input.display());
+    }
+    std::fs::create_dir_all(&args.out)
+        .with_context(|| format!("cannot create {}", args.out.display()))?;
+
+    println!("Initializing OCR, please wait...");
+    infra::init_ocr(args.force_cpu)?;
+
+    let cache = match (args.use_cache, OcrCache::default_path()) {
+        (true, Some(path)) => Some(OcrCache::open(path, cache::DEFAULT_TOLERANCE)),
+        _ => None,
+    };
+
+    let started = Instant::now();
+    let mut failed = 0usize;
+
+    for (i, path) in images.iter().enumerate() {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+        let result = load_image_file(path)
+            .and_then(|img| run_ocr(args.engine, cache.as_ref(), &img, (0, 0), &args.options))
+            .and_then(|result| write_result(args, path, &result).map(|_| result));
+
+        match result {
+            Ok(result) => println!(
+                "[{}/{}] {name}: {} blocks, {} ms",
+                i + 1,
+                images.len(),
+                result.blocks.len(),
+                result.elapsed_ms
+            ),
+            Err(e) => {
+                failed += 1;
+                eprintln!("[{}/{}] {name}: {e:#}", i + 1, images.len());
+            }
+        }
+    }
+
+    if let Some(cache) = &cache {
+        cache.flush();
+    }
+
+    let secs = started.elapsed().as_secs_f64();
+    let done = images.len() - failed;
+    println!(
+        "Processed {done}/{} images in {secs:.1}s ({:.2} images/s) -> {}",
+        images.len(),
+        done as f64 / secs.max(f64::EPSILON),
+        args.out.display()
+    );
+
+    if failed > 0 {
+        bail!("{failed} image(s) failed");
+    }
+    Ok(())
+}
+
+/// Supported images directly inside `dir`, sorted by file name.
+fn list_images(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut images: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("cannot read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        })
+        .collect();
+
+    images.sort();
+    Ok(images)
+}
+
+fn write_result(args: &BatchArgs, image: &Path, result: &OcrResult) -> Result<()> {
+    if matches!(args.format, OutputFormat::Json | OutputFormat::Both) {
+        let path = output_path(&args.out, image, "json");
+        std::fs::write(&path, serde_json::to_vec_pretty(result)?)?;
+    }
+
+    if matches!(args.format, OutputFormat::Tsv | OutputFormat::Both) {
+        let path = output_path(&args.out, image, "tsv");
+        std::fs::write(&path, to_tsv(result))?;
+    }
+
+    Ok(())
+}
+
+/// `<out>/<image file name>.<extension>`; the image's own extension is kept
+/// so `a.png` and `a.jpg` do not overwrite each other's results.
+fn output_path(out: &Path, image: &Path, extension: &str) -> PathBuf {
+    let name = image.file_name().unwrap_or_default().to_string_lossy();
+    out.join(format!("{name}.{extension}"))
+}
+
+/// One row per block: index, bounds, orientation, confidence, text.
+fn to_tsv(result: &OcrResult) -> String {
+    let mut out = String::from("block\tx\ty\tw\th\torientation\tconfidence\ttext\n");
+
+    for (i, block) in result.blocks.iter().enumerate() {
+        let orientation = block.orientation.as_str();
+        let confidence = block.confidence.map(|c| c.to_string()).unwrap_or_default();
+        let text = block.text.replace(['\t', '\n', '\r'], " ");
+
+        out.push_str(&format!(
+            "{i}\t{}\t{}\t{}\t{}\t{orientation}\t{confidence}\t{text}\n",
+            block.bounds.x, block.bounds.y, block.bounds.w, block.bounds.h
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::settings::OcrDevice;
+    use crate::shared::models::ocr::{OcrBlock, Orientation, ScreenRect};
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn options_default_to_the_settings() {
+        let settings = OcrSettings {
+            device: OcrDevice::Cuda,
+            preprocess: PreprocessPreset::LightOnDark,
+            split_blocks: true,
+            ..OcrSettings::default()
+        };
+
+        let parsed = parse_args(&args("pages"), &settings).unwrap();
+        assert_eq!(parsed.input, Path::new("pages"));
+        assert_eq!(parsed.out, Path::new("pages/umod-ocr"));
+        assert_eq!(parsed.format, OutputFormat::Json);
+        let manga_ocr = infra::ocr_engine("manga-ocr").unwrap();
+        assert_eq!(parsed.engine.name(), manga_ocr.name());
+        assert_eq!(
+            parsed.options.preprocess,
+            PreprocessPipeline::from_preset(PreprocessPreset::LightOnDark)
+        );
+        assert!(parsed.options.split_blocks);
+        assert!(!parsed.force_cpu);
+        assert!(parsed.use_cache);
+    }
+
+    #[test]
+    fn flags_override_the_settings() {
+        let line = "--out results pages --format both --preset none --cpu --no-cache";
+        let parsed = parse_args(&args(line), &OcrSettings::default()).unwrap();
+
+        assert_eq!(parsed.out, Path::new("results"));
+        assert_eq!(parsed.format, OutputFormat::Both);
+        assert_eq!(
+            parsed.options.preprocess,
+            PreprocessPipeline::from_preset(PreprocessPreset::None)
+        );
+        assert!(parsed.force_cpu);
+        assert!(!parsed.use_cache);
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        let settings = OcrSettings::default();
+        for line in [
+            "",
+            "a b",
+            "a --format xml",
+            "a --preset sharp",
+            "a --engine tesseract",
+            "a --verbose",
+            "a --out",
+        ] {
+            assert!(parse_args(&args(line), &settings).is_err(), "{line:?}");
+        }
+    }
+
+    #[test]
+    fn tsv_has_one_row_per_block() {
+        let block = |text: &str, confidence| OcrBlock {
+            text: text.into(),
+            lines: vec![text.into()],
+            bounds: ScreenRect {
+                x: 1,
+                y: 2,
+                w: 30,
+                h: 40,
+            },
+            orientation: Orientation::Vertical,
+            confidence,
+        };
+        let result = OcrResult {
+            text: String::new(),
+            blocks: vec![block("一\t二\n三", Some(0.5)), block("四", None)],
+            orientation: Orientation::Vertical,
+            engine: "manga-ocr".into(),
+            confidence: Some(0.5),
+            elapsed_ms: 0,
+        };
+
+        assert_eq!(
+            to_tsv(&result),
+            "block\tx\ty\tw\th\torientation\tconfidence\ttext\n\
+             0\t1\t2\t30\t40\tvertical\t0.5\t一 二 三\n\
+             1\t1\t2\t30\t40\tvertical\t\t四\n"
+        );
+    }
+
+    #[test]
+    fn output_names_keep_the_image_extension() {
+        let out = Path::new("out");
+        assert_eq!(
+            output_path(out, Path::new("in/a.png"), "json"),
+            Path::new("out/a.png.json")
+        );
+        assert_ne!(
+            output_path(out, Path::new("in/a.png"), "tsv"),
+            output_path(out, Path::new("in/a.jpg"), "tsv")
+        );
+    }
+}
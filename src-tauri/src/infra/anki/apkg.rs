@@ -0,0 +1,563 @@
+// src-tauri/src/infra/anki/apkg.rs
+//
+// Offline export of notes as an `.apkg` deck package, for Anki without
+// AnkiConnect. A package is a zip of `collection.anki2`, a SQLite collection
+// in Anki's legacy (schema 11) format which every Anki version imports, and
+// its media: files named "0", "1", ... with a `media` JSON map back to their
+// real names.
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, params};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use super::{Note, NoteContent, uses_screenshot};
+use crate::infra::settings::AnkiSettings;
+
+/// Fields Anki fills in itself in card templates.
+const SPECIAL_FIELDS: [&str; 7] = [
+    "FrontSide",
+    "Tags",
+    "Type",
+    "Deck",
+    "Subdeck",
+    "Card",
+    "CardFlag",
+];
+
+/// Anki's field separator in `notes.flds`.
+const FIELD_SEPARATOR: &str = "\x1f";
+
+/// Characters of Anki's base91 note GUIDs.
+const GUID_CHARS: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!#$%&()*+,-./:;<=>?@[]^_`{|}~";
+
+const SCHEMA: &str = "
+    CREATE TABLE col (
+        id integer PRIMARY KEY, crt integer NOT NULL, mod integer NOT NULL,
+        scm integer NOT NULL, ver integer NOT NULL, dty integer NOT NULL,
+        usn integer NOT NULL, ls integer NOT NULL, conf text NOT NULL,
+        models text NOT NULL, decks text NOT NULL, dconf text NOT NULL,
+        tags text NOT NULL
+    );
+    CREATE TABLE notes (
+        id integer PRIMARY KEY, guid text NOT NULL, mid integer NOT NULL,
+        mod integer NOT NULL, usn integer NOT NULL, tags text NOT NULL,
+        flds text NOT NULL, sfld text NOT NULL, csum integer NOT NULL,
+        flags integer NOT NULL, data text NOT NULL
+    );
+    CREATE TABLE cards (
+        id integer PRIMARY KEY, nid integer NOT NULL, did integer NOT NULL,
+        ord integer NOT NULL, mod integer NOT NULL, usn integer NOT NULL,
+        type integer NOT NULL, queue integer NOT NULL, due integer NOT NULL,
+        ivl integer NOT NULL, factor integer NOT NULL, reps integer NOT NULL,
+        lapses integer NOT NULL, left integer NOT NULL, odue integer NOT NULL,
+        odid integer NOT NULL, flags integer NOT NULL, data text NOT NULL
+    );
+    CREATE TABLE revlog (
+        id integer PRIMARY KEY, cid integer NOT NULL, usn integer NOT NULL,
+        ease integer NOT NULL, ivl integer NOT NULL, lastIvl integer NOT NULL,
+        factor integer NOT NULL, time integer NOT NULL, type integer NOT NULL
+    );
+    CREATE TABLE graves (
+        usn integer NOT NULL, oid integer NOT NULL, type integer NOT NULL
+    );
+    CREATE INDEX ix_notes_usn ON notes (usn);
+    CREATE INDEX ix_cards_usn ON cards (usn);
+    CREATE INDEX ix_revlog_usn ON revlog (usn);
+    CREATE INDEX ix_cards_nid ON cards (nid);
+    CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+    CREATE INDEX ix_revlog_cid ON revlog (cid);
+    CREATE INDEX ix_notes_csum ON notes (csum);
+";
+
+/// Write a deck package with a note for each of `notes` to `path`, using the
+/// deck, fields and `.apkg` note type of `settings`. Unless duplicates are
+/// allowed, notes with the same sort field as an earlier one are left out.
+/// Returns how many notes were written.
+pub fn write_apkg(path: &Path, settings: &AnkiSettings, notes: &[NoteContent]) -> Result<usize> {
+    let collection_path = path.with_extension("anki2.tmp");
+    let _ = std::fs::remove_file(&collection_path);
+
+    let written = write_collection(&collection_path, settings, notes).and_then(|media| {
+        let collection = std::fs::read(&collection_path).context("reading collection")?;
+        write_package(path, &collection, &media)?;
+        Ok(media.notes)
+    });
+    let _ = std::fs::remove_file(&collection_path);
+    written
+}
+
+/// Field names referenced by a card template, e.g. "Front" for `{{Front}}`,
+/// `{{#Front}}` or `{{text:Front}}`. Fields Anki fills in itself are skipped.
+pub fn card_fields(template: &str) -> impl Iterator<Item = &str> {
+    template
+        .split("{{")
+        .skip(1)
+        .filter_map(|s| s.split_once("}}"))
+        .map(|(tag, _)| {
+            let tag = tag.trim().trim_start_matches(['#', '^', '/']);
+            tag.rsplit(':').next().unwrap_or(tag).trim()
+        })
+        .filter(|name| !name.is_empty() && !SPECIAL_FIELDS.contains(name))
+}
+
+/// Check that a card template only references fields in `fields`.
+pub fn check_card_template(
+    template: &str,
+    fields: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    match card_fields(template).find(|name| !fields.contains_key(*name)) {
+        Some(name) => Err(format!(
+            "unknown field {{{{{name}}}}}, the note has {}",
+            fields.keys().cloned().collect::<Vec<_>>().join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// What goes into the package besides the collection.
+struct Media {
+    notes: usize,
+    /// File name -> content.
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+fn write_collection(path: &Path, settings: &AnkiSettings, notes: &[NoteContent]) -> Result<Media> {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    let now = now_ms / 1000;
+
+    let fields = field_order(settings);
+    let mut model = vec![
+        settings.apkg.note_type.as_str(),
+        &settings.apkg.front,
+        &settings.apkg.back,
+    ];
+    model.extend(&fields);
+    let model_id = stable_id(&model);
+    let deck_id = stable_id(&[&settings.deck]);
+
+    let mut conn = Connection::open(path).context("creating collection")?;
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?3, 11, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        params![
+            now,
+            now_ms,
+            now_ms,
+            collection_conf(deck_id, model_id).to_string(),
+            json!({ model_id.to_string(): model_json(settings, &fields, model_id, deck_id, now) })
+                .to_string(),
+            json!({
+                "1": deck_json(1, "Default", now),
+                deck_id.to_string(): deck_json(deck_id, &settings.deck, now),
+            })
+            .to_string(),
+            json!({ "1": deck_options() }).to_string(),
+        ],
+    )?;
+
+    let with_screenshots = uses_screenshot(settings);
+    let mut media = Media {
+        notes: 0,
+        files: BTreeMap::new(),
+    };
+    let mut seen = HashSet::new();
+
+    for (i, content) in notes.iter().enumerate() {
+        let note = Note::build(settings, content);
+        let values: Vec<&str> = fields
+            .iter()
+            .map(|name| note.fields.get(*name).map_or("", String::as_str))
+            .collect();
+        let sort_field = strip_html(values.first().copied().unwrap_or_default());
+        let key = if settings.allow_duplicates {
+            format!("{i}\x1f{}", values.join(FIELD_SEPARATOR))
+        } else {
+            sort_field.clone()
+        };
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+
+        let id = now_ms + media.notes as i64;
+        tx.execute(
+            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ' umod ', ?5, ?6, ?7, 0, '')",
+            params![
+                id,
+                guid(&[&settings.apkg.note_type, &key]),
+                model_id,
+                now,
+                values.join(FIELD_SEPARATOR),
+                sort_field,
+                checksum(&sort_field),
+            ],
+        )?;
+        // a new card, due in the order of the export
+        tx.execute(
+            "INSERT INTO cards VALUES (?1, ?1, ?2, 0, ?3, -1, 0, 0, ?4, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            params![id, deck_id, now, media.notes as i64 + 1],
+        )?;
+        media.notes += 1;
+
+        if with_screenshots
+            && let (Some(file), Some(png)) = (content.screenshot_file(), &content.screenshot)
+        {
+            media.files.entry(file).or_insert_with(|| png.clone());
+        }
+    }
+
+    tx.commit()?;
+    Ok(media)
+}
+
+fn write_package(path: &Path, collection: &[u8], media: &Media) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("collection.anki2", options)?;
+    zip.write_all(collection)?;
+
+    let mut names = serde_json::Map::new();
+    for (i, (name, data)) in media.files.iter().enumerate() {
+        zip.start_file(i.to_string(), options)?;
+        zip.write_all(data)?;
+        names.insert(i.to_string(), Value::from(name.as_str()));
+    }
+    zip.start_file("media", options)?;
+    zip.write_all(Value::Object(names).to_string().as_bytes())?;
+
+    zip.finish().context("writing package")?;
+    Ok(())
+}
+
+/// Note type fields in order: those on the front of the card first, as they
+/// appear there, so the first one becomes the sort field; then the others.
+fn field_order(settings: &AnkiSettings) -> Vec<&str> {
+    let mut order: Vec<&str> = Vec::new();
+    let front = card_fields(&settings.apkg.front);
+    for name in front.chain(settings.fields.keys().map(String::as_str)) {
+        if settings.fields.contains_key(name) && !order.contains(&name) {
+            order.push(name);
+        }
+    }
+    order
+}
+
+fn model_json(
+    settings: &AnkiSettings,
+    fields: &[&str],
+    model_id: i64,
+    deck_id: i64,
+    now: i64,
+) -> Value {
+    let front: Vec<usize> = card_fields(&settings.apkg.front)
+        .filter_map(|name| fields.iter().position(|f| *f == name))
+        .collect();
+    json!({
+        "id": model_id,
+        "name": settings.apkg.note_type,
+        "type": 0,
+        "mod": now,
+        "usn": -1,
+        "sortf": 0,
+        "did": deck_id,
+        "tmpls": [{
+            "name": "Card 1",
+            "ord": 0,
+            "qfmt": settings.apkg.front,
+            "afmt": settings.apkg.back,
+            "bqfmt": "",
+            "bafmt": "",
+            "did": null,
+        }],
+        "flds": fields.iter().enumerate().map(|(ord, name)| json!({
+            "name": name,
+            "ord": ord,
+            "sticky": false,
+            "rtl": false,
+            "font": "Arial",
+            "size": 20,
+            "media": [],
+        })).collect::<Vec<_>>(),
+        "css": settings.apkg.css,
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\
+                     \\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\
+                     \\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "tags": [],
+        "vers": [],
+        "req": [[0, "any", front]],
+    })
+}
+
+fn deck_json(id: i64, name: &str, now: i64) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "desc": "",
+        "mod": now,
+        "usn": -1,
+        "collapsed": false,
+        "dyn": 0,
+        "conf": 1,
+        "extendNew": 10,
+        "extendRev": 50,
+        "newToday": [0, 0],
+        "revToday": [0, 0],
+        "lrnToday": [0, 0],
+        "timeToday": [0, 0],
+    })
+}
+
+fn deck_options() -> Value {
+    json!({
+        "id": 1,
+        "name": "Default",
+        "mod": 0,
+        "usn": 0,
+        "maxTaken": 60,
+        "autoplay": true,
+        "timer": 0,
+        "replayq": true,
+        "new": {
+            "bury": true,
+            "delays": [1, 10],
+            "initialFactor": 2500,
+            "ints": [1, 4, 7],
+            "order": 1,
+            "perDay": 20,
+            "separate": true,
+        },
+        "lapse": {
+            "delays": [10],
+            "leechAction": 0,
+            "leechFails": 8,
+            "minInt": 1,
+            "mult": 0,
+        },
+        "rev": {
+            "bury": true,
+            "ease4": 1.3,
+            "fuzz": 0.05,
+            "ivlFct": 1,
+            "maxIvl": 36500,
+            "minSpace": 1,
+            "perDay": 100,
+        },
+    })
+}
+
+fn collection_conf(deck_id: i64, model_id: i64) -> Value {
+    json!({
+        "activeDecks": [deck_id],
+        "curDeck": deck_id,
+        "curModel": model_id,
+        "newSpread": 0,
+        "collapseTime": 1200,
+        "timeLim": 0,
+        "estTimes": true,
+        "dueCounts": true,
+        "nextPos": 1,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true,
+    })
+}
+
+/// An id that stays the same across exports, so importing again updates the
+/// deck and note type instead of adding new ones. Kept below 2^52 for
+/// Anki's JavaScript.
+fn stable_id(parts: &[&str]) -> i64 {
+    (digest(parts) >> 12) as i64 + 1
+}
+
+/// Note GUID; the same for the same note, so a word exported twice updates
+/// its note on import.
+fn guid(parts: &[&str]) -> String {
+    let mut n = digest(parts);
+
+    let mut out = Vec::new();
+    while n > 0 {
+        out.push(GUID_CHARS[(n % GUID_CHARS.len() as u64) as usize]);
+        n /= GUID_CHARS.len() as u64;
+    }
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// The first 8 bytes of the SHA-1 of `parts`, each written as its length (4
+/// bytes, big-endian) and UTF-8 bytes; unlike `std::hash` this never changes
+/// between Rust releases.
+fn digest(parts: &[&str]) -> u64 {
+    let mut sha = sha1_smol::Sha1::new();
+    for part in parts {
+        sha.update(&(part.len() as u32).to_be_bytes());
+        sha.update(part.as_bytes());
+    }
+    let bytes = sha.digest().bytes();
+    u64::from_be_bytes([
+        bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+    ])
+}
+
+/// Anki's duplicate check value: the first 8 hex digits of the SHA-1 of the
+/// sort field.
+fn checksum(sort_field: &str) -> i64 {
+    let digest = sha1_smol::Sha1::from(sort_field).digest().bytes();
+    i64::from(u32::from_be_bytes([
+        digest[0], digest[1], digest[2], digest[3],
+    ]))
+}
+
+/// Field HTML as plain text, like Anki keeps the sort field.
+pub(super) fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    fn content(headword: &str, screenshot: Option<Vec<u8>>) -> NoteContent {
+        NoteContent {
+            headword: headword.into(),
+            senses: vec![vec!["meaning".into()]],
+            screenshot,
+            ..NoteContent::default()
+        }
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("umod-{}-{name}", std::process::id()))
+    }
+
+    fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        archive
+            .by_name(name)
+            .unwrap_or_else(|_| panic!("no {name} in the package"))
+            .read_to_end(&mut data)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn writes_notes_and_media() {
+        let path = temp_path("export.apkg");
+        let mut settings = AnkiSettings::default();
+        settings
+            .fields
+            .insert("Sentence".into(), "{sentence}".into());
+        let notes = [
+            content("食べる", Some(vec![1, 2, 3])),
+            content("分かる", None),
+            content("食べる", Some(vec![4])),
+        ];
+
+        assert_eq!(write_apkg(&path, &settings, &notes).unwrap(), 2);
+
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let media: Value = serde_json::from_slice(&read_entry(&mut archive, "media")).unwrap();
+        let file = notes[0].screenshot_file().unwrap();
+        assert_eq!(media, json!({ "0": file }));
+        assert_eq!(read_entry(&mut archive, "0"), [1, 2, 3]);
+
+        let collection = temp_path("export.anki2");
+        std::fs::write(&collection, read_entry(&mut archive, "collection.anki2")).unwrap();
+        let conn = Connection::open(&collection).unwrap();
+
+        let models: String = conn
+            .query_row("SELECT models FROM col", [], |r| r.get(0))
+            .unwrap();
+        let models: Value = serde_json::from_str(&models).unwrap();
+        let model = models.as_object().unwrap().values().next().unwrap();
+        assert_eq!(model["name"], "UMOD");
+        let names: Vec<&str> = model["flds"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["name"].as_str().unwrap())
+            .collect();
+        // the front field sorts first
+        assert_eq!(names, ["Front", "Back", "Sentence"]);
+
+        let notes: Vec<(String, String, i64)> = conn
+            .prepare("SELECT flds, sfld, csum FROM notes ORDER BY id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(notes.len(), 2);
+        let fields: Vec<&str> = notes[0].0.split(FIELD_SEPARATOR).collect();
+        assert_eq!(fields[0], "食べる");
+        assert!(fields[1].contains(&format!(r#"<img src="{file}">"#)));
+        assert_eq!(notes[1].1, "分かる");
+        // sha1("分かる") = 08cea58f...
+        assert_eq!(notes[1].2, 0x08cea58f);
+
+        let cards: i64 = conn
+            .query_row("SELECT count(*) FROM cards", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(cards, 2);
+
+        drop(conn);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&collection);
+    }
+
+    #[test]
+    fn card_templates_reference_fields() {
+        let fields = AnkiSettings::default().fields;
+        let template = "{{#Back}}{{text:Back}}{{/Back}} {{Front}} {{FrontSide}} {{Tags}}";
+
+        assert_eq!(
+            card_fields(template).collect::<Vec<_>>(),
+            ["Back", "Back", "Back", "Front"]
+        );
+        assert_eq!(check_card_template(template, &fields), Ok(()));
+        let err = check_card_template("{{Reading}}", &fields).unwrap_err();
+        assert_eq!(err, "unknown field {{Reading}}, the note has Back, Front");
+    }
+
+    #[test]
+    fn ids_are_fixed_for_the_same_values() {
+        assert_eq!(stable_id(&["UMOD"]), 1500365678016935);
+        assert_eq!(guid(&["UMOD", "分かる"]), "0T!^F,`0eA");
+        assert_ne!(stable_id(&["ab", "c"]), stable_id(&["a", "bc"]));
+    }
+
+    #[test]
+    fn sort_field_is_plain_text() {
+        assert_eq!(strip_html("<b>a &amp; b</b><br>&lt;c&gt;"), "a & b<c>");
+    }
+}
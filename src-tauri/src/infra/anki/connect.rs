@@ -0,0 +1,414 @@
+// src-tauri/src/infra/anki/connect.rs
+//
+// Client for AnkiConnect (or anything speaking its protocol): every request
+// is a POST of `{ action, version, params }`, answered with
+// `{ result, error }`. Used to add cards and to read which words the user
+// has studied in Anki.
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+use super::apkg::strip_html;
+use super::{Note, NoteContent, uses_screenshot};
+use crate::infra::settings::AnkiSettings;
+
+/// AnkiConnect API version the requests are written for.
+const API_VERSION: u32 = 6;
+
+/// Error AnkiConnect gives for a note that is already in the deck.
+const DUPLICATE_ERROR: &str = "cannot create note because it is a duplicate";
+
+/// Cards asked for per `cardsInfo` request.
+const CARDS_PER_REQUEST: usize = 500;
+
+/// Card type of cards never studied.
+const NEW_CARD: u8 = 0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AddOutcome {
+    Added {
+        note_id: i64,
+    },
+    /// The deck already has a note for this word; nothing was added.
+    Duplicate,
+}
+
+/// What `cardsInfo` tells about a card, in part.
+#[derive(Deserialize)]
+struct CardInfo {
+    fields: BTreeMap<String, CardField>,
+    /// Days; negative (seconds) for cards still in their learning steps.
+    interval: i64,
+    #[serde(rename = "type")]
+    card_type: u8,
+}
+
+#[derive(Deserialize)]
+struct CardField {
+    value: String,
+}
+
+pub struct AnkiConnect {
+    client: Client,
+    url: String,
+}
+
+impl AnkiConnect {
+    /// Client for the AnkiConnect server at `url` (e.g. `DEFAULT_URL`).
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.into(),
+        }
+    }
+
+    fn request<T: DeserializeOwned>(&self, action: &str, params: Value) -> Result<T> {
+        let body = json!({ "action": action, "version": API_VERSION, "params": params });
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .with_context(|| {
+                format!(
+                    "AnkiConnect not reachable at {}; is Anki running?",
+                    self.url
+                )
+            })?
+            .json()
+            .context("AnkiConnect sent an invalid response")?;
+
+        if let Some(error) = response.get("error").and_then(Value::as_str) {
+            bail!("{action}: {error}");
+        }
+        let result = response.get("result").cloned().unwrap_or(Value::Null);
+        serde_json::from_value(result).with_context(|| format!("{action}: unexpected result"))
+    }
+
+    /// Create `deck` unless it exists. Returns its id.
+    pub fn create_deck(&self, deck: &str) -> Result<i64> {
+        self.request("createDeck", json!({ "deck": deck }))
+    }
+
+    pub fn model_field_names(&self, note_type: &str) -> Result<Vec<String>> {
+        self.request("modelFieldNames", json!({ "modelName": note_type }))
+    }
+
+    /// `Ok(None)` when `note` can be added, otherwise Anki's reason.
+    pub fn add_blocker(&self, note: &Note) -> Result<Option<String>> {
+        let [detail]: [Value; 1] = self.request(
+            "canAddNotesWithErrorDetail",
+            json!({ "notes": [note_json(note, false)] }),
+        )?;
+        if detail.get("canAdd").and_then(Value::as_bool) == Some(true) {
+            return Ok(None);
+        }
+        let error = detail.get("error").and_then(Value::as_str);
+        Ok(Some(error.unwrap_or("cannot add note").to_string()))
+    }
+
+    /// Store `data` in Anki's media folder. Returns the stored file name.
+    pub fn store_media_file(&self, filename: &str, data: &[u8]) -> Result<String> {
+        self.request(
+            "storeMediaFile",
+            json!({ "filename": filename, "data": BASE64.encode(data) }),
+        )
+    }
+
+    pub fn add_note(&self, note: &Note, allow_duplicate: bool) -> Result<i64> {
+        self.request(
+            "addNote",
+            json!({ "note": note_json(note, allow_duplicate) }),
+        )
+    }
+
+    /// The words in field `field` of the cards matching `query` (Anki's
+    /// search syntax, e.g. `deck:Japanese`) that have been studied, each with
+    /// the longest interval of its cards in days.
+    pub fn studied_words(&self, query: &str, field: &str) -> Result<Vec<(String, f64)>> {
+        let ids: Vec<i64> = self.request("findCards", json!({ "query": query }))?;
+        let mut words = BTreeMap::new();
+        let mut has_field = ids.is_empty();
+
+        for chunk in ids.chunks(CARDS_PER_REQUEST) {
+            let cards: Vec<CardInfo> = self.request("cardsInfo", json!({ "cards": chunk }))?;
+            for card in cards {
+                let Some(value) = card.fields.get(field) else {
+                    continue;
+                };
+                has_field = true;
+                let term = field_text(&value.value);
+                if card.card_type == NEW_CARD || term.is_empty() {
+                    continue;
+                }
+                let days = card.interval.max(0) as f64;
+                let longest = words.entry(term).or_insert(days);
+                *longest = longest.max(days);
+            }
+        }
+
+        if !has_field {
+            bail!("no card matching \"{query}\" has a field \"{field}\"");
+        }
+        Ok(words.into_iter().collect())
+    }
+
+    /// Add a card for `content` as configured in `settings`: the note type
+    /// must have the configured fields, the deck is created if needed and,
+    /// unless duplicates are allowed, a word already in the deck is skipped.
+    pub fn add_card(&self, settings: &AnkiSettings, content: &NoteContent) -> Result<AddOutcome> {
+        let fields = self.model_field_names(&settings.note_type)?;
+        if let Some(missing) = settings.fields.keys().find(|f| !fields.contains(f)) {
+            bail!(
+                "note type \"{}\" has no field \"{missing}\" (it has {})",
+                settings.note_type,
+                fields.join(", ")
+            );
+        }
+
+        let note = Note::build(settings, content);
+        self.create_deck(&note.deck)?;
+
+        if !settings.allow_duplicates {
+            match self.add_blocker(&note)? {
+                Some(error) if error.contains(DUPLICATE_ERROR) => return Ok(AddOutcome::Duplicate),
+                Some(error) => return Err(anyhow!("{error}")),
+                None => {}
+            }
+        }
+
+        if uses_screenshot(settings)
+            && let (Some(file), Some(png)) = (content.screenshot_file(), &content.screenshot)
+        {
+            self.store_media_file(&file, png)?;
+        }
+
+        let note_id = self.add_note(&note, settings.allow_duplicates)?;
+        Ok(AddOutcome::Added { note_id })
+    }
+}
+
+/// Plain text of a field, without furigana written as `食[た]べる`.
+fn field_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_reading = false;
+    for c in strip_html(html).chars() {
+        match c {
+            '[' => in_reading = true,
+            ']' => in_reading = false,
+            _ if in_reading || c.is_whitespace() => {}
+            _ => text.push(c),
+        }
+    }
+    text
+}
+
+fn note_json(note: &Note, allow_duplicate: bool) -> Value {
+    json!({
+        "deckName": note.deck,
+        "modelName": note.note_type,
+        "fields": note.fields,
+        "options": {
+            "allowDuplicate": allow_duplicate,
+            "duplicateScope": "deck",
+        },
+        "tags": ["umod"],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Requests received by a stand-in server, in order.
+    type Received = Arc<Mutex<Vec<Value>>>;
+
+    /// Local stand-in for AnkiConnect: answers each request with
+    /// `respond(action, params)` as `{ result, error }`.
+    fn stand_in(respond: fn(&str, &Value) -> Value) -> (String, Received) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Received::default();
+
+        let log = received.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&mut stream);
+
+                let mut length = 0;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                    line.clear();
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let reply = respond(request["action"].as_str().unwrap(), &request["params"]);
+                log.lock().unwrap().push(request);
+
+                let reply = reply.to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+                    reply.len()
+                )
+                .unwrap();
+            }
+        });
+
+        (url, received)
+    }
+
+    fn ok(result: Value) -> Value {
+        json!({ "result": result, "error": null })
+    }
+
+    fn anki(action: &str, params: &Value) -> Value {
+        match action {
+            "modelFieldNames" => ok(json!(["Front", "Back"])),
+            "createDeck" => ok(json!(1)),
+            "canAddNotesWithErrorDetail" => {
+                let front = &params["notes"][0]["fields"]["Front"];
+                if front == "分かる" {
+                    ok(json!([{ "canAdd": false, "error": DUPLICATE_ERROR }]))
+                } else {
+                    ok(json!([{ "canAdd": true }]))
+                }
+            }
+            "storeMediaFile" => ok(params["filename"].clone()),
+            "addNote" => ok(json!(1234)),
+            _ => json!({ "result": null, "error": "unsupported action" }),
+        }
+    }
+
+    fn actions(received: &Received) -> Vec<String> {
+        let requests = received.lock().unwrap();
+        requests
+            .iter()
+            .map(|r| r["action"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn content(headword: &str) -> NoteContent {
+        NoteContent {
+            headword: headword.into(),
+            sentence: Some(format!("{headword}よ")),
+            screenshot: Some(vec![1, 2, 3]),
+            ..NoteContent::default()
+        }
+    }
+
+    #[test]
+    fn adds_a_card_with_its_screenshot() {
+        let (url, received) = stand_in(anki);
+        let settings = AnkiSettings::default();
+
+        let outcome = AnkiConnect::new(url).add_card(&settings, &content("食べる"));
+
+        assert_eq!(outcome.unwrap(), AddOutcome::Added { note_id: 1234 });
+        assert_eq!(
+            actions(&received),
+            [
+                "modelFieldNames",
+                "createDeck",
+                "canAddNotesWithErrorDetail",
+                "storeMediaFile",
+                "addNote"
+            ]
+        );
+
+        let requests = received.lock().unwrap();
+        assert_eq!(requests[0]["version"], API_VERSION);
+        assert_eq!(requests[3]["params"]["data"], "AQID");
+        let note = &requests[4]["params"]["note"];
+        assert_eq!(note["deckName"], settings.deck);
+        assert_eq!(note["fields"]["Front"], "食べる");
+        let image = format!(r#"<img src="{}">"#, content("").screenshot_file().unwrap());
+        assert!(note["fields"]["Back"].as_str().unwrap().contains(&image));
+    }
+
+    #[test]
+    fn duplicates_are_not_added() {
+        let (url, received) = stand_in(anki);
+        let client = AnkiConnect::new(url);
+
+        let outcome = client.add_card(&AnkiSettings::default(), &content("分かる"));
+
+        assert_eq!(outcome.unwrap(), AddOutcome::Duplicate);
+        assert!(!actions(&received).contains(&"addNote".to_string()));
+    }
+
+    #[test]
+    fn studied_words_have_their_longest_interval() {
+        fn cards(action: &str, _: &Value) -> Value {
+            let card = |front: &str, interval: i64, card_type: u8| {
+                json!({
+                    "fields": { "Front": { "value": front, "order": 0 } },
+                    "interval": interval,
+                    "type": card_type,
+                })
+            };
+            match action {
+                "findCards" => ok(json!([1, 2, 3, 4])),
+                "cardsInfo" => ok(json!([
+                    card("<b> 食[た]べる</b>", 30, 2),
+                    card("食べる", 3, 2),
+                    card("見る", -600, 1),
+                    card("走る", 0, NEW_CARD),
+                ])),
+                _ => json!({ "result": null, "error": "unsupported action" }),
+            }
+        }
+        let (url, received) = stand_in(cards);
+        let client = AnkiConnect::new(url);
+
+        let words = client.studied_words("deck:UMOD", "Front").unwrap();
+
+        assert_eq!(
+            words,
+            [("見る".to_string(), 0.0), ("食べる".to_string(), 30.0)]
+        );
+        assert_eq!(received.lock().unwrap()[0]["params"]["query"], "deck:UMOD");
+
+        let error = client.studied_words("deck:UMOD", "Word").unwrap_err();
+        assert!(error.to_string().contains("has a field \"Word\""));
+    }
+
+    #[test]
+    fn anki_errors_are_reported() {
+        let (url, _) =
+            stand_in(|_, _| json!({ "result": null, "error": "model was not found: Basic" }));
+        let error = AnkiConnect::new(url)
+            .add_card(&AnkiSettings::default(), &content("食べる"))
+            .unwrap_err();
+        assert!(format!("{error:#}").contains("model was not found"));
+
+        let (url, _) = stand_in(anki);
+        let settings = AnkiSettings {
+            fields: [("Sentence".to_string(), "{sentence}".to_string())].into(),
+            ..AnkiSettings::default()
+        };
+        let error = AnkiConnect::new(url)
+            .add_card(&settings, &content("食べる"))
+            .unwrap_err();
+        assert!(error.to_string().contains("no field \"Sentence\""));
+    }
+}
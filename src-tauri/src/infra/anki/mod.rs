@@ -0,0 +1,287 @@
+// src-tauri/src/infra/anki/mod.rs
+//
+// Anki cards from dictionary lookups. The fields of a note are filled from
+// the templates in the Anki settings, e.g. "{headword}" or
+// "{sentence}<br>{screenshot}"; notes are sent to Anki through AnkiConnect
+// or exported as an `.apkg` deck package.
+
+use std::collections::BTreeMap;
+
+use super::dictionary::LookupResult;
+use super::settings::AnkiSettings;
+
+mod apkg;
+mod connect;
+
+pub use apkg::{card_fields, check_card_template, write_apkg};
+pub use connect::{AddOutcome, AnkiConnect};
+
+/// AnkiConnect's default address.
+pub const DEFAULT_URL: &str = "http://127.0.0.1:8765";
+
+/// Placeholders available in field templates, written as `{name}`.
+pub const PLACEHOLDERS: [&str; 6] = [
+    "headword",
+    "reading",
+    "glosses",
+    "sentence",
+    "screenshot",
+    "pitch",
+];
+
+/// What a card is made of: the best entry of a lookup and where the word was
+/// found.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoteContent {
+    pub headword: String,
+    pub reading: Option<String>,
+    /// Glosses of each sense of the first definition.
+    pub senses: Vec<Vec<String>>,
+    /// Pitch accent downsteps, without duplicates.
+    pub pitch_accents: Vec<u32>,
+    pub sentence: Option<String>,
+    /// PNG of the capture the word was looked up in.
+    pub screenshot: Option<Vec<u8>>,
+}
+
+impl NoteContent {
+    /// Content of the best entry of `lookup`; `None` when nothing was found.
+    pub fn from_lookup(lookup: &LookupResult, sentence: Option<&str>) -> Option<Self> {
+        let entry = lookup.term_entries.entries.first()?;
+        let headword = entry.headwords.first()?;
+
+        let senses = entry
+            .definitions
+            .first()
+            .map(|d| d.senses.iter().map(|s| s.glosses.clone()).collect())
+            .unwrap_or_default();
+        let mut pitch_accents: Vec<u32> = Vec::new();
+        for accent in &entry.pitch_accents {
+            if !pitch_accents.contains(&accent.position) {
+                pitch_accents.push(accent.position);
+            }
+        }
+
+        Some(Self {
+            headword: headword.term.clone(),
+            reading: headword.reading.clone(),
+            senses,
+            pitch_accents,
+            sentence: sentence.map(str::to_string),
+            screenshot: None,
+        })
+    }
+
+    /// Media file name for the screenshot, named after the SHA-1 of the PNG
+    /// so the same image is stored in Anki only once.
+    pub fn screenshot_file(&self) -> Option<String> {
+        let png = self.screenshot.as_ref()?;
+        Some(format!("umod-{}.png", sha1_smol::Sha1::from(png).digest()))
+    }
+}
+
+/// A note ready to be added.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub deck: String,
+    pub note_type: String,
+    /// Field name -> HTML content.
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Note {
+    /// Fill the field templates of `settings` with `content`.
+    pub fn build(settings: &AnkiSettings, content: &NoteContent) -> Self {
+        let screenshot = content.screenshot_file();
+        Self {
+            deck: settings.deck.clone(),
+            note_type: settings.note_type.clone(),
+            fields: settings
+                .fields
+                .iter()
+                .map(|(name, template)| {
+                    (
+                        name.clone(),
+                        render(template, content, screenshot.as_deref()),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Check that `template` only uses known placeholders.
+pub fn check_template(template: &str) -> Result<(), String> {
+    match placeholders(template).find(|name| !PLACEHOLDERS.contains(name)) {
+        Some(name) => Err(format!(
+            "unknown placeholder {{{name}}}, use one of {}",
+            PLACEHOLDERS.map(|p| format!("{{{p}}}")).join(" ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Whether any field template uses `{screenshot}`.
+pub fn uses_screenshot(settings: &AnkiSettings) -> bool {
+    settings
+        .fields
+        .values()
+        .any(|t| placeholders(t).any(|name| name == "screenshot"))
+}
+
+/// `template` with its placeholders replaced. Text is HTML-escaped;
+/// unknown placeholders are kept as they are.
+pub fn render(template: &str, content: &NoteContent, screenshot_file: Option<&str>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            rest = &rest[open..];
+            break;
+        };
+
+        let name = &after[..close];
+        match placeholder_value(name, content, screenshot_file) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[open..open + close + 2]),
+        }
+        rest = &after[close + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn placeholder_value(
+    name: &str,
+    content: &NoteContent,
+    screenshot_file: Option<&str>,
+) -> Option<String> {
+    let value = match name {
+        "headword" => escape_html(&content.headword),
+        "reading" => content
+            .reading
+            .as_deref()
+            .map(escape_html)
+            .unwrap_or_default(),
+        "glosses" => glosses_html(&content.senses),
+        "sentence" => content
+            .sentence
+            .as_deref()
+            .map(escape_html)
+            .unwrap_or_default(),
+        "screenshot" => screenshot_file
+            .map(|file| format!(r#"<img src="{}">"#, escape_html(file)))
+            .unwrap_or_default(),
+        "pitch" => content
+            .pitch_accents
+            .iter()
+            .map(|p| format!("[{p}]"))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// One sense as "a; b", several as a numbered list.
+fn glosses_html(senses: &[Vec<String>]) -> String {
+    let sense = |glosses: &Vec<String>| {
+        glosses
+            .iter()
+            .map(|g| escape_html(g))
+            .collect::<Vec<_>>()
+            .join("; ")
+    };
+
+    match senses {
+        [] => String::new(),
+        [only] => sense(only),
+        _ => {
+            let items: String = senses
+                .iter()
+                .map(|s| format!("<li>{}</li>", sense(s)))
+                .collect();
+            format!("<ol>{items}</ol>")
+        }
+    }
+}
+
+/// Names of the `{name}` placeholders in `template`.
+fn placeholders(template: &str) -> impl Iterator<Item = &str> {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|s| s.split_once('}'))
+        .map(|(name, _)| name)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content() -> NoteContent {
+        NoteContent {
+            headword: "食べる".into(),
+            reading: Some("たべる".into()),
+            senses: vec![vec!["to eat".into()], vec!["to live on <sth>".into()]],
+            pitch_accents: vec![2],
+            sentence: Some("ご飯を食べる".into()),
+            screenshot: None,
+        }
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let content = content();
+
+        assert_eq!(
+            render("{headword} ({reading}) {pitch}", &content, None),
+            "食べる (たべる) [2]"
+        );
+        assert_eq!(
+            render("{glosses}", &content, None),
+            "<ol><li>to eat</li><li>to live on &lt;sth&gt;</li></ol>"
+        );
+        assert_eq!(
+            render("{sentence}<br>{screenshot}", &content, Some("a.png")),
+            r#"ご飯を食べる<br><img src="a.png">"#
+        );
+        // no screenshot, unknown placeholders and stray braces
+        assert_eq!(render("{screenshot}{nope} {", &content, None), "{nope} {");
+    }
+
+    #[test]
+    fn checks_templates() {
+        assert_eq!(check_template("{headword}<br>{glosses} {"), Ok(()));
+        let err = check_template("{headword} {meaning}").unwrap_err();
+        assert!(err.contains("{meaning}"));
+
+        let mut settings = AnkiSettings::default();
+        assert!(uses_screenshot(&settings));
+        settings.fields.retain(|_, t| !t.contains("{screenshot}"));
+        assert!(!uses_screenshot(&settings));
+    }
+
+    #[test]
+    fn screenshot_file_is_named_after_the_image() {
+        let mut content = content();
+        assert_eq!(content.screenshot_file(), None);
+
+        content.screenshot = Some(b"png".to_vec());
+        assert_eq!(
+            content.screenshot_file().as_deref(),
+            Some("umod-9040a7d6cdf7a0d6cab1823831c6ceb7d01af97f.png")
+        );
+    }
+}
@@ -0,0 +1,164 @@
+// src-tauri/src/infra/change_detection/mod.rs
+//
+// Decides when a watched region shows new text. Frames are shrunk to a small
+// grayscale thumbnail (which also smooths out compression noise) and compared
+// pixel by pixel. A frame is reported once it differs from the last reported
+// frame *and* has stopped changing, so a text box that types itself out is
+// OCR'd once, after the last character appears, and small animations (a
+// blinking cursor, a bouncing "next" arrow) never trigger.
+
+use screenshots::image::imageops::{self, FilterType};
+use screenshots::image::{GrayImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+/// Frames are compared at most this wide.
+const THUMB_WIDTH: u32 = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WatchOptions {
+    /// Time between two captures of the watched region.
+    pub interval_ms: u64,
+    /// Luma difference (0-255) below which a thumbnail pixel counts as unchanged.
+    pub pixel_threshold: u8,
+    /// Fraction of thumbnail pixels that must change for the frame to count
+    /// as different.
+    pub min_changed: f32,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval_ms: 500,
+            pixel_threshold: 24,
+            min_changed: 0.01,
+        }
+    }
+}
+
+pub struct ChangeDetector {
+    options: WatchOptions,
+    /// Last frame that was reported.
+    baseline: Option<GrayImage>,
+    previous: Option<GrayImage>,
+}
+
+impl ChangeDetector {
+    pub fn new(options: WatchOptions) -> Self {
+        Self {
+            options,
+            baseline: None,
+            previous: None,
+        }
+    }
+
+    /// Feed the next frame. Returns true when it should be OCR'd.
+    pub fn update(&mut self, frame: &RgbaImage) -> bool {
+        let thumb = thumbnail(frame);
+
+        let stable = self
+            .previous
+            .as_ref()
+            .is_some_and(|prev| !self.differs(prev, &thumb));
+        let new_text = self
+            .baseline
+            .as_ref()
+            .is_none_or(|base| self.differs(base, &thumb));
+
+        self.previous = Some(thumb);
+        if stable && new_text {
+            self.baseline = self.previous.clone();
+            return true;
+        }
+        false
+    }
+
+    fn differs(&self, a: &GrayImage, b: &GrayImage) -> bool {
+        changed_fraction(a, b, self.options.pixel_threshold) >= self.options.min_changed
+    }
+}
+
+/// Fraction of pixels whose luma differs by more than `pixel_threshold`.
+/// Images of different sizes are completely different.
+pub fn changed_fraction(a: &GrayImage, b: &GrayImage, pixel_threshold: u8) -> f32 {
+    if a.dimensions() != b.dimensions() || a.is_empty() {
+        return 1.0;
+    }
+
+    let changed = a
+        .iter()
+        .zip(b.iter())
+        .filter(|(p, q)| p.abs_diff(**q) > pixel_threshold)
+        .count();
+    changed as f32 / a.len() as f32
+}
+
+fn thumbnail(frame: &RgbaImage) -> GrayImage {
+    let gray = imageops::grayscale(frame);
+    let (w, h) = gray.dimensions();
+    if w <= THUMB_WIDTH {
+        return gray;
+    }
+
+    let th = (h as u64 * THUMB_WIDTH as u64 / w as u64).max(1) as u32;
+    imageops::resize(&gray, THUMB_WIDTH, th, FilterType::Triangle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screenshots::image::Rgba;
+
+    /// Dark text box with `chars` white "glyphs" typed out so far.
+    fn text_box(chars: u32) -> RgbaImage {
+        let mut img = RgbaImage::from_pixel(400, 80, Rgba([20, 20, 40, 255]));
+        for c in 0..chars {
+            for x in (10 + c * 20)..(26 + c * 20) {
+                for y in 30..50 {
+                    img.put_pixel(x, y, Rgba([240, 240, 240, 255]));
+                }
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn reports_once_text_settles() {
+        let mut detector = ChangeDetector::new(WatchOptions::default());
+
+        // first frame has nothing to compare with yet
+        assert!(!detector.update(&text_box(5)));
+        assert!(detector.update(&text_box(5)));
+        assert!(!detector.update(&text_box(5)));
+    }
+
+    #[test]
+    fn waits_for_typewriter_text() {
+        let mut detector = ChangeDetector::new(WatchOptions::default());
+        detector.update(&text_box(3));
+        detector.update(&text_box(3));
+
+        for chars in 4..=10 {
+            assert!(!detector.update(&text_box(chars)), "fired mid-animation");
+        }
+        assert!(detector.update(&text_box(10)));
+    }
+
+    #[test]
+    fn ignores_small_animation() {
+        let mut detector = ChangeDetector::new(WatchOptions::default());
+        let still = text_box(8);
+        detector.update(&still);
+        detector.update(&still);
+
+        // a small blinking marker in the corner
+        let mut blink = still.clone();
+        for x in 390..394 {
+            for y in 70..74 {
+                blink.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+            }
+        }
+        for frame in [&blink, &still, &blink, &blink] {
+            assert!(!detector.update(frame));
+        }
+    }
+}
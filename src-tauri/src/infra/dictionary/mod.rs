@@ -1,5 +1,7 @@
 use reqwest::blocking::Client;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
 mod adapter_models;
 mod yomitan;
 
@@ -7,17 +9,23 @@ use crate::shared::models::dictionary::UmodTermEntries;
 use adapter_models::*;
 use yomitan::{condense_term_entries, yomitan_models::YomitanTermEntriesResponse};
 
-/// Configure this once in your app
-const BASE_URL: &str = "http://127.0.0.1:19633";
+/// Backend used until the user configures another one in the settings.
+pub const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:19633";
+
+/// How many terms a `LemmaCache` remembers.
+const LEMMA_CACHE_SIZE: usize = 4096;
 
 pub struct DictionaryAdapter {
     client: Client,
+    base_url: String,
 }
 
 impl DictionaryAdapter {
-    pub fn new() -> Self {
+    /// Adapter for the backend at `base_url` (e.g. `DEFAULT_ENDPOINT`).
+    pub fn new(base_url: impl Into<String>) -> Self {
         Self {
             client: Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
         }
     }
 
@@ -32,7 +40,7 @@ impl DictionaryAdapter {
 
         let raw: Vec<TokenizeItem> = self
             .client
-            .post(format!("{BASE_URL}/tokenize"))
+            .post(format!("{}/tokenize", self.base_url))
             .json(&payload)
             .send()?
             .json()?;
@@ -52,6 +60,46 @@ impl DictionaryAdapter {
         Ok(TokenizeResponse { tokens })
     }
 
+    // -------------------------------------------------
+    // SEGMENT
+    // -------------------------------------------------
+    /// All of `text` cut into the tokenizer's words, each with the headword
+    /// of its dictionary entry as its lemma (食べる for 食べた). Text between
+    /// words, like punctuation, has no lemma. Lemmas are looked up once per
+    /// term and kept in `cache`; a word whose lookup fails has no lemma.
+    pub fn segment(&self, text: &str, cache: &LemmaCache) -> reqwest::Result<Vec<Segment>> {
+        let words = self.tokenize(text)?.tokens;
+
+        let mut lemmas: HashMap<&str, Option<String>> = HashMap::new();
+        for word in &words {
+            let term = word.term.as_str();
+            if lemmas.contains_key(term) || !term.chars().any(is_japanese) {
+                continue;
+            }
+            let lemma = match cache.get(term) {
+                Some(lemma) => lemma,
+                None => match self.term_entries_umod(term) {
+                    Ok(entries) => {
+                        let lemma = lemma(term, &entries);
+                        cache.insert(term, lemma.clone());
+                        lemma
+                    }
+                    Err(e) => {
+                        eprintln!("No lemma for {term}: {e}");
+                        None
+                    }
+                },
+            };
+            lemmas.insert(term, lemma);
+        }
+
+        let words = words.iter().map(|word| {
+            let term = word.term.as_str();
+            (term, lemmas.get(term).cloned().flatten())
+        });
+        Ok(align_segments(text, words))
+    }
+
     // -------------------------------------------------
     // TERM ENTRIES (RAW)
     // -------------------------------------------------
@@ -60,7 +108,7 @@ impl DictionaryAdapter {
 
         let resp: YomitanTermEntriesResponse = self
             .client
-            .post(format!("{BASE_URL}/termEntries"))
+            .post(format!("{}/termEntries", self.base_url))
             .json(&payload)
             .send()?
             .json()?;
@@ -86,7 +134,7 @@ impl DictionaryAdapter {
 
         let entries: Vec<KanjiEntry> = self
             .client
-            .post(format!("{BASE_URL}/kanjiEntries"))
+            .post(format!("{}/kanjiEntries", self.base_url))
             .json(&payload)
             .send()?
             .json()?;
@@ -117,6 +165,107 @@ impl DictionaryAdapter {
     }
 }
 
+/// Lemmas (or `None` for no dictionary entry) of terms segmented before.
+/// Bounded: beyond `LEMMA_CACHE_SIZE` terms the oldest are forgotten.
+pub struct LemmaCache {
+    capacity: usize,
+    lemmas: Mutex<Lemmas>,
+}
+
+#[derive(Default)]
+struct Lemmas {
+    by_term: HashMap<String, Option<String>>,
+    /// Terms in the order they were added.
+    order: VecDeque<String>,
+}
+
+impl Default for LemmaCache {
+    fn default() -> Self {
+        Self::with_capacity(LEMMA_CACHE_SIZE)
+    }
+}
+
+impl LemmaCache {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            lemmas: Mutex::default(),
+        }
+    }
+
+    /// `None` when `term` was not looked up (or was forgotten).
+    fn get(&self, term: &str) -> Option<Option<String>> {
+        self.lemmas.lock().unwrap().by_term.get(term).cloned()
+    }
+
+    fn insert(&self, term: &str, lemma: Option<String>) {
+        let mut lemmas = self.lemmas.lock().unwrap();
+        if lemmas.by_term.insert(term.to_string(), lemma).is_none() {
+            lemmas.order.push_back(term.to_string());
+        }
+        while lemmas.order.len() > self.capacity {
+            if let Some(oldest) = lemmas.order.pop_front() {
+                lemmas.by_term.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Headword of the best entry for `term`, if the entry matches all of it
+/// rather than a prefix.
+fn lemma(term: &str, entries: &UmodTermEntries) -> Option<String> {
+    // Yomitan counts UTF-16 code units
+    if entries.original_text_length != term.encode_utf16().count() {
+        return None;
+    }
+    let headword = entries.entries.first()?.headwords.first()?;
+    Some(headword.term.clone())
+}
+
+/// Kana (full and half width), kanji and 々.
+fn is_japanese(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3005}'
+            | '\u{3040}'..='\u{30ff}'
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{ff66}'..='\u{ff9f}'
+    )
+}
+
+/// `text` as a list of segments: the `words` found in order, with the text
+/// the tokenizer skipped (or changed) in between as segments without lemma.
+fn align_segments<'a>(
+    text: &str,
+    words: impl IntoIterator<Item = (&'a str, Option<String>)>,
+) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+
+    for (word, lemma) in words {
+        if word.is_empty() {
+            continue;
+        }
+        let Some(start) = rest.find(word) else {
+            continue;
+        };
+        if start > 0 {
+            segments.push(Segment::plain(&rest[..start]));
+        }
+        segments.push(Segment {
+            text: word.to_string(),
+            lemma,
+        });
+        rest = &rest[start + word.len()..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::plain(rest));
+    }
+
+    segments
+}
+
 /* ---------------- Errors / Results ---------------- */
 
 #[derive(Debug)]
@@ -126,13 +275,41 @@ pub enum LookupError {
     NoCharacters,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LookupResult {
     pub token: TokenInfo,
     pub term_entries: UmodTermEntries,
     pub kanji_entries: KanjiEntriesResponse,
 }
 
+/// A piece of segmented text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Segment {
+    pub text: String,
+    /// Dictionary form of the word; `None` for text that is no word.
+    pub lemma: Option<String>,
+}
+
+impl Segment {
+    fn plain(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            lemma: None,
+        }
+    }
+}
+
+impl LookupResult {
+    /// Headword of the best matching entry.
+    pub fn headword(&self) -> Option<&str> {
+        self.term_entries
+            .entries
+            .first()
+            .and_then(|e| e.headwords.first())
+            .map(|h| h.term.as_str())
+    }
+}
+
 //Tests
 #[cfg(test)]
 mod tests {
@@ -140,7 +317,7 @@ mod tests {
 
     /// Helper to create adapter once
     fn adapter() -> DictionaryAdapter {
-        DictionaryAdapter::new()
+        DictionaryAdapter::new(DEFAULT_ENDPOINT)
     }
 
     #[test]
@@ -196,6 +373,76 @@ mod tests {
         assert!(!first_sense.glosses.is_empty(), "sense has no glosses");
     }
 
+    fn entries(query: &str, length: usize, headword: &str) -> UmodTermEntries {
+        serde_json::from_value(serde_json::json!({
+            "query": query,
+            "original_text_length": length,
+            "entries": [{
+                "headwords": [{ "term": headword, "reading": null, "word_classes": [] }],
+                "definitions": [],
+                "frequencies": [],
+            }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn lemma_needs_an_entry_for_the_whole_term() {
+        assert_eq!(
+            lemma("食べた", &entries("食べた", 3, "食べる")).as_deref(),
+            Some("食べる")
+        );
+        // the best entry only covers 食べ
+        assert_eq!(lemma("食べた", &entries("食べた", 2, "食べる")), None);
+        // lengths are UTF-16 code units: 𠮷 is two
+        assert_eq!(
+            lemma("𠮷野", &entries("𠮷野", 3, "𠮷野")).as_deref(),
+            Some("𠮷野")
+        );
+    }
+
+    #[test]
+    fn lemma_cache_forgets_the_oldest_terms() {
+        let cache = LemmaCache::with_capacity(2);
+        cache.insert("食べた", Some("食べる".into()));
+        cache.insert("ぴょ", None);
+        assert_eq!(cache.get("食べた"), Some(Some("食べる".into())));
+        assert_eq!(cache.get("ぴょ"), Some(None));
+
+        cache.insert("分かる", Some("分かる".into()));
+        assert_eq!(cache.get("食べた"), None);
+        assert_eq!(cache.get("分かる"), Some(Some("分かる".into())));
+    }
+
+    #[test]
+    fn segments_cover_the_whole_text() {
+        let lemma = |s: &str| Some(s.to_string());
+        let words = [
+            ("食べた", lemma("食べる")),
+            ("！", None),
+            ("missing", None),
+            ("分かる", lemma("分かる")),
+        ];
+
+        let segments = align_segments("「食べた！」\n分かる。", words);
+
+        let pieces: Vec<_> = segments
+            .iter()
+            .map(|s| (s.text.as_str(), s.lemma.as_deref()))
+            .collect();
+        assert_eq!(
+            pieces,
+            [
+                ("「", None),
+                ("食べた", Some("食べる")),
+                ("！", None),
+                ("」\n", None),
+                ("分かる", Some("分かる")),
+                ("。", None),
+            ]
+        );
+    }
+
     #[test]
     fn kanji_entries_basic() {
         let adapter = adapter();
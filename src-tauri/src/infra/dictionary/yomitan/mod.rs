@@ -103,10 +103,30 @@ pub fn condense_term_entries(query: &str, raw: &YomitanTermEntriesResponse) -> U
                 })
                 .collect();
 
+            /* ---------------------------------------------
+             Pitch accents
+            --------------------------------------------- */
+            let pitch_accents = dict_entry
+                .pronunciations
+                .iter()
+                .flat_map(|group| {
+                    group
+                        .pronunciations
+                        .iter()
+                        .filter(|p| p.kind == "pitch-accent")
+                        .filter_map(|p| p.positions.as_u64())
+                        .map(|position| UmodPitchAccent {
+                            dictionary: group.dictionary.clone(),
+                            position: position as u32,
+                        })
+                })
+                .collect();
+
             Some(UmodDictionaryEntry {
                 headwords,
                 definitions,
                 frequencies,
+                pitch_accents,
             })
         })
         .collect();
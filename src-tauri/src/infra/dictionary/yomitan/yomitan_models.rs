@@ -17,6 +17,9 @@ pub struct YomitanDictionaryEntry {
 
     #[serde(default)]
     pub frequencies: Vec<YomitanFrequency>,
+
+    #[serde(default)]
+    pub pronunciations: Vec<YomitanPronunciations>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -66,3 +69,22 @@ pub struct YomitanFrequency {
 
     pub frequency: Option<u64>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct YomitanPronunciations {
+    pub dictionary: String,
+
+    #[serde(default)]
+    pub pronunciations: Vec<YomitanPronunciation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct YomitanPronunciation {
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Downstep mora for pitch accents; some dictionaries give a pattern
+    /// string instead.
+    #[serde(default)]
+    pub positions: Value,
+}
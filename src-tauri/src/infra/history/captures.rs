@@ -0,0 +1,394 @@
+// src-tauri/src/infra/history/captures.rs
+//
+// Captures and their OCR text. The image is kept (as PNG, scaled down if it
+// is large) so OCR can be run again with other settings, next to a small
+// thumbnail for browsing. The
+// text can be corrected by the user; the OCR result it came from is kept as
+// JSON so the overlay can be reopened.
+
+use anyhow::Result;
+use rusqlite::{OptionalExtension, Row, params};
+use std::borrow::Cow;
+use screenshots::image::imageops::{self, FilterType};
+use screenshots::image::{self, RgbaImage};
+use serde::Serialize;
+
+use super::{DEFAULT_SEARCH_LIMIT, HistoryFilter, HistoryStore};
+use crate::infra::preprocess::encode_png;
+use crate::shared::models::ocr::{CaptureSource, OcrResult, ScreenRect};
+
+pub(super) const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS captures (
+        id         INTEGER PRIMARY KEY,
+        created_at INTEGER NOT NULL,
+        source     TEXT NOT NULL,
+        engine     TEXT NOT NULL,
+        x          INTEGER NOT NULL,
+        y          INTEGER NOT NULL,
+        w          INTEGER NOT NULL,
+        h          INTEGER NOT NULL,
+        text       TEXT NOT NULL,
+        edited     INTEGER NOT NULL DEFAULT 0,
+        result     TEXT NOT NULL,
+        thumbnail  BLOB NOT NULL,
+        image      BLOB NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS captures_created_at ON captures (created_at);
+";
+
+/// Thumbnails fit in a square of this size (px).
+const THUMBNAIL_SIZE: u32 = 240;
+
+/// Stored images are scaled down to fit in a square of this size (px); the
+/// recorded region keeps the captured size.
+const MAX_IMAGE_SIZE: u32 = 1920;
+
+/// A capture to record.
+pub struct NewCapture<'a> {
+    /// Unix seconds.
+    pub created_at: i64,
+    pub source: &'a CaptureSource,
+    /// Screen position of the capture's top-left corner.
+    pub origin: (i32, i32),
+    pub image: &'a RgbaImage,
+    pub result: &'a OcrResult,
+}
+
+/// A recorded capture, without its images.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaptureEntry {
+    pub id: i64,
+    pub created_at: i64,
+    pub source: CaptureSource,
+    /// `source` for display.
+    pub source_label: String,
+    pub engine: String,
+    /// Physical desktop pixels.
+    pub region: ScreenRect,
+    pub text: String,
+    /// The text was changed by the user since the last OCR run.
+    pub edited: bool,
+}
+
+impl CaptureEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let source: CaptureSource = serde_json::from_str(&row.get::<_, String>("source")?)
+            .map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, e.into())
+            })?;
+
+        Ok(Self {
+            id: row.get("id")?,
+            created_at: row.get("created_at")?,
+            source_label: source.to_string(),
+            source,
+            engine: row.get("engine")?,
+            region: ScreenRect {
+                x: row.get("x")?,
+                y: row.get("y")?,
+                w: row.get("w")?,
+                h: row.get("h")?,
+            },
+            text: row.get("text")?,
+            edited: row.get("edited")?,
+        })
+    }
+}
+
+const ENTRY_COLUMNS: &str = "id, created_at, source, engine, x, y, w, h, text, edited";
+
+impl HistoryStore {
+    pub fn add_capture(&self, capture: &NewCapture) -> Result<i64> {
+        let image = encode_png(&fit(capture.image, MAX_IMAGE_SIZE))?;
+        let thumbnail = encode_png(&fit(capture.image, THUMBNAIL_SIZE))?;
+        let source = serde_json::to_string(capture.source)?;
+        let result = serde_json::to_string(capture.result)?;
+        let (x, y) = capture.origin;
+
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO captures
+                (created_at, source, engine, x, y, w, h, text, result, thumbnail, image)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                capture.created_at,
+                source,
+                capture.result.engine,
+                x,
+                y,
+                capture.image.width(),
+                capture.image.height(),
+                capture.result.text,
+                result,
+                thumbnail,
+                image,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Matching captures, newest first. The text filter looks at the
+    /// (possibly edited) text.
+    pub fn search_captures(&self, filter: &HistoryFilter) -> Result<Vec<CaptureEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ENTRY_COLUMNS} FROM captures
+             WHERE (?1 IS NULL OR text LIKE ?1 ESCAPE '\\')
+               AND (?2 IS NULL OR created_at >= ?2)
+               AND (?3 IS NULL OR created_at < ?3)
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?4"
+        ))?;
+        let entries = stmt
+            .query_map(
+                params![
+                    filter.text_pattern(),
+                    filter.from,
+                    filter.to,
+                    filter.limit.unwrap_or(DEFAULT_SEARCH_LIMIT)
+                ],
+                CaptureEntry::from_row,
+            )?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(entries)
+    }
+
+    pub fn capture(&self, id: i64) -> Result<Option<CaptureEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let entry = conn
+            .query_row(
+                &format!("SELECT {ENTRY_COLUMNS} FROM captures WHERE id = ?1"),
+                [id],
+                CaptureEntry::from_row,
+            )
+            .optional()?;
+        Ok(entry)
+    }
+
+    /// The OCR result of capture `id`, carrying the user's text if it was
+    /// edited.
+    pub fn capture_result(&self, id: i64) -> Result<Option<OcrResult>> {
+        let conn = self.conn.lock().unwrap();
+        let row: Option<(String, String)> = conn
+            .query_row(
+                "SELECT result, text FROM captures WHERE id = ?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        let Some((json, text)) = row else {
+            return Ok(None);
+        };
+        let mut result: OcrResult = serde_json::from_str(&json)?;
+        result.text = text;
+        Ok(Some(result))
+    }
+
+    /// PNG thumbnail of capture `id`.
+    pub fn capture_thumbnail(&self, id: i64) -> Result<Option<Vec<u8>>> {
+        self.capture_blob(id, "thumbnail")
+    }
+
+    /// The full image of capture `id` as PNG.
+    pub fn capture_png(&self, id: i64) -> Result<Option<Vec<u8>>> {
+        self.capture_blob(id, "image")
+    }
+
+    pub fn capture_image(&self, id: i64) -> Result<Option<RgbaImage>> {
+        let Some(png) = self.capture_png(id)? else {
+            return Ok(None);
+        };
+        Ok(Some(image::load_from_memory(&png)?.to_rgba8()))
+    }
+
+    fn capture_blob(&self, id: i64, column: &str) -> Result<Option<Vec<u8>>> {
+        let conn = self.conn.lock().unwrap();
+        let blob = conn
+            .query_row(
+                &format!("SELECT {column} FROM captures WHERE id = ?1"),
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(blob)
+    }
+
+    /// Replace the text of capture `id` with the user's correction. Returns
+    /// whether the capture exists.
+    pub fn set_capture_text(&self, id: i64, text: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let changed = conn.execute(
+            "UPDATE captures SET text = ?2, edited = 1 WHERE id = ?1",
+            params![id, text],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Store the result of running OCR on capture `id` again; this replaces
+    /// any edited text.
+    pub fn set_capture_result(&self, id: i64, result: &OcrResult) -> Result<bool> {
+        let json = serde_json::to_string(result)?;
+        let conn = self.conn.lock().unwrap();
+        let changed = conn.execute(
+            "UPDATE captures SET engine = ?2, text = ?3, result = ?4, edited = 0 WHERE id = ?1",
+            params![id, result.engine, result.text, json],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Returns whether capture `id` existed.
+    pub fn delete_capture(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM captures WHERE id = ?1", [id])? > 0)
+    }
+}
+
+/// `image` scaled down to fit in a square of `size`, as is if it does.
+fn fit(image: &RgbaImage, size: u32) -> Cow<'_, RgbaImage> {
+    let (w, h) = image.dimensions();
+    let scale = size as f64 / w.max(h).max(1) as f64;
+    if scale >= 1.0 {
+        return Cow::Borrowed(image);
+    }
+    let tw = ((w as f64 * scale).round() as u32).max(1);
+    let th = ((h as f64 * scale).round() as u32).max(1);
+    Cow::Owned(imageops::resize(image, tw, th, FilterType::Triangle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::settings::HistorySettings;
+    use crate::shared::models::ocr::Orientation;
+    use screenshots::image::Rgba;
+
+    fn result(text: &str) -> OcrResult {
+        OcrResult {
+            text: text.into(),
+            blocks: Vec::new(),
+            orientation: Orientation::Horizontal,
+            engine: "manga-ocr".into(),
+            confidence: Some(0.9),
+            elapsed_ms: 12,
+        }
+    }
+
+    fn add(store: &HistoryStore, created_at: i64, text: &str) -> i64 {
+        add_sized(store, created_at, text, (600, 100))
+    }
+
+    fn add_sized(store: &HistoryStore, created_at: i64, text: &str, (w, h): (u32, u32)) -> i64 {
+        let image = RgbaImage::from_fn(w, h, |x, y| Rgba([(x * 7) as u8, (y * 13) as u8, 0, 255]));
+        store
+            .add_capture(&NewCapture {
+                created_at,
+                source: &CaptureSource::Profile {
+                    name: "Dialogue".into(),
+                },
+                origin: (10, 20),
+                image: &image,
+                result: &result(text),
+            })
+            .expect("insert failed")
+    }
+
+    #[test]
+    fn capture_round_trip() {
+        let store = HistoryStore::in_memory();
+        let id = add(&store, 100, "こんにちは");
+
+        let entry = store.capture(id).unwrap().expect("capture missing");
+        assert_eq!(entry.source_label, "Profile \"Dialogue\"");
+        assert_eq!(
+            entry.region,
+            ScreenRect {
+                x: 10,
+                y: 20,
+                w: 600,
+                h: 100
+            }
+        );
+        assert!(!entry.edited);
+
+        let image = store.capture_image(id).unwrap().expect("image missing");
+        assert_eq!(image.dimensions(), (600, 100));
+        let thumbnail = store.capture_thumbnail(id).unwrap().expect("no thumbnail");
+        let thumbnail = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (240, 40));
+
+        assert!(store.delete_capture(id).unwrap());
+        assert_eq!(store.capture(id).unwrap(), None);
+    }
+
+    #[test]
+    fn large_images_are_stored_scaled_down() {
+        let store = HistoryStore::in_memory();
+        let id = add_sized(&store, 100, "大きい", (3000, 500));
+
+        let entry = store.capture(id).unwrap().unwrap();
+        assert_eq!((entry.region.w, entry.region.h), (3000, 500));
+        let image = store.capture_image(id).unwrap().unwrap();
+        assert_eq!(image.dimensions(), (1920, 320));
+    }
+
+    #[test]
+    fn pruning_gives_space_back() {
+        let path = std::env::temp_dir().join(format!("umod-history-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = HistoryStore::open(&path);
+        for i in 0..5 {
+            add_sized(&store, 100 + i, "ページ", (800, 800));
+        }
+        let full = std::fs::metadata(&path).unwrap().len();
+
+        let limits = HistorySettings {
+            max_entries: 1,
+            ..HistorySettings::default()
+        };
+        assert_eq!(store.prune(&limits, 200).unwrap(), 4);
+
+        let free: i64 = {
+            let conn = store.conn.lock().unwrap();
+            conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(free, 0);
+        assert!(std::fs::metadata(&path).unwrap().len() < full / 2);
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn edited_text_is_searched_and_replaced_by_a_rerun() {
+        let store = HistoryStore::in_memory();
+        let id = add(&store, 100, "今日は晴れ");
+        add(&store, 200, "明日は雨");
+
+        assert!(store.set_capture_text(id, "今日は晴れだ").unwrap());
+        let filter = HistoryFilter {
+            text: Some("晴れだ".into()),
+            ..HistoryFilter::default()
+        };
+        let found = store.search_captures(&filter).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].edited);
+        assert_eq!(
+            store.capture_result(id).unwrap().unwrap().text,
+            "今日は晴れだ"
+        );
+
+        assert!(
+            store
+                .set_capture_result(id, &result("今日は晴れ。"))
+                .unwrap()
+        );
+        let entry = store.capture(id).unwrap().unwrap();
+        assert_eq!(entry.text, "今日は晴れ。");
+        assert!(!entry.edited);
+
+        let all = store.search_captures(&HistoryFilter::default()).unwrap();
+        let texts: Vec<&str> = all.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, ["明日は雨", "今日は晴れ。"]);
+    }
+}
@@ -0,0 +1,322 @@
+// src-tauri/src/infra/history/lookups.rs
+//
+// Dictionary lookups: what was looked up, the headword it matched, the OCR
+// sentence it was selected from and where that text was captured, with the
+// capture itself when it is in the history. The full lookup result is kept as
+// JSON so the lookup window can be reopened as it was.
+
+use anyhow::Result;
+use rusqlite::{OptionalExtension, Row, params};
+use serde::Serialize;
+
+use super::{DEFAULT_SEARCH_LIMIT, HistoryFilter, HistoryStore};
+
+pub(super) const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS lookups (
+        id         INTEGER PRIMARY KEY,
+        created_at INTEGER NOT NULL,
+        query      TEXT NOT NULL,
+        headword   TEXT,
+        sentence   TEXT,
+        source     TEXT NOT NULL,
+        result     TEXT NOT NULL,
+        capture_id INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS lookups_created_at ON lookups (created_at);
+";
+
+/// A lookup to record.
+pub struct NewLookup<'a> {
+    /// Unix seconds.
+    pub created_at: i64,
+    pub query: &'a str,
+    pub headword: Option<&'a str>,
+    pub sentence: Option<&'a str>,
+    /// Where the looked up text came from, e.g. "Region" or "Clipboard text".
+    pub source: &'a str,
+    /// The `LookupResult` as JSON.
+    pub result_json: &'a str,
+    /// History capture the text was selected from.
+    pub capture_id: Option<i64>,
+}
+
+/// A recorded lookup, without its result.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LookupEntry {
+    pub id: i64,
+    pub created_at: i64,
+    pub query: String,
+    pub headword: Option<String>,
+    pub sentence: Option<String>,
+    pub source: String,
+    /// May name a capture that was pruned or deleted since.
+    pub capture_id: Option<i64>,
+}
+
+impl LookupEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            created_at: row.get("created_at")?,
+            query: row.get("query")?,
+            headword: row.get("headword")?,
+            sentence: row.get("sentence")?,
+            source: row.get("source")?,
+            capture_id: row.get("capture_id")?,
+        })
+    }
+}
+
+impl HistoryStore {
+    pub fn add_lookup(&self, lookup: &NewLookup) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO lookups
+                (created_at, query, headword, sentence, source, result, capture_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                lookup.created_at,
+                lookup.query,
+                lookup.headword,
+                lookup.sentence,
+                lookup.source,
+                lookup.result_json,
+                lookup.capture_id,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Matching lookups, newest first. The text filter looks at query,
+    /// headword and sentence.
+    pub fn search_lookups(&self, filter: &HistoryFilter) -> Result<Vec<LookupEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, created_at, query, headword, sentence, source, capture_id
+             FROM lookups
+             WHERE (?1 IS NULL
+                    OR query LIKE ?1 ESCAPE '\\'
+                    OR headword LIKE ?1 ESCAPE '\\'
+                    OR sentence LIKE ?1 ESCAPE '\\')
+               AND (?2 IS NULL OR created_at >= ?2)
+               AND (?3 IS NULL OR created_at < ?3)
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?4",
+        )?;
+        let entries = stmt
+            .query_map(
+                params![
+                    filter.text_pattern(),
+                    filter.from,
+                    filter.to,
+                    filter.limit.unwrap_or(DEFAULT_SEARCH_LIMIT)
+                ],
+                LookupEntry::from_row,
+            )?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(entries)
+    }
+
+    pub fn lookup(&self, id: i64) -> Result<Option<LookupEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let entry = conn
+            .query_row(
+                "SELECT id, created_at, query, headword, sentence, source, capture_id
+                 FROM lookups WHERE id = ?1",
+                [id],
+                LookupEntry::from_row,
+            )
+            .optional()?;
+        Ok(entry)
+    }
+
+    /// The stored `LookupResult` JSON of lookup `id`.
+    pub fn lookup_result(&self, id: i64) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let json = conn
+            .query_row("SELECT result FROM lookups WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok(json)
+    }
+
+    /// Returns whether lookup `id` existed.
+    pub fn delete_lookup(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM lookups WHERE id = ?1", [id])? > 0)
+    }
+}
+
+/// The sentence of `text` that contains `selection`, e.g. the OCR line a word
+/// was looked up from. Sentences end at Japanese or Latin sentence
+/// punctuation and at line breaks.
+pub fn sentence_around(text: &str, selection: &str) -> Option<String> {
+    let selection = selection.trim();
+    if selection.is_empty() {
+        return None;
+    }
+    let start = text.find(selection)?;
+    let end = start + selection.len();
+
+    let is_end = |c: char| matches!(c, '。' | '！' | '？' | '!' | '?' | '.' | '\n');
+    let sentence_start = text[..start]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| is_end(*c))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let sentence_end = match text[end..].char_indices().find(|(_, c)| is_end(*c)) {
+        // keep the punctuation, not the line break
+        Some((i, '\n')) => end + i,
+        Some((i, c)) => end + i + c.len_utf8(),
+        None => text.len(),
+    };
+
+    let sentence = text[sentence_start..sentence_end].trim();
+    (!sentence.is_empty()).then(|| sentence.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::create_tables;
+    use super::*;
+    use crate::infra::settings::HistorySettings;
+    use rusqlite::Connection;
+    use std::sync::Mutex;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn add(store: &HistoryStore, created_at: i64, query: &str, sentence: Option<&str>) -> i64 {
+        store
+            .add_lookup(&NewLookup {
+                created_at,
+                query,
+                headword: Some(query),
+                sentence,
+                source: "Region",
+                result_json: &format!(r#"{{"query":"{query}"}}"#),
+                capture_id: None,
+            })
+            .expect("insert failed")
+    }
+
+    fn queries(entries: &[LookupEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.query.as_str()).collect()
+    }
+
+    #[test]
+    fn search_by_text_and_date() {
+        let store = HistoryStore::in_memory();
+        add(&store, 10 * DAY, "分かる", Some("よく分かった。"));
+        add(&store, 11 * DAY, "食べる", Some("ご飯を食べる"));
+        add(&store, 12 * DAY, "100%", None);
+
+        let all = store.search_lookups(&HistoryFilter::default()).unwrap();
+        assert_eq!(queries(&all), ["100%", "食べる", "分かる"]);
+
+        let by_sentence = HistoryFilter {
+            text: Some("ご飯".into()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(
+            queries(&store.search_lookups(&by_sentence).unwrap()),
+            ["食べる"]
+        );
+
+        // LIKE wildcards are matched literally
+        let percent = HistoryFilter {
+            text: Some("%".into()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(queries(&store.search_lookups(&percent).unwrap()), ["100%"]);
+
+        let day_11 = HistoryFilter {
+            from: Some(11 * DAY),
+            to: Some(12 * DAY),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(queries(&store.search_lookups(&day_11).unwrap()), ["食べる"]);
+    }
+
+    #[test]
+    fn result_is_kept_until_deleted() {
+        let store = HistoryStore::in_memory();
+        let id = add(&store, DAY, "分かる", None);
+
+        assert_eq!(
+            store.lookup_result(id).unwrap().as_deref(),
+            Some(r#"{"query":"分かる"}"#)
+        );
+        assert_eq!(store.lookup(id).unwrap().unwrap().query, "分かる");
+        assert!(store.delete_lookup(id).unwrap());
+        assert!(!store.delete_lookup(id).unwrap());
+        assert_eq!(store.lookup_result(id).unwrap(), None);
+    }
+
+    #[test]
+    fn old_databases_get_the_capture_column() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE lookups (
+                id INTEGER PRIMARY KEY, created_at INTEGER NOT NULL,
+                query TEXT NOT NULL, headword TEXT, sentence TEXT,
+                source TEXT NOT NULL, result TEXT NOT NULL
+            );",
+        )
+        .unwrap();
+        create_tables(&conn).unwrap();
+        let store = HistoryStore {
+            conn: Mutex::new(conn),
+        };
+
+        let id = store
+            .add_lookup(&NewLookup {
+                created_at: DAY,
+                query: "分かる",
+                headword: None,
+                sentence: None,
+                source: "Region",
+                result_json: "{}",
+                capture_id: Some(7),
+            })
+            .unwrap();
+        assert_eq!(store.lookup(id).unwrap().unwrap().capture_id, Some(7));
+    }
+
+    #[test]
+    fn prune_by_age_and_count() {
+        let store = HistoryStore::in_memory();
+        for day in 1..=5 {
+            add(&store, day * DAY, &format!("q{day}"), None);
+        }
+
+        let limits = HistorySettings {
+            retention_days: 3,
+            max_entries: 2,
+            ..HistorySettings::default()
+        };
+        // q1 is older than 3 days, q2 is beyond the 2 newest
+        assert_eq!(store.prune(&limits, 5 * DAY).unwrap(), 3);
+        let left = store.search_lookups(&HistoryFilter::default()).unwrap();
+        assert_eq!(queries(&left), ["q5", "q4"]);
+    }
+
+    #[test]
+    fn finds_the_sentence_of_a_selection() {
+        let text = "昨日は雨だった。今日は晴れ！\n明日はどうかな";
+
+        assert_eq!(
+            sentence_around(text, "今日").as_deref(),
+            Some("今日は晴れ！")
+        );
+        assert_eq!(
+            sentence_around(text, "昨日").as_deref(),
+            Some("昨日は雨だった。")
+        );
+        assert_eq!(
+            sentence_around(text, "明日").as_deref(),
+            Some("明日はどうかな")
+        );
+        assert_eq!(sentence_around(text, "雪"), None);
+    }
+}
@@ -0,0 +1,189 @@
+// src-tauri/src/infra/history/mod.rs
+//
+// Persistent history of what the user did, in a SQLite database in the
+// platform data dir. Each kind of entry has its own table and submodule; old
+// entries are pruned according to the history settings, and the space they
+// took is given back to the file system.
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::settings::HistorySettings;
+
+mod captures;
+mod lookups;
+
+pub use captures::{CaptureEntry, NewCapture};
+pub use lookups::{LookupEntry, NewLookup, sentence_around};
+
+/// Tables pruned by `HistoryStore::prune`.
+const TABLES: [&str; 2] = ["lookups", "captures"];
+
+/// Search results are capped at this many entries unless asked otherwise.
+const DEFAULT_SEARCH_LIMIT: u32 = 200;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// `PRAGMA auto_vacuum` value of incremental auto-vacuum.
+const INCREMENTAL_VACUUM: i64 = 2;
+
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+/// History search. Every field is optional; `from` is inclusive and `to`
+/// exclusive (Unix seconds).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    /// Text to find anywhere in the entry's searchable columns.
+    pub text: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+impl HistoryFilter {
+    /// `LIKE` pattern for `text`, `None` when there is no text to find.
+    fn text_pattern(&self) -> Option<String> {
+        self.text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(like_pattern)
+    }
+}
+
+impl HistoryStore {
+    /// `<platform data dir>/umod/history.db`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("umod").join("history.db"))
+    }
+
+    /// Open (or create) the database at `path`. If that fails the history is
+    /// kept in memory for this session.
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        match open_file(path) {
+            Ok(conn) => Self {
+                conn: Mutex::new(conn),
+            },
+            Err(e) => {
+                eprintln!(
+                    "History at {} not available, keeping it in memory: {e:#}",
+                    path.display()
+                );
+                Self::in_memory()
+            }
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        let conn = Connection::open_in_memory().expect("in-memory database");
+        create_tables(&conn).expect("history tables");
+        Self {
+            conn: Mutex::new(conn),
+        }
+    }
+
+    /// Delete entries older than the retention period and the oldest entries
+    /// beyond the maximum count, per table, and shrink the file by the space
+    /// they took. Returns how many were deleted.
+    pub fn prune(&self, limits: &HistorySettings, now: i64) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let mut deleted = 0;
+
+        for table in TABLES {
+            if limits.retention_days > 0 {
+                let cutoff = now - limits.retention_days as i64 * SECONDS_PER_DAY;
+                deleted += conn.execute(
+                    &format!("DELETE FROM {table} WHERE created_at < ?1"),
+                    [cutoff],
+                )?;
+            }
+            deleted += conn.execute(
+                &format!(
+                    "DELETE FROM {table} WHERE id NOT IN
+                        (SELECT id FROM {table} ORDER BY created_at DESC, id DESC LIMIT ?1)"
+                ),
+                [limits.max_entries],
+            )?;
+        }
+
+        if deleted > 0 {
+            // Each step of the pragma frees one page, so run it to the end.
+            let mut vacuum = conn.prepare("PRAGMA incremental_vacuum")?;
+            let mut rows = vacuum.query([])?;
+            while rows.next()?.is_some() {}
+        }
+        Ok(deleted)
+    }
+}
+
+/// Seconds since the Unix epoch, the timestamp format of every table.
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn open_file(path: &Path) -> Result<Connection> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let conn = Connection::open(path).context("opening database")?;
+    enable_auto_vacuum(&conn).context("enabling auto-vacuum")?;
+    create_tables(&conn)?;
+    Ok(conn)
+}
+
+/// Let `prune` shrink the file. Databases created before auto-vacuum was
+/// enabled are rebuilt once to switch it on.
+fn enable_auto_vacuum(conn: &Connection) -> rusqlite::Result<()> {
+    let mode: i64 = conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+    if mode != INCREMENTAL_VACUUM {
+        conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
+    }
+    Ok(())
+}
+
+fn create_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(lookups::SCHEMA)
+        .and_then(|()| conn.execute_batch(captures::SCHEMA))
+        .and_then(|()| add_missing_column(conn, "lookups", "capture_id", "INTEGER"))
+        .context("creating history tables")
+}
+
+/// Add a column that tables created by an older version lack.
+fn add_missing_column(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let exists = conn
+        .prepare(&format!(
+            "SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"
+        ))?
+        .exists([column])?;
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))?;
+    }
+    Ok(())
+}
+
+/// `LIKE` pattern matching `text` anywhere, with wildcards in it escaped.
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
@@ -0,0 +1,186 @@
+// src-tauri/src/infra/hotkeys.rs
+//
+// Global hotkey bindings: which shortcut (e.g. "Ctrl+Alt+D") runs which app
+// action. Built from the hotkey settings; registering the shortcuts with the
+// OS is left to the app layer.
+
+use tauri_plugin_global_shortcut::Shortcut;
+
+use super::settings::{FieldError, HotkeySettings};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    Capture,
+    RepeatLastRegion,
+    ToggleWatch,
+    OpenHistory,
+    CloseOverlays,
+    LookupClipboard,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 6] = [
+        HotkeyAction::Capture,
+        HotkeyAction::RepeatLastRegion,
+        HotkeyAction::ToggleWatch,
+        HotkeyAction::OpenHistory,
+        HotkeyAction::CloseOverlays,
+        HotkeyAction::LookupClipboard,
+    ];
+
+    /// Settings field holding this action's hotkey.
+    pub fn field(&self) -> &'static str {
+        match self {
+            HotkeyAction::Capture => "hotkeys.capture",
+            HotkeyAction::RepeatLastRegion => "hotkeys.repeat_last_region",
+            HotkeyAction::ToggleWatch => "hotkeys.toggle_watch",
+            HotkeyAction::OpenHistory => "hotkeys.open_history",
+            HotkeyAction::CloseOverlays => "hotkeys.close_overlays",
+            HotkeyAction::LookupClipboard => "hotkeys.lookup_clipboard",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HotkeyAction::Capture => "Capture Region",
+            HotkeyAction::RepeatLastRegion => "Repeat Last Region",
+            HotkeyAction::ToggleWatch => "Toggle Watch Mode",
+            HotkeyAction::OpenHistory => "Open History",
+            HotkeyAction::CloseOverlays => "Close Overlays",
+            HotkeyAction::LookupClipboard => "Look Up Clipboard Text",
+        }
+    }
+
+    /// This action's hotkey string in `hotkeys`.
+    pub fn hotkey(self, hotkeys: &HotkeySettings) -> &str {
+        match self {
+            HotkeyAction::Capture => &hotkeys.capture,
+            HotkeyAction::RepeatLastRegion => &hotkeys.repeat_last_region,
+            HotkeyAction::ToggleWatch => &hotkeys.toggle_watch,
+            HotkeyAction::OpenHistory => &hotkeys.open_history,
+            HotkeyAction::CloseOverlays => &hotkeys.close_overlays,
+            HotkeyAction::LookupClipboard => &hotkeys.lookup_clipboard,
+        }
+    }
+}
+
+/// Parsed hotkey bindings, at most one action per shortcut.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HotkeyRegistry {
+    bindings: Vec<(HotkeyAction, Shortcut)>,
+}
+
+impl HotkeyRegistry {
+    /// Parse the bindings in `hotkeys`; an empty string leaves its action
+    /// unbound. Invalid hotkeys and shortcuts bound twice are reported per
+    /// field and left out of the registry.
+    pub fn parse(hotkeys: &HotkeySettings) -> (Self, Vec<FieldError>) {
+        let mut registry = Self::default();
+        let mut errors = Vec::new();
+
+        for action in HotkeyAction::ALL {
+            let hotkey = action.hotkey(hotkeys).trim();
+            if hotkey.is_empty() {
+                continue;
+            }
+
+            match hotkey.parse::<Shortcut>() {
+                Ok(shortcut) => match registry.action(&shortcut) {
+                    Some(other) => errors.push(FieldError::new(
+                        action.field(),
+                        format!("already used by \"{}\"", other.label()),
+                    )),
+                    None => registry.bindings.push((action, shortcut)),
+                },
+                Err(e) => errors.push(FieldError::new(
+                    action.field(),
+                    format!("invalid hotkey \"{hotkey}\": {e}"),
+                )),
+            }
+        }
+
+        (registry, errors)
+    }
+
+    /// Action bound to `shortcut`.
+    pub fn action(&self, shortcut: &Shortcut) -> Option<HotkeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, s)| s == shortcut)
+            .map(|(action, _)| *action)
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (HotkeyAction, Shortcut)> + '_ {
+        self.bindings.iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(s: &str) -> Shortcut {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn default_bindings() {
+        let (registry, errors) = HotkeyRegistry::parse(&HotkeySettings::default());
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            registry.action(&shortcut("Ctrl+Shift+R")),
+            Some(HotkeyAction::Capture)
+        );
+        assert_eq!(
+            registry.action(&shortcut("Ctrl+Shift+L")),
+            Some(HotkeyAction::RepeatLastRegion)
+        );
+        assert_eq!(registry.action(&shortcut("Ctrl+Alt+D")), None);
+    }
+
+    #[test]
+    fn parses_user_bindings_and_skips_empty_ones() {
+        let hotkeys = HotkeySettings {
+            toggle_watch: "Ctrl+Alt+W".into(),
+            lookup_clipboard: " ctrl+alt+d ".into(),
+            open_history: String::new(),
+            ..HotkeySettings::default()
+        };
+        let (registry, errors) = HotkeyRegistry::parse(&hotkeys);
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            registry.action(&shortcut("Ctrl+Alt+D")),
+            Some(HotkeyAction::LookupClipboard)
+        );
+        assert_eq!(
+            registry.action(&shortcut("Alt+Control+W")),
+            Some(HotkeyAction::ToggleWatch)
+        );
+        assert!(
+            registry
+                .bindings()
+                .all(|(a, _)| a != HotkeyAction::OpenHistory)
+        );
+    }
+
+    #[test]
+    fn conflicts_and_invalid_hotkeys_are_reported() {
+        let hotkeys = HotkeySettings {
+            close_overlays: "Shift+Ctrl+R".into(),
+            open_history: "Ctrl+Hyper+Nope".into(),
+            ..HotkeySettings::default()
+        };
+        let (registry, errors) = HotkeyRegistry::parse(&hotkeys);
+
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["hotkeys.open_history", "hotkeys.close_overlays"]);
+        assert!(errors[1].message.contains("Capture Region"));
+        // the first binding wins
+        assert_eq!(
+            registry.action(&shortcut("Ctrl+Shift+R")),
+            Some(HotkeyAction::Capture)
+        );
+    }
+}
@@ -3,6 +3,8 @@ use pyo3::types::PyAny;
 use pyo3::types::PyBytes;
 use std::sync::{Mutex, OnceLock};
 
+use super::ocr::{OcrEngine, RecognizedText};
+
 // Global singleton for the OCR instance
 static MANGA_OCR_INSTANCE: OnceLock<Py<PyAny>> = OnceLock::new();
 static INIT_LOCK: Mutex<()> = Mutex::new(());
@@ -76,6 +78,27 @@ pub fn manga_ocr(image_bytes: &[u8]) -> PyResult<String> {
     manga_ocr_fast(image_bytes)
 }
 
+/// `OcrEngine` backed by the Python Manga OCR model.
+/// Manga OCR does not report a confidence score.
+pub struct MangaOcrEngine;
+
+impl OcrEngine for MangaOcrEngine {
+    fn name(&self) -> &'static str {
+        "manga-ocr"
+    }
+
+    fn languages(&self) -> &'static [&'static str] {
+        &["ja"]
+    }
+
+    fn recognize(&self, image: &[u8]) -> anyhow::Result<RecognizedText> {
+        Ok(RecognizedText {
+            text: manga_ocr(image)?,
+            confidence: None,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,43 @@
 mod manga_ocr;
 
+pub mod anki;
+pub mod change_detection;
 pub mod dictionary;
+pub mod history;
+pub mod hotkeys;
+pub mod ocr;
+pub mod preprocess;
+pub mod profiles;
 pub mod screenshot;
+pub mod settings;
+pub mod text_detection;
+pub mod words;
 
-pub use manga_ocr::{init_ocr, manga_ocr};
+pub use manga_ocr::{MangaOcrEngine, init_ocr, manga_ocr};
+
+use std::path::Path;
+
+/// Names accepted by `ocr_engine`.
+pub const OCR_ENGINES: [&str; 1] = ["manga-ocr"];
+
+/// OCR engines selectable by name (e.g. in region profiles).
+pub fn ocr_engine(name: &str) -> Option<&'static dyn ocr::OcrEngine> {
+    match name {
+        "manga-ocr" => Some(&MangaOcrEngine),
+        _ => None,
+    }
+}
+
+/// Write `bytes` to a temporary file next to `path` and rename it over
+/// `path`, so a crash mid-write leaves the old file intact and readers never
+/// see a partial one.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
@@ -0,0 +1,314 @@
+// src-tauri/src/infra/ocr/cache.rs
+//
+// Cache in front of the OCR engine. Visual novels show the same text box over
+// and over; re-running the transformer model for an identical capture is
+// wasted time. Entries are keyed by a perceptual hash of the *preprocessed*
+// block, so small rendering noise still hits, together with its exact amount
+// of ink: a hash small enough to tolerate noise cannot see a single changed
+// character in a long line (。 vs 、), but the ink count can. Entries are
+// persisted as JSON between sessions. Changes are written by `flush`, which
+// the app calls periodically and on quit, not per capture.
+
+use screenshots::image::imageops::{self, FilterType};
+use screenshots::image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::RecognizedText;
+use crate::infra::write_atomic;
+
+/// Hash grid. Text needs far more detail than photo hashes (8x8) to tell two
+/// lines of similar length apart.
+const HASH_W: u32 = 32;
+const HASH_H: u32 = 16;
+const HASH_WORDS: usize = (HASH_W * HASH_H / 64) as usize;
+
+/// Allowed differing bits out of `HASH_W * HASH_H` for a hit.
+pub const DEFAULT_TOLERANCE: u32 = 4;
+
+/// Oldest entries are dropped beyond this.
+const MAX_ENTRIES: usize = 5000;
+
+/// Blocks whose size differs by more than this fraction never match.
+const MAX_SIZE_DRIFT: f64 = 0.05;
+
+/// Luma below which a pixel counts as ink.
+const INK_LUMA: u8 = 128;
+
+/// Difference hash (dHash) of an image plus its dimensions and ink.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageHash {
+    pub width: u32,
+    pub height: u32,
+    pub bits: [u64; HASH_WORDS],
+    /// Number of dark pixels, which must match exactly for a hit. Entries
+    /// from before it existed have 0 and are never hit again.
+    #[serde(default)]
+    pub ink: u32,
+}
+
+impl ImageHash {
+    pub fn of(img: &RgbaImage) -> Self {
+        let gray = imageops::grayscale(img);
+        let small = imageops::resize(&gray, HASH_W + 1, HASH_H, FilterType::Triangle);
+
+        let ink = gray.pixels().filter(|p| p[0] < INK_LUMA).count() as u32;
+
+        let mut bits = [0u64; HASH_WORDS];
+        for y in 0..HASH_H {
+            for x in 0..HASH_W {
+                let i = (y * HASH_W + x) as usize;
+                if small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0] {
+                    bits[i / 64] |= 1 << (i % 64);
+                }
+            }
+        }
+
+        Self {
+            width: img.width(),
+            height: img.height(),
+            bits,
+            ink,
+        }
+    }
+
+    pub fn distance(&self, other: &ImageHash) -> u32 {
+        self.bits
+            .iter()
+            .zip(other.bits.iter())
+            .map(|(a, b)| (a ^ b).count_ones())
+            .sum()
+    }
+
+    fn similar_size(&self, other: &ImageHash) -> bool {
+        let drift = |a: u32, b: u32| (a as f64 - b as f64).abs() / a.max(b).max(1) as f64;
+        drift(self.width, other.width) <= MAX_SIZE_DRIFT
+            && drift(self.height, other.height) <= MAX_SIZE_DRIFT
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    engine: String,
+    hash: ImageHash,
+    text: String,
+    confidence: Option<f32>,
+    last_used: u64,
+}
+
+pub struct OcrCache {
+    /// `None` keeps the cache in memory only.
+    path: Option<PathBuf>,
+    tolerance: u32,
+    entries: Mutex<Vec<CacheEntry>>,
+    /// Entries changed since they were last written.
+    dirty: AtomicBool,
+}
+
+impl OcrCache {
+    /// `<platform cache dir>/umod/ocr_cache.json`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|d| d.join("umod").join("ocr_cache.json"))
+    }
+
+    /// Load the cache stored at `path`. A missing or unreadable file starts empty.
+    pub fn open(path: impl Into<PathBuf>, tolerance: u32) -> Self {
+        let path = path.into();
+        let entries = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                eprintln!("OCR cache at {} is corrupt, starting empty: {e}", path.display());
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self {
+            path: Some(path),
+            tolerance,
+            entries: Mutex::new(entries),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    pub fn in_memory(tolerance: u32) -> Self {
+        Self {
+            path: None,
+            tolerance,
+            entries: Mutex::new(Vec::new()),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Closest entry within tolerance produced by `engine`.
+    pub fn get(&self, engine: &str, hash: &ImageHash) -> Option<RecognizedText> {
+        let mut entries = self.entries.lock().unwrap();
+
+        let entry = entries
+            .iter_mut()
+            .filter(|e| e.engine == engine && e.hash.ink == hash.ink && e.hash.similar_size(hash))
+            .map(|e| (e.hash.distance(hash), e))
+            .filter(|(d, _)| *d <= self.tolerance)
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, e)| e)?;
+
+        entry.last_used = now();
+        self.dirty.store(true, Ordering::Relaxed);
+        Some(RecognizedText {
+            text: entry.text.clone(),
+            confidence: entry.confidence,
+        })
+    }
+
+    pub fn insert(&self, engine: &str, hash: ImageHash, recognized: &RecognizedText) {
+        let mut entries = self.entries.lock().unwrap();
+
+        entries.push(CacheEntry {
+            engine: engine.to_string(),
+            hash,
+            text: recognized.text.clone(),
+            confidence: recognized.confidence,
+            last_used: now(),
+        });
+
+        if entries.len() > MAX_ENTRIES {
+            entries.sort_by_key(|e| std::cmp::Reverse(e.last_used));
+            entries.truncate(MAX_ENTRIES);
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+        self.dirty.store(true, Ordering::Relaxed);
+        self.flush();
+    }
+
+    /// Write the entries to disk if they changed since the last write.
+    pub fn flush(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return;
+        }
+
+        // serialize under the lock, write without holding it
+        let json = serde_json::to_vec(&*self.entries.lock().unwrap());
+        // a failed write only costs a future cache miss
+        let written = json
+            .map_err(anyhow::Error::from)
+            .and_then(|json| write_atomic(path, &json));
+        if let Err(e) = written {
+            eprintln!("Failed to save OCR cache to {}: {e}", path.display());
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screenshots::image::Rgba;
+
+    /// Dark "glyph" bars on white, spaced according to `pattern`.
+    fn line(pattern: &[u32]) -> RgbaImage {
+        let mut img = RgbaImage::from_pixel(320, 40, Rgba([255, 255, 255, 255]));
+        let mut x = 4;
+        for &w in pattern {
+            for px in x..(x + w).min(320) {
+                for py in 10..30 {
+                    img.put_pixel(px, py, Rgba([0, 0, 0, 255]));
+                }
+            }
+            x += w + 10;
+        }
+        img
+    }
+
+    #[test]
+    fn identical_image_hits() {
+        let cache = OcrCache::in_memory(DEFAULT_TOLERANCE);
+        let img = line(&[12, 20, 8, 16, 12]);
+        let recognized = RecognizedText {
+            text: "素直にあやまるしか".into(),
+            confidence: None,
+        };
+
+        cache.insert("manga-ocr", ImageHash::of(&img), &recognized);
+
+        let hit = cache.get("manga-ocr", &ImageHash::of(&img)).expect("cache miss");
+        assert_eq!(hit.text, recognized.text);
+        assert!(cache.get("other-engine", &ImageHash::of(&img)).is_none());
+    }
+
+    #[test]
+    fn different_text_misses() {
+        let cache = OcrCache::in_memory(DEFAULT_TOLERANCE);
+        let recognized = RecognizedText {
+            text: "a".into(),
+            confidence: None,
+        };
+
+        cache.insert("manga-ocr", ImageHash::of(&line(&[12, 20, 8, 16, 12])), &recognized);
+
+        let other = ImageHash::of(&line(&[30, 6, 24, 6, 30, 10, 20]));
+        assert!(cache.get("manga-ocr", &other).is_none());
+    }
+
+    #[test]
+    fn one_changed_character_misses() {
+        let cache = OcrCache::in_memory(DEFAULT_TOLERANCE);
+        let recognized = RecognizedText {
+            text: "素直にあやまるしか。".into(),
+            confidence: None,
+        };
+        let pattern = [12, 20, 8, 16, 12, 20, 8, 16, 12, 20];
+
+        // the same long line ending in a dot (。) or a short stroke (、)
+        let mut dot = line(&pattern);
+        let mut comma = dot.clone();
+        for (x, y) in (300..306).flat_map(|x| (22..28).map(move |y| (x, y))) {
+            dot.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+        }
+        for (x, y) in (0..8).flat_map(|i| (0..2).map(move |d| (300 + i / 2 + d, 22 + i))) {
+            comma.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+        }
+
+        cache.insert("manga-ocr", ImageHash::of(&dot), &recognized);
+        assert!(cache.get("manga-ocr", &ImageHash::of(&dot)).is_some());
+        assert!(cache.get("manga-ocr", &ImageHash::of(&comma)).is_none());
+    }
+
+    #[test]
+    fn flush_writes_only_changes() {
+        let path = std::env::temp_dir().join(format!("umod-ocr-cache-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let recognized = RecognizedText {
+            text: "a".into(),
+            confidence: None,
+        };
+
+        let cache = OcrCache::open(&path, DEFAULT_TOLERANCE);
+        cache.flush();
+        assert!(!path.exists(), "nothing changed yet");
+
+        cache.insert("manga-ocr", ImageHash::of(&line(&[12, 20])), &recognized);
+        assert!(!path.exists(), "inserts are not written right away");
+        cache.flush();
+
+        let reopened = OcrCache::open(&path, DEFAULT_TOLERANCE);
+        let hash = ImageHash::of(&line(&[12, 20]));
+        assert!(reopened.get("manga-ocr", &hash).is_some());
+        assert!(!path.with_extension("json.tmp").exists());
+        let _ = std::fs::remove_file(&path);
+    }
+}
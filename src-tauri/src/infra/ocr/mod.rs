@@ -0,0 +1,127 @@
+// src-tauri/src/infra/ocr/mod.rs
+//
+// Engine-agnostic OCR pipeline: text block detection -> preprocessing ->
+// (cache) -> recognition, producing an `OcrResult` in screen coordinates.
+
+pub mod cache;
+
+use anyhow::Result;
+use screenshots::image::RgbaImage;
+use std::time::Instant;
+
+use crate::infra::preprocess::{self, PreprocessPipeline};
+use cache::{ImageHash, OcrCache};
+use crate::infra::text_detection::{TextBlock, detect_text_blocks};
+use crate::shared::models::ocr::{OcrBlock, OcrResult, Orientation, ScreenRect};
+
+/// Raw output of an engine for a single text block.
+#[derive(Debug, Clone)]
+pub struct RecognizedText {
+    pub text: String,
+    pub confidence: Option<f32>,
+}
+
+pub trait OcrEngine: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Language codes (ISO 639-1) the engine can read.
+    fn languages(&self) -> &'static [&'static str];
+
+    /// Recognize a single text block, given as encoded image bytes (PNG).
+    fn recognize(&self, image: &[u8]) -> Result<RecognizedText>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OcrOptions {
+    pub preprocess: PreprocessPipeline,
+    /// Detect and OCR text blocks separately instead of the whole image.
+    pub split_blocks: bool,
+}
+
+/// Run OCR over `image`, whose top-left corner sits at `origin` on screen.
+/// Blocks found in `cache` skip the engine entirely.
+pub fn run_ocr(
+    engine: &dyn OcrEngine,
+    cache: Option<&OcrCache>,
+    image: &RgbaImage,
+    origin: (i32, i32),
+    options: &OcrOptions,
+) -> Result<OcrResult> {
+    let started = Instant::now();
+
+    let detected = if options.split_blocks {
+        detect_text_blocks(image)
+    } else {
+        vec![TextBlock::whole(image)]
+    };
+
+    let mut blocks = Vec::with_capacity(detected.len());
+    for block in detected {
+        let processed = options.preprocess.apply(&block.crop(image));
+        let recognized = recognize_cached(engine, cache, &processed)?;
+
+        if recognized.text.is_empty() {
+            continue;
+        }
+
+        blocks.push(OcrBlock {
+            lines: recognized.text.lines().map(str::to_string).collect(),
+            text: recognized.text,
+            bounds: ScreenRect {
+                x: origin.0 + block.x as i32,
+                y: origin.1 + block.y as i32,
+                w: block.w,
+                h: block.h,
+            },
+            orientation: block.orientation,
+            confidence: recognized.confidence,
+        });
+    }
+
+    let vertical = blocks
+        .iter()
+        .filter(|b| b.orientation == Orientation::Vertical)
+        .count();
+    let orientation = if vertical * 2 > blocks.len() {
+        Orientation::Vertical
+    } else {
+        Orientation::Horizontal
+    };
+
+    let confidence = blocks
+        .iter()
+        .filter_map(|b| b.confidence)
+        .reduce(f32::min);
+
+    Ok(OcrResult {
+        text: blocks
+            .iter()
+            .map(|b| b.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        blocks,
+        orientation,
+        engine: engine.name().to_string(),
+        confidence,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+fn recognize_cached(
+    engine: &dyn OcrEngine,
+    cache: Option<&OcrCache>,
+    processed: &RgbaImage,
+) -> Result<RecognizedText> {
+    let Some(cache) = cache else {
+        return engine.recognize(&preprocess::encode_png(processed)?);
+    };
+
+    let hash = ImageHash::of(processed);
+    if let Some(hit) = cache.get(engine.name(), &hash) {
+        return Ok(hit);
+    }
+
+    let recognized = engine.recognize(&preprocess::encode_png(processed)?)?;
+    cache.insert(engine.name(), hash, &recognized);
+    Ok(recognized)
+}
@@ -0,0 +1,399 @@
+// src-tauri/src/infra/preprocess/mod.rs
+//
+// Image preprocessing applied to a captured region before it is handed to OCR.
+// Each step works on an RGBA `ImageBuffer` and returns a new one, so steps can be
+// chained freely and dumped to disk for debugging.
+
+use anyhow::Result;
+use screenshots::image::imageops::{self, FilterType};
+use screenshots::image::{GrayImage, ImageFormat, Luma, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+/// Upscaling never goes beyond this factor, no matter how small the capture is.
+const MAX_UPSCALE_FACTOR: u32 = 4;
+
+/// A single preprocessing operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PreprocessStep {
+    /// Upscale so the image is at least `min_height` pixels tall (tiny text).
+    Upscale { min_height: u32 },
+    /// Convert to luminance.
+    Grayscale,
+    /// Local mean threshold over a `(2 * radius + 1)` window, minus `offset`.
+    AdaptiveThreshold { radius: u32, offset: i16 },
+    /// Invert colors. With `auto`, only when the image is mostly dark
+    /// (light text on a dark background).
+    Invert { auto: bool },
+    /// Median filter over a `(2 * radius + 1)` window.
+    Denoise { radius: u32 },
+    /// Add a border of `pixels` filled with the estimated background color.
+    Pad { pixels: u32 },
+}
+
+/// Built-in pipelines offered in the tray.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreprocessPreset {
+    None,
+    Default,
+    LightOnDark,
+    BusyBackground,
+}
+
+impl PreprocessPreset {
+    pub const ALL: [PreprocessPreset; 4] = [
+        PreprocessPreset::None,
+        PreprocessPreset::Default,
+        PreprocessPreset::LightOnDark,
+        PreprocessPreset::BusyBackground,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            PreprocessPreset::None => "none",
+            PreprocessPreset::Default => "default",
+            PreprocessPreset::LightOnDark => "light_on_dark",
+            PreprocessPreset::BusyBackground => "busy_background",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PreprocessPreset::None => "None",
+            PreprocessPreset::Default => "Default",
+            PreprocessPreset::LightOnDark => "Light Text on Dark",
+            PreprocessPreset::BusyBackground => "Busy Background",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.id() == id)
+    }
+
+    pub fn steps(&self) -> Vec<PreprocessStep> {
+        use PreprocessStep::*;
+
+        match self {
+            PreprocessPreset::None => Vec::new(),
+            PreprocessPreset::Default => vec![Upscale { min_height: 64 }, Pad { pixels: 8 }],
+            PreprocessPreset::LightOnDark => vec![
+                Upscale { min_height: 64 },
+                Grayscale,
+                Invert { auto: true },
+                Pad { pixels: 8 },
+            ],
+            PreprocessPreset::BusyBackground => vec![
+                Upscale { min_height: 64 },
+                Grayscale,
+                Invert { auto: true },
+                Denoise { radius: 1 },
+                AdaptiveThreshold {
+                    radius: 12,
+                    offset: 10,
+                },
+                Pad { pixels: 8 },
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreprocessPipeline {
+    pub steps: Vec<PreprocessStep>,
+
+    /// When set, the input and the output of every step are written here as PNGs.
+    #[serde(default)]
+    pub debug_dir: Option<PathBuf>,
+}
+
+impl Default for PreprocessPipeline {
+    fn default() -> Self {
+        Self::from_preset(PreprocessPreset::Default)
+    }
+}
+
+impl PreprocessPipeline {
+    /// `<platform data dir>/umod/preprocess_debug`, where "Save Debug Images"
+    /// puts its dumps.
+    pub fn default_debug_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("umod").join("preprocess_debug"))
+    }
+
+    pub fn from_preset(preset: PreprocessPreset) -> Self {
+        Self {
+            steps: preset.steps(),
+            debug_dir: None,
+        }
+    }
+
+    /// Run every step in order.
+    pub fn apply(&self, input: &RgbaImage) -> RgbaImage {
+        let mut img = input.clone();
+        self.dump(0, "input", &img);
+
+        for (i, step) in self.steps.iter().enumerate() {
+            img = step.apply(&img);
+            self.dump(i + 1, step.name(), &img);
+        }
+
+        img
+    }
+
+    fn dump(&self, index: usize, name: &str, img: &RgbaImage) {
+        let Some(dir) = &self.debug_dir else {
+            return;
+        };
+
+        // debug output only, never fail a capture over it
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            eprintln!("preprocess dump failed: {e}");
+            return;
+        }
+        let path = dir.join(format!("{index:02}_{name}.png"));
+        if let Err(e) = img.save_with_format(&path, ImageFormat::Png) {
+            eprintln!("preprocess dump failed ({}): {e}", path.display());
+        }
+    }
+}
+
+impl PreprocessStep {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PreprocessStep::Upscale { .. } => "upscale",
+            PreprocessStep::Grayscale => "grayscale",
+            PreprocessStep::AdaptiveThreshold { .. } => "adaptive_threshold",
+            PreprocessStep::Invert { .. } => "invert",
+            PreprocessStep::Denoise { .. } => "denoise",
+            PreprocessStep::Pad { .. } => "pad",
+        }
+    }
+
+    pub fn apply(&self, img: &RgbaImage) -> RgbaImage {
+        match *self {
+            PreprocessStep::Upscale { min_height } => upscale(img, min_height),
+            PreprocessStep::Grayscale => gray_to_rgba(&imageops::grayscale(img)),
+            PreprocessStep::AdaptiveThreshold { radius, offset } => {
+                gray_to_rgba(&adaptive_threshold(&imageops::grayscale(img), radius, offset))
+            }
+            PreprocessStep::Invert { auto } => {
+                let mut out = img.clone();
+                if !auto || mean_luma(img) < 128.0 {
+                    imageops::invert(&mut out);
+                }
+                out
+            }
+            PreprocessStep::Denoise { radius } => median_filter(img, radius),
+            PreprocessStep::Pad { pixels } => pad(img, pixels),
+        }
+    }
+}
+
+/// Encode as PNG, which is what the OCR engine expects as input bytes.
+pub fn encode_png(img: &RgbaImage) -> Result<Vec<u8>> {
+    let mut buf = Cursor::new(Vec::new());
+    img.write_to(&mut buf, ImageFormat::Png)?;
+    Ok(buf.into_inner())
+}
+
+/* ---------------- Steps ---------------- */
+
+fn upscale(img: &RgbaImage, min_height: u32) -> RgbaImage {
+    let (w, h) = img.dimensions();
+    if h == 0 || h >= min_height {
+        return img.clone();
+    }
+
+    let factor = min_height.div_ceil(h).min(MAX_UPSCALE_FACTOR);
+    imageops::resize(img, w * factor, h * factor, FilterType::CatmullRom)
+}
+
+fn gray_to_rgba(gray: &GrayImage) -> RgbaImage {
+    RgbaImage::from_fn(gray.width(), gray.height(), |x, y| {
+        let l = gray.get_pixel(x, y)[0];
+        Rgba([l, l, l, 255])
+    })
+}
+
+fn mean_luma(img: &RgbaImage) -> f64 {
+    let gray = imageops::grayscale(img);
+    let n = gray.len().max(1) as f64;
+    gray.iter().map(|&v| v as f64).sum::<f64>() / n
+}
+
+/// Mean-C adaptive threshold using an integral image, so the cost does not
+/// depend on the window size.
+fn adaptive_threshold(gray: &GrayImage, radius: u32, offset: i16) -> GrayImage {
+    let (w, h) = gray.dimensions();
+    let (wu, hu) = (w as usize, h as usize);
+
+    // (w + 1) x (h + 1) summed-area table
+    let mut integral = vec![0u64; (wu + 1) * (hu + 1)];
+    for y in 0..hu {
+        let mut row = 0u64;
+        for x in 0..wu {
+            row += gray.get_pixel(x as u32, y as u32)[0] as u64;
+            integral[(y + 1) * (wu + 1) + x + 1] = integral[y * (wu + 1) + x + 1] + row;
+        }
+    }
+
+    let r = radius as usize;
+    GrayImage::from_fn(w, h, |x, y| {
+        let (x, y) = (x as usize, y as usize);
+        let x0 = x.saturating_sub(r);
+        let y0 = y.saturating_sub(r);
+        let x1 = (x + r + 1).min(wu);
+        let y1 = (y + r + 1).min(hu);
+
+        let sum = integral[y1 * (wu + 1) + x1] + integral[y0 * (wu + 1) + x0]
+            - integral[y0 * (wu + 1) + x1]
+            - integral[y1 * (wu + 1) + x0];
+        let count = ((x1 - x0) * (y1 - y0)) as i64;
+        let mean = sum as i64 / count;
+
+        let v = gray.get_pixel(x as u32, y as u32)[0] as i64;
+        if v > mean - offset as i64 {
+            Luma([255])
+        } else {
+            Luma([0])
+        }
+    })
+}
+
+fn median_filter(img: &RgbaImage, radius: u32) -> RgbaImage {
+    if radius == 0 {
+        return img.clone();
+    }
+
+    let (w, h) = img.dimensions();
+    let r = radius as i64;
+    let mut window: [Vec<u8>; 3] = Default::default();
+
+    RgbaImage::from_fn(w, h, |x, y| {
+        for channel in window.iter_mut() {
+            channel.clear();
+        }
+
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let sx = (x as i64 + dx).clamp(0, w as i64 - 1) as u32;
+                let sy = (y as i64 + dy).clamp(0, h as i64 - 1) as u32;
+                let p = img.get_pixel(sx, sy);
+                for c in 0..3 {
+                    window[c].push(p[c]);
+                }
+            }
+        }
+
+        let mut out = [0u8, 0, 0, img.get_pixel(x, y)[3]];
+        for c in 0..3 {
+            let mid = window[c].len() / 2;
+            out[c] = *window[c].select_nth_unstable(mid).1;
+        }
+        Rgba(out)
+    })
+}
+
+/// Average color of the outermost pixels, used as the padding fill.
+fn border_color(img: &RgbaImage) -> Rgba<u8> {
+    let (w, h) = img.dimensions();
+    if w == 0 || h == 0 {
+        return Rgba([255, 255, 255, 255]);
+    }
+
+    let mut sum = [0u64; 3];
+    let mut n = 0u64;
+    for (x, y, p) in img.enumerate_pixels() {
+        if x == 0 || y == 0 || x == w - 1 || y == h - 1 {
+            for c in 0..3 {
+                sum[c] += p[c] as u64;
+            }
+            n += 1;
+        }
+    }
+
+    Rgba([
+        (sum[0] / n) as u8,
+        (sum[1] / n) as u8,
+        (sum[2] / n) as u8,
+        255,
+    ])
+}
+
+fn pad(img: &RgbaImage, pixels: u32) -> RgbaImage {
+    if pixels == 0 {
+        return img.clone();
+    }
+
+    let (w, h) = img.dimensions();
+    let mut out = RgbaImage::from_pixel(w + 2 * pixels, h + 2 * pixels, border_color(img));
+    imageops::replace(&mut out, img, pixels as i64, pixels as i64);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(w: u32, h: u32, value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(w, h, Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn upscale_factor_is_capped() {
+        let step = PreprocessStep::Upscale { min_height: 64 };
+
+        // 64 / 4 would need 16x
+        assert_eq!(step.apply(&solid(10, 4, 0)).dimensions(), (40, 16));
+        assert_eq!(step.apply(&solid(10, 40, 0)).dimensions(), (20, 80));
+        assert_eq!(step.apply(&solid(10, 64, 0)).dimensions(), (10, 64));
+    }
+
+    #[test]
+    fn adaptive_threshold_keeps_a_gradient_white() {
+        // left-to-right gradient with one darker stroke in the middle
+        let gray = GrayImage::from_fn(64, 8, |x, _| {
+            let v = x as u8 * 4;
+            Luma([if x == 32 { v - 60 } else { v }])
+        });
+        let out = adaptive_threshold(&gray, 4, 10);
+
+        for (x, _, p) in out.enumerate_pixels() {
+            let expected = if x == 32 { 0 } else { 255 };
+            assert_eq!(p[0], expected, "x = {x}");
+        }
+    }
+
+    #[test]
+    fn auto_invert_only_inverts_dark_images() {
+        let auto = PreprocessStep::Invert { auto: true };
+        assert_eq!(auto.apply(&solid(4, 4, 20)), solid(4, 4, 235));
+        assert_eq!(auto.apply(&solid(4, 4, 200)), solid(4, 4, 200));
+
+        let always = PreprocessStep::Invert { auto: false };
+        assert_eq!(always.apply(&solid(4, 4, 200)), solid(4, 4, 55));
+    }
+
+    #[test]
+    fn median_filter_removes_salt_noise() {
+        let mut img = solid(5, 5, 100);
+        img.put_pixel(2, 2, Rgba([255, 255, 255, 255]));
+        img.put_pixel(0, 4, Rgba([255, 255, 255, 255]));
+
+        assert_eq!(median_filter(&img, 1), solid(5, 5, 100));
+    }
+
+    #[test]
+    fn pad_adds_a_border_of_the_background_color() {
+        let mut img = RgbaImage::from_pixel(4, 3, Rgba([10, 20, 30, 255]));
+        img.put_pixel(1, 1, Rgba([0, 0, 0, 255]));
+        let out = pad(&img, 2);
+
+        assert_eq!(out.dimensions(), (8, 7));
+        assert_eq!(*out.get_pixel(0, 0), Rgba([10, 20, 30, 255]));
+        assert_eq!(*out.get_pixel(7, 6), Rgba([10, 20, 30, 255]));
+        assert_eq!(*out.get_pixel(3, 3), Rgba([0, 0, 0, 255]));
+    }
+}
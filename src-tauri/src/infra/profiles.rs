@@ -0,0 +1,226 @@
+// src-tauri/src/infra/profiles.rs
+//
+// Named capture profiles: a saved region (e.g. the dialogue box of one game)
+// together with the OCR settings that work for it and an optional hotkey.
+// Stored as JSON in the platform config dir.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::infra::preprocess::PreprocessPipeline;
+use crate::infra::screenshot::window::WindowTarget;
+use crate::infra::write_atomic;
+use crate::shared::models::ocr::ScreenRect;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionProfile {
+    pub name: String,
+    /// Physical pixels. Relative to `monitor` when set, so the profile keeps
+    /// working if displays are rearranged; desktop coordinates otherwise.
+    pub bounds: ScreenRect,
+    #[serde(default)]
+    pub monitor: Option<String>,
+    /// Capture this window (or an area of it) instead of `bounds`.
+    #[serde(default)]
+    pub window: Option<WindowTarget>,
+    #[serde(default)]
+    pub preprocess: PreprocessPipeline,
+    #[serde(default)]
+    pub split_blocks: bool,
+    #[serde(default = "default_engine")]
+    pub engine: String,
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Global shortcut such as "Ctrl+Alt+1".
+    #[serde(default)]
+    pub hotkey: Option<String>,
+}
+
+fn default_engine() -> String {
+    "manga-ocr".into()
+}
+
+fn default_language() -> String {
+    "ja".into()
+}
+
+impl RegionProfile {
+    pub fn new(name: impl Into<String>, bounds: ScreenRect) -> Self {
+        Self {
+            name: name.into(),
+            bounds,
+            monitor: None,
+            window: None,
+            preprocess: PreprocessPipeline::default(),
+            split_blocks: false,
+            engine: default_engine(),
+            language: default_language(),
+            hotkey: None,
+        }
+    }
+}
+
+pub struct ProfileStore {
+    /// `None` keeps profiles in memory only.
+    path: Option<PathBuf>,
+    profiles: Mutex<Vec<RegionProfile>>,
+}
+
+impl ProfileStore {
+    /// `<platform config dir>/umod/profiles.json`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("umod").join("profiles.json"))
+    }
+
+    /// Load the profiles stored at `path`. A missing file starts empty.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let profiles = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                eprintln!(
+                    "Profiles at {} are invalid, ignoring them: {e}",
+                    path.display()
+                );
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self {
+            path: Some(path),
+            profiles: Mutex::new(profiles),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            profiles: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn list(&self) -> Vec<RegionProfile> {
+        self.profiles.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, name: &str) -> Option<RegionProfile> {
+        self.profiles
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+    }
+
+    /// Add `profile`, replacing any profile with the same name. Nothing
+    /// changes if the profiles cannot be written.
+    pub fn save(&self, profile: RegionProfile) -> Result<()> {
+        if profile.name.trim().is_empty() {
+            bail!("Profile name must not be empty");
+        }
+        if profile.window.is_none() && (profile.bounds.w == 0 || profile.bounds.h == 0) {
+            bail!("Profile \"{}\" has an empty region", profile.name);
+        }
+
+        let mut profiles = self.profiles.lock().unwrap();
+        let mut updated = profiles.clone();
+        match updated.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => updated.push(profile),
+        }
+
+        self.persist(&updated)?;
+        *profiles = updated;
+        Ok(())
+    }
+
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let mut profiles = self.profiles.lock().unwrap();
+        let mut updated = profiles.clone();
+        updated.retain(|p| p.name != name);
+
+        if updated.len() == profiles.len() {
+            return Ok(false);
+        }
+        self.persist(&updated)?;
+        *profiles = updated;
+        Ok(true)
+    }
+
+    /// First of "`base` 1", "`base` 2", ... that is not taken yet.
+    pub fn unused_name(&self, base: &str) -> String {
+        let profiles = self.profiles.lock().unwrap();
+        (1..)
+            .map(|n| format!("{base} {n}"))
+            .find(|name| !profiles.iter().any(|p| &p.name == name))
+            .unwrap()
+    }
+
+    fn persist(&self, profiles: &[RegionProfile]) -> Result<()> {
+        match &self.path {
+            Some(path) => write_json(path, profiles),
+            None => Ok(()),
+        }
+    }
+}
+
+fn write_json(path: &Path, profiles: &[RegionProfile]) -> Result<()> {
+    write_atomic(path, &serde_json::to_vec_pretty(profiles)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect() -> ScreenRect {
+        ScreenRect {
+            x: 100,
+            y: 800,
+            w: 1200,
+            h: 200,
+        }
+    }
+
+    #[test]
+    fn save_replaces_by_name() {
+        let store = ProfileStore::in_memory();
+        store.save(RegionProfile::new("Dialogue", rect())).unwrap();
+
+        let mut updated = RegionProfile::new("Dialogue", rect());
+        updated.hotkey = Some("Ctrl+Alt+1".into());
+        store.save(updated).unwrap();
+
+        assert_eq!(store.list().len(), 1);
+        assert_eq!(
+            store.get("Dialogue").unwrap().hotkey.as_deref(),
+            Some("Ctrl+Alt+1")
+        );
+        assert_eq!(store.unused_name("Dialogue"), "Dialogue 1");
+        assert!(store.remove("Dialogue").unwrap());
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn failed_writes_change_nothing() {
+        // a file where the profiles' directory should be
+        let blocker = std::env::temp_dir().join(format!("umod-profiles-{}", std::process::id()));
+        std::fs::write(&blocker, b"").unwrap();
+        let store = ProfileStore::open(blocker.join("profiles.json"));
+
+        assert!(store.save(RegionProfile::new("Dialogue", rect())).is_err());
+        assert!(store.list().is_empty());
+        std::fs::remove_file(blocker).unwrap();
+    }
+
+    #[test]
+    fn missing_fields_get_defaults() {
+        let json = r#"[{ "name": "VN", "bounds": { "x": 0, "y": 0, "w": 10, "h": 10 } }]"#;
+        let profiles: Vec<RegionProfile> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(profiles[0].engine, "manga-ocr");
+        assert_eq!(profiles[0].language, "ja");
+        assert_eq!(profiles[0].preprocess, PreprocessPipeline::default());
+    }
+}
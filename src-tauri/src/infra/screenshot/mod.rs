@@ -1,23 +1,127 @@
-use anyhow::Result;
-use screenshots::image::{ImageBuffer, ImageFormat, Rgba};
+use anyhow::{Result, bail};
+use screenshots::image::imageops;
+use screenshots::image::{self, ImageFormat, RgbaImage};
 use screenshots::Screen;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-const SCREENSHOT_PATH: &str = "region_capture.png";
+pub mod window;
+
+/// Disambiguates captures saved within the same millisecond.
+static CAPTURE_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Image files accepted for OCR outside of region capture.
+pub const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "webp"];
+
+/// Decode a PNG/JPEG/WebP file from disk.
+pub fn load_image_file(path: &Path) -> Result<RgbaImage> {
+    let supported = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+    if !supported {
+        bail!("Unsupported image file: {}", path.display());
+    }
+
+    Ok(image::open(path)?.to_rgba8())
+}
 
 pub struct ScreenshotService;
 
 impl ScreenshotService {
-    pub fn capture_region(&self, x: i32, y: i32, w: u32, h: u32) -> Result<String> {
-        // Choose primary display (or later: display that contains the region)
-        let screens = Screen::all()?;
-        let screen = &screens[0];
-
-        // Capture specified region
-        let img: ImageBuffer<Rgba<u8>, Vec<u8>> = screen.capture_area(x, y, w, h)?;
-        img.save_with_format(SCREENSHOT_PATH, ImageFormat::Png)
-            .unwrap();
-
-        // img is already an ImageBuffer, return it directly
-        Ok(String::from(SCREENSHOT_PATH))
+    /// Capture a rectangle given in physical desktop pixels. The rectangle may
+    /// lie on any display or straddle several; parts outside every display
+    /// stay transparent.
+    pub fn capture_region(&self, x: i32, y: i32, w: u32, h: u32) -> Result<RgbaImage> {
+        let mut out = RgbaImage::new(w, h);
+        let mut covered = false;
+
+        for screen in Screen::all()? {
+            let info = screen.display_info;
+            let display = (info.x, info.y, info.width, info.height);
+            let Some((ix, iy, iw, ih)) = intersect((x, y, w, h), display) else {
+                continue;
+            };
+
+            // full-resolution shot of the display, cropped in physical pixels
+            let shot = screen.capture()?;
+            let part =
+                imageops::crop_imm(&shot, (ix - info.x) as u32, (iy - info.y) as u32, iw, ih)
+                    .to_image();
+
+            imageops::replace(&mut out, &part, (ix - x) as i64, (iy - y) as i64);
+            covered = true;
+        }
+
+        if !covered {
+            bail!("Region ({x}, {y}, {w}x{h}) is not on any display");
+        }
+        Ok(out)
+    }
+
+    /// `<platform pictures dir>/UMOD Captures`, used when saving is turned on.
+    pub fn default_captures_dir() -> Option<PathBuf> {
+        dirs::picture_dir()
+            .or_else(dirs::data_dir)
+            .map(|d| d.join("UMOD Captures"))
+    }
+
+    /// Save a capture as PNG under a unique name inside `dir`.
+    pub fn save_capture(&self, img: &RgbaImage, dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let n = CAPTURE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("capture-{millis}-{n}.png"));
+
+        img.save_with_format(&path, ImageFormat::Png)?;
+        Ok(path)
+    }
+}
+
+/// Part of `rect` lying on `display`, both in physical desktop pixels.
+///
+/// `display_info` reports physical bounds for DPI-aware processes (its scale
+/// factor is 1.0 there), the same space the overlay maps its rects into, so
+/// displays at different scales are compared without any conversion.
+fn intersect(
+    (x, y, w, h): (i32, i32, u32, u32),
+    (dx, dy, dw, dh): (i32, i32, u32, u32),
+) -> Option<(i32, i32, u32, u32)> {
+    let x0 = x.max(dx);
+    let y0 = y.max(dy);
+    let x1 = (x + w as i32).min(dx + dw as i32);
+    let y1 = (y + h as i32).min(dy + dh as i32);
+    if x0 >= x1 || y0 >= y1 {
+        return None;
+    }
+    Some((x0, y0, (x1 - x0) as u32, (y1 - y0) as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1920x1080 at 100%, and 2560x1440 at 150% right of it
+    const PRIMARY: (i32, i32, u32, u32) = (0, 0, 1920, 1080);
+    const SECONDARY: (i32, i32, u32, u32) = (1920, 0, 2560, 1440);
+
+    #[test]
+    fn regions_are_split_per_display() {
+        let region = (1800, 100, 400, 100);
+
+        assert_eq!(intersect(region, PRIMARY), Some((1800, 100, 120, 100)));
+        assert_eq!(intersect(region, SECONDARY), Some((1920, 100, 280, 100)));
+    }
+
+    #[test]
+    fn regions_off_a_display_miss_it() {
+        assert_eq!(intersect((2000, 100, 200, 100), PRIMARY), None);
+        assert_eq!(intersect((100, 1200, 200, 100), PRIMARY), None);
+        assert_eq!(intersect((100, 1200, 200, 100), SECONDARY), None);
     }
 }
@@ -0,0 +1,132 @@
+// src-tauri/src/infra/screenshot/window.rs
+//
+// Capture of a single top-level window by id. Windows are looked up again on
+// every capture, so a window that moved (or was resized) is still captured
+// correctly, and a sub-rectangle stays fixed relative to the window.
+
+use anyhow::{Context, Result, bail};
+use screenshots::image::{RgbaImage, imageops};
+use serde::{Deserialize, Serialize};
+use xcap::Window;
+
+use crate::shared::models::ocr::ScreenRect;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowInfo {
+    pub id: u32,
+    pub title: String,
+    pub app_name: String,
+    pub bounds: ScreenRect,
+}
+
+/// A rectangle relative to a window's top-left corner, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowArea {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+/// A window chosen for capture. The id is tried first; window ids do not
+/// survive restarts, so the title is used as a fallback.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowTarget {
+    pub id: u32,
+    pub title: String,
+    #[serde(default)]
+    pub area: Option<WindowArea>,
+}
+
+/// Capturable top-level windows: visible, titled and not minimized.
+pub fn list_windows() -> Result<Vec<WindowInfo>> {
+    let mut out = Vec::new();
+
+    for window in Window::all()? {
+        if window.is_minimized().unwrap_or(true) {
+            continue;
+        }
+        let Ok(info) = window_info(&window) else {
+            continue;
+        };
+        if info.title.trim().is_empty() || info.bounds.w == 0 || info.bounds.h == 0 {
+            continue;
+        }
+        out.push(info);
+    }
+
+    Ok(out)
+}
+
+/// Current position and size of the window `id`.
+pub fn find_window(id: u32) -> Result<WindowInfo> {
+    let window = Window::all()?
+        .into_iter()
+        .find(|w| w.id().ok() == Some(id))
+        .with_context(|| format!("Window {id} no longer exists"))?;
+    window_info(&window)
+}
+
+/// Capture the target window, or only its `area`. Returns the image and
+/// where it currently sits on screen.
+pub fn capture_window(target: &WindowTarget) -> Result<(RgbaImage, ScreenRect)> {
+    let window = lookup(target)?;
+    if window.is_minimized()? {
+        bail!("Window \"{}\" is minimized", target.title);
+    }
+
+    let info = window_info(&window)?;
+    let shot = window.capture_image()?;
+    let (w, h) = shot.dimensions();
+
+    // xcap uses a newer `image`; move the raw buffer across
+    let image = RgbaImage::from_raw(w, h, shot.into_raw())
+        .context("Window capture has an invalid size")?;
+
+    let Some(area) = target.area else {
+        return Ok((image, info.bounds));
+    };
+
+    if area.x >= w || area.y >= h {
+        bail!("Capture area lies outside the window (window is now {w}x{h})");
+    }
+    let cropped = imageops::crop_imm(&image, area.x, area.y, area.w, area.h).to_image();
+    let bounds = ScreenRect {
+        x: info.bounds.x + area.x as i32,
+        y: info.bounds.y + area.y as i32,
+        w: cropped.width(),
+        h: cropped.height(),
+    };
+
+    Ok((cropped, bounds))
+}
+
+fn lookup(target: &WindowTarget) -> Result<Window> {
+    let mut windows = Window::all()?;
+
+    let by_id = windows.iter().position(|w| w.id().ok() == Some(target.id));
+    let by_title = || {
+        windows
+            .iter()
+            .position(|w| w.title().ok().as_deref() == Some(target.title.as_str()))
+    };
+
+    let index = by_id
+        .or_else(by_title)
+        .with_context(|| format!("Window \"{}\" no longer exists", target.title))?;
+    Ok(windows.swap_remove(index))
+}
+
+fn window_info(window: &Window) -> Result<WindowInfo> {
+    Ok(WindowInfo {
+        id: window.id()?,
+        title: window.title()?,
+        app_name: window.app_name().unwrap_or_default(),
+        bounds: ScreenRect {
+            x: window.x()?,
+            y: window.y()?,
+            w: window.width()?,
+            h: window.height()?,
+        },
+    })
+}
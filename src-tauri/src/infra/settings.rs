@@ -0,0 +1,805 @@
+// src-tauri/src/infra/settings.rs
+//
+// User settings, persisted as JSON in the platform config dir. The file carries
+// a schema version; older files are upgraded by the migrations below before
+// they are parsed, and every loaded or submitted value is validated. Unknown or
+// missing fields fall back to their defaults, so hand-edited files stay usable.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use super::anki;
+use super::dictionary::DEFAULT_ENDPOINT;
+use super::hotkeys::HotkeyRegistry;
+use super::preprocess::PreprocessPreset;
+use super::write_atomic;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[v0_to_v1, v1_to_v2];
+
+/// Schema version written by this build.
+pub const SETTINGS_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Window sizes outside this range (logical pixels) are rejected.
+const MIN_WINDOW_SIDE: u32 = 80;
+const MAX_WINDOW_SIDE: u32 = 8192;
+
+/// Overlay font sizes outside this range (px) are rejected.
+const MIN_FONT_SIZE: u32 = 8;
+const MAX_FONT_SIZE: u32 = 72;
+
+const MAX_HISTORY_ENTRIES: u32 = 100_000;
+const MAX_RETENTION_DAYS: u32 = 3650;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub hotkeys: HotkeySettings,
+    pub dictionary: DictionarySettings,
+    pub ocr: OcrSettings,
+    pub overlay: OverlaySettings,
+    pub history: HistorySettings,
+    pub anki: AnkiSettings,
+    pub windows: WindowSettings,
+    /// Folder every capture is also saved to as PNG; `None` keeps captures
+    /// in memory only.
+    pub captures_dir: Option<PathBuf>,
+}
+
+/// Global shortcuts such as "Ctrl+Alt+D"; an empty string leaves the action
+/// unbound.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeySettings {
+    /// Start a region selection.
+    pub capture: String,
+    /// Capture the last selected region again.
+    pub repeat_last_region: String,
+    /// Start or stop watch mode.
+    pub toggle_watch: String,
+    pub open_history: String,
+    /// Close the OCR overlays and the lookup window.
+    pub close_overlays: String,
+    /// Look up the text on the clipboard.
+    pub lookup_clipboard: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DictionarySettings {
+    pub backend: DictionaryBackend,
+    /// Base URL of the dictionary backend.
+    pub endpoint: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DictionaryBackend {
+    /// Yomitan's local HTTP API.
+    #[default]
+    Yomitan,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrSettings {
+    /// Engine name, see `infra::OCR_ENGINES`.
+    pub engine: String,
+    pub device: OcrDevice,
+    pub preprocess: PreprocessPreset,
+    /// Detect and OCR text blocks separately.
+    pub split_blocks: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OcrDevice {
+    /// Always run on the CPU (avoids CUDA tensor issues).
+    #[default]
+    Cpu,
+    /// Use CUDA when it is available.
+    Cuda,
+}
+
+impl OcrDevice {
+    pub fn force_cpu(&self) -> bool {
+        *self == OcrDevice::Cpu
+    }
+}
+
+/// Look of the OCR result overlay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlaySettings {
+    /// Text size in px.
+    pub font_size: u32,
+    /// Text color as `#rrggbb`.
+    pub text_color: String,
+    /// Opacity of the panel behind the text, 0 to 1.
+    pub background_opacity: f32,
+    /// Color each word by its status in the word list.
+    pub highlight_words: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// Entries older than this are deleted; 0 keeps them forever.
+    pub retention_days: u32,
+    /// Oldest entries are deleted beyond this.
+    pub max_entries: u32,
+    /// Record watch mode captures too, not only the ones taken by hand.
+    pub record_watch: bool,
+}
+
+/// Cards added from the lookup window through AnkiConnect.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnkiSettings {
+    /// AnkiConnect URL.
+    pub url: String,
+    /// Created when it does not exist.
+    pub deck: String,
+    pub note_type: String,
+    /// Note field -> template, e.g. `"Front": "{headword}"`; see
+    /// `anki::PLACEHOLDERS`.
+    pub fields: BTreeMap<String, String>,
+    /// Add cards for words the deck already has.
+    pub allow_duplicates: bool,
+    /// Note type of exported `.apkg` decks.
+    pub apkg: ApkgSettings,
+}
+
+/// Note type written into exported `.apkg` decks. Its fields are those of
+/// `AnkiSettings::fields`, filled the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApkgSettings {
+    /// Anki renames it on import if a different note type has this name.
+    pub note_type: String,
+    /// Card templates in Anki's syntax, e.g. `{{Front}}`.
+    pub front: String,
+    pub back: String,
+    pub css: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// OCR result overlay.
+    pub ocr_overlay: WindowSize,
+    /// Dictionary lookup window.
+    pub lookup: WindowSize,
+}
+
+/// Window size in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            hotkeys: HotkeySettings::default(),
+            dictionary: DictionarySettings::default(),
+            ocr: OcrSettings::default(),
+            overlay: OverlaySettings::default(),
+            history: HistorySettings::default(),
+            anki: AnkiSettings::default(),
+            windows: WindowSettings::default(),
+            captures_dir: None,
+        }
+    }
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            capture: "Ctrl+Shift+R".into(),
+            repeat_last_region: "Ctrl+Shift+L".into(),
+            toggle_watch: String::new(),
+            open_history: String::new(),
+            close_overlays: String::new(),
+            lookup_clipboard: String::new(),
+        }
+    }
+}
+
+impl Default for DictionarySettings {
+    fn default() -> Self {
+        Self {
+            backend: DictionaryBackend::Yomitan,
+            endpoint: DEFAULT_ENDPOINT.into(),
+        }
+    }
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            engine: "manga-ocr".into(),
+            device: OcrDevice::Cpu,
+            preprocess: PreprocessPreset::Default,
+            split_blocks: false,
+        }
+    }
+}
+
+impl Default for OverlaySettings {
+    fn default() -> Self {
+        Self {
+            font_size: 18,
+            text_color: "#ff6df8".into(),
+            background_opacity: 0.9,
+            highlight_words: true,
+        }
+    }
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+            max_entries: 1000,
+            record_watch: false,
+        }
+    }
+}
+
+impl Default for AnkiSettings {
+    fn default() -> Self {
+        let fields = [
+            ("Front", "{headword}"),
+            (
+                "Back",
+                "{reading} {pitch}<br>{glosses}<br><br>{sentence}<br>{screenshot}",
+            ),
+        ];
+        Self {
+            url: anki::DEFAULT_URL.into(),
+            deck: "UMOD".into(),
+            note_type: "Basic".into(),
+            fields: fields
+                .into_iter()
+                .map(|(name, template)| (name.to_string(), template.to_string()))
+                .collect(),
+            allow_duplicates: false,
+            apkg: ApkgSettings::default(),
+        }
+    }
+}
+
+impl Default for ApkgSettings {
+    fn default() -> Self {
+        Self {
+            note_type: "UMOD".into(),
+            front: "{{Front}}".into(),
+            back: "{{FrontSide}}<hr id=answer>{{Back}}".into(),
+            css: ".card { font-family: sans-serif; font-size: 22px; text-align: center; }\n\
+                  img { max-width: 100%; }"
+                .into(),
+        }
+    }
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            ocr_overlay: WindowSize {
+                width: 594,
+                height: 121,
+            },
+            lookup: WindowSize {
+                width: 420,
+                height: 520,
+            },
+        }
+    }
+}
+
+/// A rejected setting. `field` is the dotted path of the value (e.g.
+/// `"hotkeys.capture"`), or empty for problems not tied to one field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub(crate) fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Settings {
+    /// Every problem with these settings; empty when they can be applied.
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if self.version != SETTINGS_VERSION {
+            errors.push(FieldError::new(
+                "version",
+                format!("expected version {SETTINGS_VERSION}, got {}", self.version),
+            ));
+        }
+
+        errors.extend(HotkeyRegistry::parse(&self.hotkeys).1);
+
+        if let Err(e) = check_endpoint(&self.dictionary.endpoint) {
+            errors.push(FieldError::new("dictionary.endpoint", e));
+        }
+
+        if super::ocr_engine(&self.ocr.engine).is_none() {
+            errors.push(FieldError::new(
+                "ocr.engine",
+                format!("unknown engine \"{}\"", self.ocr.engine),
+            ));
+        }
+
+        if !(MIN_FONT_SIZE..=MAX_FONT_SIZE).contains(&self.overlay.font_size) {
+            errors.push(FieldError::new(
+                "overlay.font_size",
+                format!("must be {MIN_FONT_SIZE}-{MAX_FONT_SIZE}"),
+            ));
+        }
+        if !is_hex_color(&self.overlay.text_color) {
+            errors.push(FieldError::new(
+                "overlay.text_color",
+                "must be a color like #ff6df8",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.overlay.background_opacity) {
+            errors.push(FieldError::new(
+                "overlay.background_opacity",
+                "must be between 0 and 1",
+            ));
+        }
+
+        if self.history.retention_days > MAX_RETENTION_DAYS {
+            errors.push(FieldError::new(
+                "history.retention_days",
+                format!("must be at most {MAX_RETENTION_DAYS} (0 keeps entries forever)"),
+            ));
+        }
+        if !(1..=MAX_HISTORY_ENTRIES).contains(&self.history.max_entries) {
+            errors.push(FieldError::new(
+                "history.max_entries",
+                format!("must be 1-{MAX_HISTORY_ENTRIES}"),
+            ));
+        }
+
+        errors.extend(self.anki.validate());
+
+        let windows = [
+            ("windows.ocr_overlay", self.windows.ocr_overlay),
+            ("windows.lookup", self.windows.lookup),
+        ];
+        for (field, size) in windows {
+            let range = MIN_WINDOW_SIDE..=MAX_WINDOW_SIDE;
+            if !range.contains(&size.width) || !range.contains(&size.height) {
+                errors.push(FieldError::new(
+                    field,
+                    format!("width and height must be {MIN_WINDOW_SIDE}-{MAX_WINDOW_SIDE}"),
+                ));
+            }
+        }
+
+        if let Some(dir) = &self.captures_dir
+            && !dir.is_absolute()
+        {
+            errors.push(FieldError::new(
+                "captures_dir",
+                "must be a full folder path",
+            ));
+        }
+
+        errors
+    }
+
+    /// Parse a settings document of any known version.
+    pub fn from_json(bytes: &[u8]) -> Result<Self> {
+        let mut value: Value = serde_json::from_slice(bytes)?;
+        let doc = value
+            .as_object_mut()
+            .context("settings must be a JSON object")?;
+        migrate(doc)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+impl AnkiSettings {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if let Err(e) = check_endpoint(&self.url) {
+            errors.push(FieldError::new("anki.url", e));
+        }
+        if self.deck.trim().is_empty() {
+            errors.push(FieldError::new("anki.deck", "must not be empty"));
+        }
+        if self.note_type.trim().is_empty() {
+            errors.push(FieldError::new("anki.note_type", "must not be empty"));
+        }
+
+        if self.fields.is_empty() {
+            errors.push(FieldError::new("anki.fields", "fill at least one field"));
+        }
+        for (name, template) in &self.fields {
+            if name.trim().is_empty() {
+                errors.push(FieldError::new(
+                    "anki.fields",
+                    "field names must not be empty",
+                ));
+            }
+            if let Err(e) = anki::check_template(template) {
+                errors.push(FieldError::new("anki.fields", format!("{name}: {e}")));
+            }
+        }
+
+        if self.apkg.note_type.trim().is_empty() {
+            errors.push(FieldError::new("anki.apkg.note_type", "must not be empty"));
+        }
+        if let Err(e) = anki::check_card_template(&self.apkg.front, &self.fields) {
+            errors.push(FieldError::new("anki.apkg.front", e));
+        } else if anki::card_fields(&self.apkg.front).next().is_none() {
+            errors.push(FieldError::new(
+                "anki.apkg.front",
+                "show at least one field",
+            ));
+        }
+        if let Err(e) = anki::check_card_template(&self.apkg.back, &self.fields) {
+            errors.push(FieldError::new("anki.apkg.back", e));
+        }
+
+        errors
+    }
+}
+
+fn check_endpoint(endpoint: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(endpoint).map_err(|e| format!("invalid URL: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("must be an http:// or https:// URL".into());
+    }
+    if url.host_str().is_none() {
+        return Err("URL has no host".into());
+    }
+    Ok(())
+}
+
+fn is_hex_color(s: &str) -> bool {
+    s.len() == 7 && s.starts_with('#') && s[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/* ---------------- Migrations ---------------- */
+
+/// Upgrade `doc` in place to `SETTINGS_VERSION`.
+fn migrate(doc: &mut Map<String, Value>) -> Result<()> {
+    let version = match doc.get("version") {
+        None => 0,
+        Some(v) => v.as_u64().context("version must be a number")? as u32,
+    };
+    if version > SETTINGS_VERSION {
+        bail!("settings version {version} is newer than this build ({SETTINGS_VERSION})");
+    }
+
+    for (from, step) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        step(doc);
+        doc.insert("version".into(), Value::from(from as u32 + 1));
+    }
+    Ok(())
+}
+
+/// Files written by hand before versioning: same layout, no version field.
+fn v0_to_v1(_doc: &mut Map<String, Value>) {}
+
+/// Captures were saved to a fixed folder toggled in the tray, off at every
+/// start; they now go to `captures_dir`, off until one is set.
+fn v1_to_v2(doc: &mut Map<String, Value>) {
+    doc.entry("captures_dir").or_insert(Value::Null);
+}
+
+/* ---------------- Store ---------------- */
+
+pub struct SettingsStore {
+    /// `None` keeps the settings in memory only.
+    path: Option<PathBuf>,
+    settings: Mutex<Settings>,
+    /// Modification time of the file as last read or written.
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl SettingsStore {
+    /// `<platform config dir>/umod/settings.json`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("umod").join("settings.json"))
+    }
+
+    /// Settings at the default path, or in memory when there is no config dir.
+    pub fn open_default() -> Self {
+        match Self::default_path() {
+            Some(path) => Self::open(path),
+            None => Self::in_memory(),
+        }
+    }
+
+    /// Load the settings stored at `path`. A missing file starts with the
+    /// defaults; an unreadable or invalid one is left untouched and the
+    /// defaults are used instead.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let settings = match read_settings(&path) {
+            Ok(Some(settings)) => settings,
+            Ok(None) => Settings::default(),
+            Err(e) => {
+                eprintln!(
+                    "Settings at {} ignored, using defaults: {e:#}",
+                    path.display()
+                );
+                Settings::default()
+            }
+        };
+
+        Self {
+            modified: Mutex::new(modified_time(&path)),
+            path: Some(path),
+            settings: Mutex::new(settings),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            settings: Mutex::new(Settings::default()),
+            modified: Mutex::new(None),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Validate and persist `settings`. Returns the settings they replace.
+    pub fn update(&self, settings: Settings) -> Result<Settings, Vec<FieldError>> {
+        let errors = settings.validate();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut current = self.settings.lock().unwrap();
+        if let Some(path) = &self.path {
+            write_json(path, &settings).map_err(|e| {
+                vec![FieldError::new(
+                    "",
+                    format!("Could not save settings: {e:#}"),
+                )]
+            })?;
+            *self.modified.lock().unwrap() = modified_time(path);
+        }
+
+        Ok(std::mem::replace(&mut *current, settings))
+    }
+
+    /// Pick up edits made to the file by hand. Returns `(old, new)` when the
+    /// file changed and holds valid settings.
+    pub fn reload_if_changed(&self) -> Result<Option<(Settings, Settings)>> {
+        let Some(path) = &self.path else {
+            return Ok(None);
+        };
+
+        let modified = modified_time(path);
+        {
+            let mut last = self.modified.lock().unwrap();
+            if modified.is_none() || modified == *last {
+                return Ok(None);
+            }
+            *last = modified;
+        }
+
+        let Some(settings) = read_settings(path)? else {
+            return Ok(None);
+        };
+
+        let mut current = self.settings.lock().unwrap();
+        if *current == settings {
+            return Ok(None);
+        }
+        let old = std::mem::replace(&mut *current, settings.clone());
+        Ok(Some((old, settings)))
+    }
+}
+
+/// Read, migrate and validate the file at `path`. `Ok(None)` when it does
+/// not exist. Files from older versions are rewritten in the current format.
+fn read_settings(path: &Path) -> Result<Option<Settings>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let settings = Settings::from_json(&bytes)?;
+    let errors = settings.validate();
+    if let Some(first) = errors.first() {
+        bail!(
+            "{}: {} ({} problem(s))",
+            first.field,
+            first.message,
+            errors.len()
+        );
+    }
+
+    let stored_version = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|v| v.get("version").and_then(Value::as_u64));
+    if stored_version != Some(SETTINGS_VERSION as u64) {
+        match write_json(path, &settings) {
+            Ok(()) => println!("Migrated settings to version {SETTINGS_VERSION}"),
+            Err(e) => eprintln!("Failed to save migrated settings: {e:#}"),
+        }
+    }
+
+    Ok(Some(settings))
+}
+
+/// Written atomically, so the reloader never reads a half-written file.
+fn write_json(path: &Path, settings: &Settings) -> Result<()> {
+    write_atomic(path, &serde_json::to_vec_pretty(settings)?)
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        assert_eq!(Settings::default().validate(), Vec::new());
+    }
+
+    #[test]
+    fn unversioned_file_is_migrated() {
+        let settings = Settings::from_json(br#"{ "hotkeys": { "capture": "Ctrl+Alt+D" } }"#)
+            .expect("parse failed");
+
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.hotkeys.capture, "Ctrl+Alt+D");
+        // missing fields keep their defaults
+        assert_eq!(settings.hotkeys.repeat_last_region, "Ctrl+Shift+L");
+        assert_eq!(settings.dictionary.endpoint, DEFAULT_ENDPOINT);
+    }
+
+    #[test]
+    fn captures_dir_is_migrated_and_checked() {
+        let settings = Settings::from_json(br#"{ "version": 1 }"#).expect("parse failed");
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.captures_dir, None);
+
+        let mut settings = Settings {
+            captures_dir: Some(std::env::temp_dir().join("UMOD Captures")),
+            ..Default::default()
+        };
+        assert_eq!(settings.validate(), Vec::new());
+
+        settings.captures_dir = Some("captures".into());
+        let errors = settings.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "captures_dir");
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let json = format!(r#"{{ "version": {} }}"#, SETTINGS_VERSION + 1);
+        assert!(Settings::from_json(json.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn anki_templates_are_checked() {
+        let mut settings = Settings::default();
+        settings.anki.deck = " ".into();
+        settings
+            .anki
+            .fields
+            .insert("Back".into(), "{headword} {meaning}".into());
+
+        let errors = settings.validate();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["anki.deck", "anki.fields"]);
+        assert!(
+            errors[1]
+                .message
+                .starts_with("Back: unknown placeholder {meaning}")
+        );
+
+        let mut settings = Settings::default();
+        settings.anki.apkg.front = "{{FrontSide}}".into();
+        settings.anki.apkg.back = "{{#Sentence}}{{Sentence}}{{/Sentence}}".into();
+        let errors = settings.validate();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["anki.apkg.front", "anki.apkg.back"]);
+        assert!(errors[1].message.contains("{{Sentence}}"));
+    }
+
+    #[test]
+    fn invalid_fields_are_reported() {
+        let mut settings = Settings::default();
+        settings.hotkeys.capture = "Ctrl+Nope".into();
+        settings.hotkeys.repeat_last_region = "Ctrl+Shift+R".into();
+        settings.dictionary.endpoint = "ftp://localhost".into();
+        settings.ocr.engine = "tesseract".into();
+        settings.overlay.text_color = "pink".into();
+        settings.overlay.background_opacity = 1.5;
+        settings.history.max_entries = 0;
+        settings.windows.lookup.width = 10;
+
+        let fields: Vec<String> = settings.validate().into_iter().map(|e| e.field).collect();
+        assert_eq!(
+            fields,
+            [
+                "hotkeys.capture",
+                "dictionary.endpoint",
+                "ocr.engine",
+                "overlay.text_color",
+                "overlay.background_opacity",
+                "history.max_entries",
+                "windows.lookup"
+            ]
+        );
+
+        let mut duplicate = Settings::default();
+        duplicate.hotkeys.repeat_last_region = duplicate.hotkeys.capture.clone();
+        let errors = duplicate.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "hotkeys.repeat_last_region");
+    }
+
+    #[test]
+    fn update_persists_and_reload_sees_edits() {
+        let dir = std::env::temp_dir().join(format!("umod-settings-{}", std::process::id()));
+        let path = dir.join("settings.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let store = SettingsStore::open(&path);
+        let mut settings = store.get();
+        settings.ocr.device = OcrDevice::Cuda;
+        store.update(settings.clone()).expect("update failed");
+        assert_eq!(SettingsStore::open(&path).get(), settings);
+        // written through a temporary file that is renamed into place
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        // nothing changed on disk since our own write
+        assert!(store.reload_if_changed().unwrap().is_none());
+
+        // a hand edit (mtime granularity can be coarse, so set it explicitly)
+        let mut edited = settings.clone();
+        edited.dictionary.endpoint = "http://localhost:8080".into();
+        write_json(&path, &edited).unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(later))
+            .unwrap();
+
+        let (old, new) = store.reload_if_changed().unwrap().expect("edit not seen");
+        assert_eq!(old, settings);
+        assert_eq!(new, edited);
+        assert_eq!(store.get(), edited);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
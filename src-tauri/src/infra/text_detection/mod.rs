@@ -0,0 +1,392 @@
+// src-tauri/src/infra/text_detection/mod.rs
+//
+// Splits a captured image into separate text blocks (speech bubbles, dialogue
+// lines, captions) so each one can be OCR'd on its own. Manga OCR expects a
+// single block per image and degrades badly when given a whole page.
+//
+// Approach: Otsu binarization -> dilation to fuse glyphs into blobs ->
+// connected components -> merge overlapping boxes -> reading order.
+
+use screenshots::image::imageops;
+use screenshots::image::{GrayImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::shared::models::ocr::Orientation;
+
+/// Components smaller than this (in either dimension) are treated as noise.
+const MIN_BLOCK_SIDE: u32 = 8;
+
+/// A detected text block, in pixel coordinates of the image it was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextBlock {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+    pub orientation: Orientation,
+}
+
+impl TextBlock {
+    fn right(&self) -> u32 {
+        self.x + self.w
+    }
+
+    fn bottom(&self) -> u32 {
+        self.y + self.h
+    }
+
+    fn overlaps(&self, other: &TextBlock) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    fn union(&self, other: &TextBlock) -> TextBlock {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let w = self.right().max(other.right()) - x;
+        let h = self.bottom().max(other.bottom()) - y;
+        TextBlock::new(x, y, w, h)
+    }
+
+    fn new(x: u32, y: u32, w: u32, h: u32) -> Self {
+        // Japanese vertical text produces tall, narrow columns
+        let orientation = if h > w {
+            Orientation::Vertical
+        } else {
+            Orientation::Horizontal
+        };
+        Self {
+            x,
+            y,
+            w,
+            h,
+            orientation,
+        }
+    }
+
+    /// A single block covering all of `img`.
+    pub fn whole(img: &RgbaImage) -> Self {
+        TextBlock::new(0, 0, img.width(), img.height())
+    }
+
+    /// Crop this block out of `img`.
+    pub fn crop(&self, img: &RgbaImage) -> RgbaImage {
+        imageops::crop_imm(img, self.x, self.y, self.w, self.h).to_image()
+    }
+}
+
+/// Detect text blocks in `img`, returned in reading order.
+///
+/// Always returns at least one block; if nothing usable is found the whole
+/// image is returned as a single block.
+pub fn detect_text_blocks(img: &RgbaImage) -> Vec<TextBlock> {
+    let (w, h) = img.dimensions();
+    let whole = vec![TextBlock::whole(img)];
+    if w < MIN_BLOCK_SIDE || h < MIN_BLOCK_SIDE {
+        return whole;
+    }
+
+    let gray = imageops::grayscale(img);
+    let mask = text_mask(&gray);
+
+    // Gap to bridge between glyphs of the same block; scales with capture size
+    let radius = (w.min(h) / 40).max(3) as usize;
+    let fused = dilate(&mask, w as usize, h as usize, radius);
+
+    let mut blocks: Vec<TextBlock> = connected_components(&fused, w as usize, h as usize)
+        .into_iter()
+        .map(|b| shrink(b, radius as u32, w, h))
+        .filter(|b| b.w >= MIN_BLOCK_SIDE && b.h >= MIN_BLOCK_SIDE)
+        .collect();
+
+    merge_overlapping(&mut blocks);
+
+    if blocks.is_empty() {
+        return whole;
+    }
+
+    sort_reading_order(&mut blocks);
+    blocks
+}
+
+/// Order blocks the way a reader would: right-to-left columns for vertical
+/// text, top-to-bottom rows for horizontal text.
+pub fn sort_reading_order(blocks: &mut [TextBlock]) {
+    let vertical = blocks
+        .iter()
+        .filter(|b| b.orientation == Orientation::Vertical)
+        .count();
+
+    if vertical * 2 > blocks.len() {
+        // rightmost column first, top to bottom inside a column
+        blocks.sort_by_key(|b| std::cmp::Reverse(b.right()));
+        for band in bands(blocks, |b| (b.x, b.right())) {
+            band.sort_by_key(|b| b.y);
+        }
+    } else {
+        // topmost row first, left to right inside a row
+        blocks.sort_by_key(|b| b.y);
+        for band in bands(blocks, |b| (b.y, b.bottom())) {
+            band.sort_by_key(|b| b.x);
+        }
+    }
+}
+
+/// Positions of `rects` (x, y, w, h) in reading order, as `sort_reading_order`
+/// would order blocks with those bounds.
+pub fn reading_order(rects: &[(u32, u32, u32, u32)]) -> Vec<usize> {
+    let mut blocks: Vec<TextBlock> = rects
+        .iter()
+        .map(|&(x, y, w, h)| TextBlock::new(x, y, w, h))
+        .collect();
+    sort_reading_order(&mut blocks);
+
+    let mut taken = vec![false; rects.len()];
+    blocks
+        .iter()
+        .filter_map(|b| {
+            let i = (0..rects.len()).find(|&i| !taken[i] && rects[i] == (b.x, b.y, b.w, b.h))?;
+            taken[i] = true;
+            Some(i)
+        })
+        .collect()
+}
+
+/// Split already-sorted blocks into runs whose `span` ranges overlap.
+fn bands(
+    blocks: &mut [TextBlock],
+    span: impl Fn(&TextBlock) -> (u32, u32),
+) -> Vec<&mut [TextBlock]> {
+    let mut out = Vec::new();
+    let mut rest = blocks;
+
+    while !rest.is_empty() {
+        let (mut lo, mut hi) = span(&rest[0]);
+        let mut len = 1;
+        while len < rest.len() {
+            let (a, b) = span(&rest[len]);
+            if a >= hi || b <= lo {
+                break;
+            }
+            lo = lo.min(a);
+            hi = hi.max(b);
+            len += 1;
+        }
+
+        let (band, tail) = rest.split_at_mut(len);
+        out.push(band);
+        rest = tail;
+    }
+
+    out
+}
+
+/* ---------------- Helpers ---------------- */
+
+/// Otsu threshold; text is assumed to be whichever side covers fewer pixels.
+fn text_mask(gray: &GrayImage) -> Vec<bool> {
+    let mut hist = [0u64; 256];
+    for p in gray.iter() {
+        hist[*p as usize] += 1;
+    }
+
+    let total = gray.len() as u64;
+    let sum_all: u64 = hist.iter().enumerate().map(|(i, &c)| i as u64 * c).sum();
+
+    let (mut best_t, mut best_var) = (0usize, 0f64);
+    let (mut w_bg, mut sum_bg) = (0u64, 0u64);
+    for (t, &count) in hist.iter().enumerate() {
+        w_bg += count;
+        if w_bg == 0 {
+            continue;
+        }
+        let w_fg = total - w_bg;
+        if w_fg == 0 {
+            break;
+        }
+        sum_bg += t as u64 * count;
+
+        let m_bg = sum_bg as f64 / w_bg as f64;
+        let m_fg = (sum_all - sum_bg) as f64 / w_fg as f64;
+        let var = w_bg as f64 * w_fg as f64 * (m_bg - m_fg).powi(2);
+        if var > best_var {
+            best_var = var;
+            best_t = t;
+        }
+    }
+
+    let dark: Vec<bool> = gray.iter().map(|&p| p as usize <= best_t).collect();
+    let dark_count = dark.iter().filter(|&&d| d).count();
+
+    if dark_count * 2 <= dark.len() {
+        dark
+    } else {
+        dark.into_iter().map(|d| !d).collect()
+    }
+}
+
+/// Square dilation, done as two separable sliding-window passes.
+fn dilate(mask: &[bool], w: usize, h: usize, radius: usize) -> Vec<bool> {
+    let pass = |src: &[bool], len: usize, lines: usize, at: &dyn Fn(usize, usize) -> usize| {
+        let mut out = vec![false; src.len()];
+        for line in 0..lines {
+            // number of set pixels inside the current window
+            let mut count = 0usize;
+            for i in 0..radius.min(len) {
+                count += src[at(line, i)] as usize;
+            }
+            for i in 0..len {
+                if i + radius < len {
+                    count += src[at(line, i + radius)] as usize;
+                }
+                if i > radius {
+                    count -= src[at(line, i - radius - 1)] as usize;
+                }
+                out[at(line, i)] = count > 0;
+            }
+        }
+        out
+    };
+
+    let horizontal = pass(mask, w, h, &|row, col| row * w + col);
+    pass(&horizontal, h, w, &|col, row| row * w + col)
+}
+
+fn connected_components(mask: &[bool], w: usize, h: usize) -> Vec<TextBlock> {
+    let mut seen = vec![false; mask.len()];
+    let mut blocks = Vec::new();
+    let mut stack = Vec::new();
+
+    for start in 0..mask.len() {
+        if !mask[start] || seen[start] {
+            continue;
+        }
+
+        let (mut x0, mut y0, mut x1, mut y1) = (w, h, 0, 0);
+        seen[start] = true;
+        stack.push(start);
+
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+
+            let mut visit = |j: usize| {
+                if mask[j] && !seen[j] {
+                    seen[j] = true;
+                    stack.push(j);
+                }
+            };
+            if x > 0 {
+                visit(i - 1);
+            }
+            if x + 1 < w {
+                visit(i + 1);
+            }
+            if y > 0 {
+                visit(i - w);
+            }
+            if y + 1 < h {
+                visit(i + w);
+            }
+        }
+
+        blocks.push(TextBlock::new(
+            x0 as u32,
+            y0 as u32,
+            (x1 - x0 + 1) as u32,
+            (y1 - y0 + 1) as u32,
+        ));
+    }
+
+    blocks
+}
+
+/// Undo the growth added by dilation, keeping a small margin around glyphs.
+fn shrink(b: TextBlock, radius: u32, img_w: u32, img_h: u32) -> TextBlock {
+    let inset = radius / 2;
+    let x = (b.x + inset).min(img_w - 1);
+    let y = (b.y + inset).min(img_h - 1);
+    let w = b.w.saturating_sub(2 * inset).max(1).min(img_w - x);
+    let h = b.h.saturating_sub(2 * inset).max(1).min(img_h - y);
+    TextBlock::new(x, y, w, h)
+}
+
+fn merge_overlapping(blocks: &mut Vec<TextBlock>) {
+    let mut merged = true;
+    while merged {
+        merged = false;
+        'outer: for i in 0..blocks.len() {
+            for j in (i + 1)..blocks.len() {
+                if blocks[i].overlaps(&blocks[j]) {
+                    blocks[i] = blocks[i].union(&blocks[j]);
+                    blocks.swap_remove(j);
+                    merged = true;
+                    break 'outer;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screenshots::image::Rgba;
+
+    /// White page with solid black rectangles standing in for text blocks.
+    fn page(w: u32, h: u32, rects: &[(u32, u32, u32, u32)]) -> RgbaImage {
+        let mut img = RgbaImage::from_pixel(w, h, Rgba([255, 255, 255, 255]));
+        for &(x, y, rw, rh) in rects {
+            for py in y..y + rh {
+                for px in x..x + rw {
+                    img.put_pixel(px, py, Rgba([0, 0, 0, 255]));
+                }
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn blank_image_is_one_block() {
+        let img = page(200, 100, &[]);
+        let blocks = detect_text_blocks(&img);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!((blocks[0].w, blocks[0].h), (200, 100));
+    }
+
+    #[test]
+    fn vertical_columns_read_right_to_left() {
+        // two tall columns far apart
+        let img = page(400, 400, &[(40, 40, 30, 300), (300, 60, 30, 300)]);
+        let blocks = detect_text_blocks(&img);
+
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks.iter().all(|b| b.orientation == Orientation::Vertical));
+        assert!(blocks[0].x > blocks[1].x, "rightmost column must come first");
+    }
+
+    #[test]
+    fn horizontal_lines_read_top_to_bottom() {
+        let img = page(400, 400, &[(40, 300, 300, 30), (60, 40, 300, 30)]);
+        let blocks = detect_text_blocks(&img);
+
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0].y < blocks[1].y, "topmost line must come first");
+    }
+
+    #[test]
+    fn reading_order_of_rects() {
+        // mostly tall: columns right to left
+        let columns = [(10, 10, 30, 200), (300, 10, 30, 200), (150, 20, 30, 150)];
+        assert_eq!(reading_order(&columns), [1, 2, 0]);
+
+        // mostly wide: rows top to bottom, left to right inside a row
+        let rows = [(10, 300, 200, 30), (250, 12, 100, 30), (10, 10, 200, 30)];
+        assert_eq!(reading_order(&rows), [2, 1, 0]);
+    }
+}
@@ -0,0 +1,694 @@
+// src-tauri/src/infra/words/mod.rs
+//
+// The user's word list: words saved from the lookup window with their
+// dictionary entries and the sentence they were found in, each with a
+// learning status and a review schedule. Kept in its own SQLite database in
+// the platform data dir, apart from the history, so it is never pruned.
+// Words known from elsewhere (an Anki deck, a word list) can be imported
+// with just their status, which colors them in the OCR overlay.
+
+use anyhow::{Context, Result};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::infra::dictionary::Segment;
+use crate::shared::models::dictionary::UmodTermEntries;
+
+mod scheduler;
+
+pub use scheduler::Grade;
+use scheduler::{DEFAULT_EASE, Schedule};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS words (
+        id            INTEGER PRIMARY KEY,
+        term          TEXT NOT NULL UNIQUE,
+        reading       TEXT,
+        sentence      TEXT,
+        entries       TEXT,
+        status        TEXT NOT NULL,
+        added_at      INTEGER NOT NULL,
+        due           INTEGER,
+        interval_days REAL NOT NULL DEFAULT 0,
+        ease          REAL NOT NULL DEFAULT 2.5,
+        reps          INTEGER NOT NULL DEFAULT 0,
+        lapses        INTEGER NOT NULL DEFAULT 0,
+        reviewed_at   INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS words_due ON words (due);
+";
+
+const ENTRY_COLUMNS: &str =
+    "id, term, reading, sentence, status, added_at, due, interval_days, ease, reps, lapses";
+
+/// Word lists are capped at this many entries unless asked otherwise.
+const DEFAULT_LIST_LIMIT: u32 = 500;
+
+pub struct WordStore {
+    conn: Mutex<Connection>,
+}
+
+/// In the order a word goes through, so a later status is further along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WordStatus {
+    /// Saved, never reviewed.
+    New,
+    Learning,
+    /// Remembered for weeks, or marked as known by the user.
+    Known,
+}
+
+impl WordStatus {
+    pub const ALL: [WordStatus; 3] = [WordStatus::New, WordStatus::Learning, WordStatus::Known];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            WordStatus::New => "new",
+            WordStatus::Learning => "learning",
+            WordStatus::Known => "known",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.id() == id)
+    }
+}
+
+impl ToSql for WordStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.id().into())
+    }
+}
+
+impl FromSql for WordStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let id = value.as_str()?;
+        Self::from_id(id).ok_or_else(|| FromSqlError::Other(format!("unknown status {id}").into()))
+    }
+}
+
+/// A word to save.
+pub struct NewWord {
+    pub term: String,
+    pub reading: Option<String>,
+    pub sentence: Option<String>,
+    /// The `UmodTermEntries` of its lookup as JSON.
+    pub entries_json: String,
+}
+
+/// A saved word, without its dictionary entries.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WordEntry {
+    pub id: i64,
+    pub term: String,
+    pub reading: Option<String>,
+    pub sentence: Option<String>,
+    pub status: WordStatus,
+    pub added_at: i64,
+    /// Next review (Unix seconds); `None` for words marked as known and
+    /// words imported as learning, which are reviewed elsewhere.
+    pub due: Option<i64>,
+    pub interval_days: f64,
+    pub reps: u32,
+    pub lapses: u32,
+    #[serde(skip)]
+    ease: f64,
+}
+
+impl WordEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            term: row.get("term")?,
+            reading: row.get("reading")?,
+            sentence: row.get("sentence")?,
+            status: row.get("status")?,
+            added_at: row.get("added_at")?,
+            due: row.get("due")?,
+            interval_days: row.get("interval_days")?,
+            ease: row.get("ease")?,
+            reps: row.get("reps")?,
+            lapses: row.get("lapses")?,
+        })
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule {
+            interval_days: self.interval_days,
+            ease: self.ease,
+            reps: self.reps,
+            lapses: self.lapses,
+        }
+    }
+}
+
+/// A word to review, with what the back of its card shows.
+#[derive(Debug, Serialize)]
+pub struct ReviewCard {
+    #[serde(flatten)]
+    pub word: WordEntry,
+    /// `None` if the stored entries can no longer be read.
+    pub entries: Option<UmodTermEntries>,
+    /// Seconds until the next review for each answer, in `Grade::ALL` order.
+    pub intervals: [i64; 4],
+}
+
+/// A word known from elsewhere, imported with just its status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedWord {
+    pub term: String,
+    pub status: WordStatus,
+}
+
+impl ImportedWord {
+    /// A word studied elsewhere (e.g. in Anki) whose cards are at an
+    /// interval of `interval_days`.
+    pub fn studied(term: impl Into<String>, interval_days: f64) -> Self {
+        let schedule = Schedule {
+            interval_days,
+            ..Schedule::default()
+        };
+        Self {
+            term: term.into(),
+            status: schedule.status(),
+        }
+    }
+}
+
+/// Outcome of an import: words new to the list, and words already in it
+/// that moved to a later status.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportCounts {
+    pub added: u32,
+    pub updated: u32,
+}
+
+/// A piece of segmented text with the status of its lemma in the list;
+/// `None` for words not in the list and for text that is no word.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WordToken {
+    #[serde(flatten)]
+    pub segment: Segment,
+    pub status: Option<WordStatus>,
+}
+
+/// How many words have each status, and how many are due now.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WordCounts {
+    pub new: u32,
+    pub learning: u32,
+    pub known: u32,
+    pub due: u32,
+}
+
+/// Word list search. `text` is found in the term or its reading.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WordFilter {
+    pub text: Option<String>,
+    pub status: Option<WordStatus>,
+    pub limit: Option<u32>,
+}
+
+impl WordStore {
+    /// `<platform data dir>/umod/words.db`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("umod").join("words.db"))
+    }
+
+    /// Open (or create) the database at `path`. If that fails the words are
+    /// kept in memory for this session.
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        match open_file(path) {
+            Ok(conn) => Self {
+                conn: Mutex::new(conn),
+            },
+            Err(e) => {
+                eprintln!(
+                    "Word list at {} not available, keeping it in memory: {e:#}",
+                    path.display()
+                );
+                Self::in_memory()
+            }
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        let conn = Connection::open_in_memory().expect("in-memory database");
+        conn.execute_batch(SCHEMA).expect("words table");
+        Self {
+            conn: Mutex::new(conn),
+        }
+    }
+
+    /// Save `word` as a new word, due for review right away. A word already
+    /// in the list gets the new entries but keeps its status and schedule.
+    /// Returns whether the word was new.
+    pub fn save_word(&self, word: &NewWord, now: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let exists = conn
+            .query_row("SELECT 1 FROM words WHERE term = ?1", [&word.term], |_| {
+                Ok(())
+            })
+            .optional()?
+            .is_some();
+
+        conn.execute(
+            "INSERT INTO words (term, reading, sentence, entries, status, added_at, due)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+             ON CONFLICT (term) DO UPDATE SET
+                reading = excluded.reading,
+                sentence = coalesce(excluded.sentence, sentence),
+                entries = excluded.entries",
+            params![
+                word.term,
+                word.reading,
+                word.sentence,
+                word.entries_json,
+                WordStatus::New,
+                now,
+            ],
+        )?;
+        Ok(!exists)
+    }
+
+    /// Matching words, most recently added first.
+    pub fn words(&self, filter: &WordFilter) -> Result<Vec<WordEntry>> {
+        let text = filter
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty());
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ENTRY_COLUMNS} FROM words
+             WHERE (?1 IS NULL OR instr(term, ?1) > 0 OR instr(reading, ?1) > 0)
+               AND (?2 IS NULL OR status = ?2)
+             ORDER BY added_at DESC, id DESC
+             LIMIT ?3"
+        ))?;
+        let words = stmt
+            .query_map(
+                params![
+                    text,
+                    filter.status,
+                    filter.limit.unwrap_or(DEFAULT_LIST_LIMIT)
+                ],
+                WordEntry::from_row,
+            )?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(words)
+    }
+
+    /// The word due for review the longest, if any is due at `now`.
+    pub fn next_review(&self, now: i64) -> Result<Option<ReviewCard>> {
+        let conn = self.conn.lock().unwrap();
+        let row = conn
+            .query_row(
+                &format!(
+                    "SELECT {ENTRY_COLUMNS}, entries FROM words
+                     WHERE due IS NOT NULL AND due <= ?1
+                     ORDER BY due, id LIMIT 1"
+                ),
+                [now],
+                |row| {
+                    Ok((
+                        WordEntry::from_row(row)?,
+                        row.get::<_, Option<String>>("entries")?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        Ok(row.map(|(word, entries)| {
+            let schedule = word.schedule();
+            ReviewCard {
+                entries: entries.and_then(|json| serde_json::from_str(&json).ok()),
+                intervals: Grade::ALL.map(|grade| schedule.answer(grade).interval_seconds()),
+                word,
+            }
+        }))
+    }
+
+    /// Record the answer to a review of word `id` and schedule its next one.
+    /// Returns the updated word, `None` if it is gone.
+    pub fn answer(&self, id: i64, grade: Grade, now: i64) -> Result<Option<WordEntry>> {
+        let Some(word) = self.word(id)? else {
+            return Ok(None);
+        };
+        let schedule = word.schedule().answer(grade);
+
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE words SET status = ?2, due = ?3, interval_days = ?4, ease = ?5,
+                reps = ?6, lapses = ?7, reviewed_at = ?8
+             WHERE id = ?1",
+            params![
+                id,
+                schedule.status(),
+                now + schedule.interval_seconds(),
+                schedule.interval_days,
+                schedule.ease,
+                schedule.reps,
+                schedule.lapses,
+                now,
+            ],
+        )?;
+        drop(conn);
+        self.word(id)
+    }
+
+    /// Change the status of word `id` by hand: "new" starts its reviews
+    /// over, "learning" makes sure it is reviewed and "known" stops its
+    /// reviews. Returns whether the word existed.
+    pub fn set_status(&self, id: i64, status: WordStatus, now: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(update_status(&conn, id, status, now)? > 0)
+    }
+
+    /// Add imported words to the list. Words already in it only change
+    /// status when the import puts them further along, so an import never
+    /// undoes progress made here. Only new words are scheduled for review.
+    pub fn import_words(&self, words: &[ImportedWord], now: i64) -> Result<ImportCounts> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut counts = ImportCounts::default();
+
+        for word in words {
+            let existing: Option<(i64, WordStatus)> = tx
+                .query_row(
+                    "SELECT id, status FROM words WHERE term = ?1",
+                    [&word.term],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+
+            match existing {
+                Some((id, status)) if status < word.status => {
+                    update_status(&tx, id, word.status, now)?;
+                    counts.updated += 1;
+                }
+                Some(_) => {}
+                None => {
+                    let due = (word.status == WordStatus::New).then_some(now);
+                    tx.execute(
+                        "INSERT INTO words (term, status, added_at, due) VALUES (?1, ?2, ?3, ?4)",
+                        params![word.term, word.status, now, due],
+                    )?;
+                    counts.added += 1;
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(counts)
+    }
+
+    /// `segments` with the status of their lemmas.
+    pub fn word_tokens(&self, segments: Vec<Segment>) -> Result<Vec<WordToken>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached("SELECT status FROM words WHERE term = ?1")?;
+        let mut statuses: HashMap<String, Option<WordStatus>> = HashMap::new();
+
+        let mut tokens = Vec::with_capacity(segments.len());
+        for segment in segments {
+            let status = match &segment.lemma {
+                Some(lemma) => match statuses.get(lemma) {
+                    Some(status) => *status,
+                    None => {
+                        let status = stmt.query_row([lemma], |row| row.get(0)).optional()?;
+                        statuses.insert(lemma.clone(), status);
+                        status
+                    }
+                },
+                None => None,
+            };
+            tokens.push(WordToken { segment, status });
+        }
+        Ok(tokens)
+    }
+
+    /// Returns whether word `id` existed.
+    pub fn delete_word(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM words WHERE id = ?1", [id])? > 0)
+    }
+
+    pub fn counts(&self, now: i64) -> Result<WordCounts> {
+        let conn = self.conn.lock().unwrap();
+        let mut counts = WordCounts::default();
+
+        let mut stmt = conn.prepare("SELECT status, count(*) FROM words GROUP BY status")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        for row in rows {
+            let (status, count): (WordStatus, u32) = row?;
+            match status {
+                WordStatus::New => counts.new = count,
+                WordStatus::Learning => counts.learning = count,
+                WordStatus::Known => counts.known = count,
+            }
+        }
+        counts.due = conn.query_row(
+            "SELECT count(*) FROM words WHERE due IS NOT NULL AND due <= ?1",
+            [now],
+            |row| row.get(0),
+        )?;
+
+        Ok(counts)
+    }
+
+    fn word(&self, id: i64) -> Result<Option<WordEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let word = conn
+            .query_row(
+                &format!("SELECT {ENTRY_COLUMNS} FROM words WHERE id = ?1"),
+                [id],
+                WordEntry::from_row,
+            )
+            .optional()?;
+        Ok(word)
+    }
+}
+
+/// Words of a word list file: one word per line, optionally followed by a
+/// tab and its status ("new", "learning" or "known"); words without one get
+/// `status`. Further columns, empty lines and lines starting with `#` are
+/// ignored, so plain text exports of Anki notes can be read as well.
+pub fn parse_word_list(text: &str, status: WordStatus) -> Vec<ImportedWord> {
+    let mut seen = HashSet::new();
+    text.trim_start_matches('\u{feff}')
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut columns = line.split('\t');
+            let term = columns.next()?.trim();
+            let status = columns
+                .next()
+                .and_then(|s| WordStatus::from_id(s.trim()))
+                .unwrap_or(status);
+            (!term.is_empty() && seen.insert(term)).then(|| ImportedWord {
+                term: term.to_string(),
+                status,
+            })
+        })
+        .collect()
+}
+
+/// Set the status of word `id` as `WordStore::set_status` describes.
+/// Returns how many rows changed.
+fn update_status(conn: &Connection, id: i64, status: WordStatus, now: i64) -> Result<usize> {
+    let changed = match status {
+        WordStatus::New => conn.execute(
+            "UPDATE words SET status = ?2, due = ?3, interval_days = 0, ease = ?4,
+                reps = 0, lapses = 0
+             WHERE id = ?1",
+            params![id, status, now, DEFAULT_EASE],
+        )?,
+        WordStatus::Learning => conn.execute(
+            "UPDATE words SET status = ?2, due = coalesce(due, ?3) WHERE id = ?1",
+            params![id, status, now],
+        )?,
+        WordStatus::Known => conn.execute(
+            "UPDATE words SET status = ?2, due = NULL WHERE id = ?1",
+            params![id, status],
+        )?,
+    };
+    Ok(changed)
+}
+
+fn open_file(path: &Path) -> Result<Connection> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let conn = Connection::open(path).context("opening database")?;
+    conn.execute_batch(SCHEMA)
+        .context("creating the words table")?;
+    Ok(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scheduler::SECONDS_PER_DAY;
+    use super::*;
+
+    const NOW: i64 = 1_000 * SECONDS_PER_DAY;
+
+    fn word(term: &str, sentence: Option<&str>) -> NewWord {
+        NewWord {
+            term: term.into(),
+            reading: None,
+            sentence: sentence.map(str::to_string),
+            entries_json: format!(
+                r#"{{"query":"{term}","original_text_length":{},"entries":[]}}"#,
+                term.chars().count()
+            ),
+        }
+    }
+
+    fn terms(words: &[WordEntry]) -> Vec<&str> {
+        words.iter().map(|w| w.term.as_str()).collect()
+    }
+
+    #[test]
+    fn saving_again_keeps_the_schedule() {
+        let store = WordStore::in_memory();
+        assert!(
+            store
+                .save_word(&word("食べる", Some("ご飯を食べる")), NOW)
+                .unwrap()
+        );
+        let id = store.words(&WordFilter::default()).unwrap()[0].id;
+        store.answer(id, Grade::Good, NOW).unwrap();
+
+        assert!(!store.save_word(&word("食べる", None), NOW + 5).unwrap());
+        let words = store.words(&WordFilter::default()).unwrap();
+        assert_eq!(terms(&words), ["食べる"]);
+        assert_eq!(words[0].status, WordStatus::Learning);
+        assert_eq!(words[0].reps, 1);
+        assert_eq!(words[0].sentence.as_deref(), Some("ご飯を食べる"));
+    }
+
+    #[test]
+    fn reviews_come_due_in_order() {
+        let store = WordStore::in_memory();
+        store.save_word(&word("分かる", None), NOW).unwrap();
+        store.save_word(&word("食べる", None), NOW + 1).unwrap();
+
+        let card = store.next_review(NOW + 1).unwrap().unwrap();
+        assert_eq!(card.word.term, "分かる");
+        assert_eq!(card.entries.unwrap().query, "分かる");
+        assert_eq!(card.intervals[2], SECONDS_PER_DAY);
+
+        let answered = store.answer(card.word.id, Grade::Good, NOW + 1).unwrap();
+        assert_eq!(answered.unwrap().due, Some(NOW + 1 + SECONDS_PER_DAY));
+        assert_eq!(
+            store.next_review(NOW + 1).unwrap().unwrap().word.term,
+            "食べる"
+        );
+
+        let counts = store.counts(NOW + 1).unwrap();
+        assert_eq!(
+            counts,
+            WordCounts {
+                new: 1,
+                learning: 1,
+                known: 0,
+                due: 1
+            }
+        );
+    }
+
+    #[test]
+    fn status_can_be_set_by_hand() {
+        let store = WordStore::in_memory();
+        store.save_word(&word("分かる", None), NOW).unwrap();
+        let id = store.words(&WordFilter::default()).unwrap()[0].id;
+
+        assert!(store.set_status(id, WordStatus::Known, NOW).unwrap());
+        assert!(store.next_review(NOW).unwrap().is_none());
+        let known = WordFilter {
+            status: Some(WordStatus::Known),
+            ..WordFilter::default()
+        };
+        assert_eq!(terms(&store.words(&known).unwrap()), ["分かる"]);
+
+        assert!(store.set_status(id, WordStatus::New, NOW + 9).unwrap());
+        let card = store.next_review(NOW + 9).unwrap().unwrap();
+        assert_eq!(card.word.status, WordStatus::New);
+        assert_eq!(card.word.due, Some(NOW + 9));
+
+        assert!(store.delete_word(id).unwrap());
+        assert!(!store.set_status(id, WordStatus::Known, NOW).unwrap());
+    }
+
+    #[test]
+    fn imports_never_undo_progress() {
+        let store = WordStore::in_memory();
+        store.save_word(&word("分かる", None), NOW).unwrap();
+        store.save_word(&word("食べる", None), NOW).unwrap();
+        let known = store.words(&WordFilter::default()).unwrap()[0].id;
+        store.set_status(known, WordStatus::Known, NOW).unwrap();
+
+        let list = "\u{feff}# from a list\n分かる\n食べる\tlearning\n\n見る\tknown\t...\n見る\n";
+        let imported = parse_word_list(list, WordStatus::Learning);
+        assert_eq!(imported.len(), 3);
+        assert_eq!(imported[2], ImportedWord::studied("見る", 30.0));
+
+        let counts = store.import_words(&imported, NOW + 5).unwrap();
+        assert_eq!(
+            counts,
+            ImportCounts {
+                added: 1,
+                updated: 1
+            }
+        );
+
+        let status = |term: &str| {
+            let filter = WordFilter {
+                text: Some(term.into()),
+                ..WordFilter::default()
+            };
+            store.words(&filter).unwrap()[0].status
+        };
+        assert_eq!(status("分かる"), WordStatus::Learning);
+        assert_eq!(status("食べる"), WordStatus::Known);
+        assert_eq!(status("見る"), WordStatus::Known);
+        // the word saved here is still reviewed, imported ones are not
+        assert_eq!(
+            store.next_review(NOW + 5).unwrap().unwrap().word.term,
+            "分かる"
+        );
+        assert_eq!(store.counts(NOW + 5).unwrap().due, 1);
+    }
+
+    #[test]
+    fn segments_get_the_status_of_their_lemma() {
+        let store = WordStore::in_memory();
+        store
+            .import_words(&[ImportedWord::studied("食べる", 1.0)], NOW)
+            .unwrap();
+        let segment = |text: &str, lemma: Option<&str>| Segment {
+            text: text.into(),
+            lemma: lemma.map(str::to_string),
+        };
+
+        let tokens = store
+            .word_tokens(vec![
+                segment("食べた", Some("食べる")),
+                segment("。", None),
+                segment("見る", Some("見る")),
+            ])
+            .unwrap();
+
+        let statuses: Vec<_> = tokens.iter().map(|t| t.status).collect();
+        assert_eq!(statuses, [Some(WordStatus::Learning), None, None]);
+        assert_eq!(tokens[0].segment.text, "食べた");
+    }
+}
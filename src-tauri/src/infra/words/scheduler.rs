@@ -0,0 +1,177 @@
+// src-tauri/src/infra/words/scheduler.rs
+//
+// SM-2 spacing of word reviews, with Anki's four answer buttons: "Again"
+// shows the word again in a few minutes and lowers its ease, the others
+// grow the interval (1 day, 6 days, then by the ease) and adjust the ease by
+// how hard the answer was.
+
+use serde::{Deserialize, Serialize};
+
+use super::WordStatus;
+
+pub(super) const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Delay before a forgotten word comes back.
+const RELEARN_SECONDS: i64 = 10 * 60;
+
+pub(super) const DEFAULT_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+
+/// Interval multiplier of "Hard" in place of the ease.
+const HARD_FACTOR: f64 = 1.2;
+/// Extra multiplier of "Easy".
+const EASY_BONUS: f64 = 1.3;
+
+/// Words remembered for this long count as known.
+const KNOWN_AFTER_DAYS: f64 = 21.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 4] = [Grade::Again, Grade::Hard, Grade::Good, Grade::Easy];
+
+    /// Change of the ease, as Anki does it rather than SM-2's quality
+    /// formula, which drops the ease much faster.
+    fn ease_change(self) -> f64 {
+        match self {
+            Grade::Again => -0.2,
+            Grade::Hard => -0.15,
+            Grade::Good => 0.0,
+            Grade::Easy => 0.15,
+        }
+    }
+}
+
+/// Where a word stands in its reviews.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    /// Days until the next review after the last one.
+    pub interval_days: f64,
+    pub ease: f64,
+    /// Reviews remembered in a row.
+    pub reps: u32,
+    /// How often the word was forgotten after being remembered.
+    pub lapses: u32,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            interval_days: 0.0,
+            ease: DEFAULT_EASE,
+            reps: 0,
+            lapses: 0,
+        }
+    }
+}
+
+impl Schedule {
+    /// The schedule after answering `grade`.
+    pub fn answer(&self, grade: Grade) -> Self {
+        let ease = (self.ease + grade.ease_change()).max(MIN_EASE);
+
+        if grade == Grade::Again {
+            return Self {
+                interval_days: RELEARN_SECONDS as f64 / SECONDS_PER_DAY as f64,
+                ease,
+                reps: 0,
+                lapses: self.lapses + u32::from(self.reps > 0),
+            };
+        }
+
+        let reps = self.reps + 1;
+        let mut interval_days = match reps {
+            1 => 1.0,
+            2 => 6.0,
+            _ if grade == Grade::Hard => self.interval_days * HARD_FACTOR,
+            _ => self.interval_days * ease,
+        };
+        if grade == Grade::Easy {
+            interval_days *= EASY_BONUS;
+        }
+
+        Self {
+            interval_days,
+            ease,
+            reps,
+            lapses: self.lapses,
+        }
+    }
+
+    /// Seconds until the next review.
+    pub fn interval_seconds(&self) -> i64 {
+        (self.interval_days * SECONDS_PER_DAY as f64).round() as i64
+    }
+
+    /// Status of a word that has been reviewed with this schedule.
+    pub fn status(&self) -> WordStatus {
+        if self.interval_days >= KNOWN_AFTER_DAYS {
+            WordStatus::Known
+        } else {
+            WordStatus::Learning
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_grow_with_good_answers() {
+        let mut schedule = Schedule::default();
+        let mut days = Vec::new();
+        for _ in 0..4 {
+            schedule = schedule.answer(Grade::Good);
+            days.push(schedule.interval_days);
+        }
+
+        assert_eq!(days, [1.0, 6.0, 15.0, 37.5]);
+        assert_eq!(schedule.ease, DEFAULT_EASE);
+        assert_eq!(schedule.status(), WordStatus::Known);
+    }
+
+    #[test]
+    fn forgetting_starts_over_with_lower_ease() {
+        let learned = Schedule::default()
+            .answer(Grade::Good)
+            .answer(Grade::Good)
+            .answer(Grade::Good);
+
+        let forgotten = learned.answer(Grade::Again);
+        assert_eq!(forgotten.reps, 0);
+        assert_eq!(forgotten.lapses, 1);
+        assert_eq!(forgotten.interval_seconds(), RELEARN_SECONDS);
+        assert!(forgotten.ease < learned.ease);
+        assert_eq!(forgotten.status(), WordStatus::Learning);
+
+        // a new word failed right away is not a lapse
+        assert_eq!(Schedule::default().answer(Grade::Again).lapses, 0);
+        // ease never drops below the minimum
+        let mut hard = Schedule::default();
+        for _ in 0..20 {
+            hard = hard.answer(Grade::Again);
+        }
+        assert_eq!(hard.ease, MIN_EASE);
+    }
+
+    #[test]
+    fn hard_and_easy_scale_the_interval() {
+        let base = Schedule::default().answer(Grade::Good).answer(Grade::Good);
+
+        let hard = base.answer(Grade::Hard);
+        let good = base.answer(Grade::Good);
+        let easy = base.answer(Grade::Easy);
+        assert!(hard.interval_days < good.interval_days);
+        assert!(good.interval_days < easy.interval_days);
+        assert!(hard.ease < good.ease && good.ease < easy.ease);
+        assert_eq!(Schedule::default().answer(Grade::Easy).interval_days, 1.3);
+    }
+}
@@ -2,6 +2,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod cli; // headless `umod ocr` batch mode
 mod infra;
 mod run; // where your run() lives
 mod state; // your AppState
@@ -11,8 +12,15 @@ mod shared;
 
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("ocr") {
+        std::process::exit(cli::run_ocr_command(&args[2..]));
+    }
+
+    let settings = infra::settings::SettingsStore::open_default();
+
     println!("Initializing OCR, please wait...");
-    infra::init_ocr(true).expect("Failed to initialize Manga OCR");
+    infra::init_ocr(settings.get().ocr.device.force_cpu()).expect("Failed to initialize Manga OCR");
 
-    run::run().expect("error while running tauri application")
+    run::run(settings).expect("error while running tauri application")
 }
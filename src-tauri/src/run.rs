@@ -2,66 +2,105 @@
 
 use crate::app::AppMediator;
 use crate::app::region_selection::controller::*;
+use crate::infra::settings::SettingsStore;
 use crate::state::AppState;
 use crate::ui;
+use ui::history::*;
+use ui::lookup::*;
 use ui::reactive_overlay::*;
+use ui::review::*;
+use ui::settings::*;
+use ui::window_picker::*;
 use tauri::Manager;
 
-pub fn run() -> tauri::Result<()> {
+pub fn run(settings: SettingsStore) -> tauri::Result<()> {
     let app = tauri::Builder::default()
-        .manage(AppState::new())
+        .manage(AppState::new(settings))
         .invoke_handler(tauri::generate_handler![
             rs_cursor,
             rs_mousedown,
             rs_mouseup,
             rs_key_enter,
             rs_key_escape,
+            rs_key_arrow,
+            rs_toggle_aspect_lock,
+            rs_reorder,
+            rs_snapshot,
             rs_get_state,
             rs_set_window_size,
             rs_do_capture,
             rs_ready,
             lookup_selected_text,
+            ocr_word_tokens,
+            ocr_clipboard_image,
+            ocr_image_file,
+            wp_list_windows,
+            wp_select_window,
+            get_settings,
+            update_settings,
+            settings_choices,
+            pick_captures_dir,
+            list_profiles,
+            set_profile_hotkey,
+            hs_search_lookups,
+            hs_delete_lookup,
+            hs_open_lookup,
+            hs_export_apkg,
+            hs_search_captures,
+            hs_capture_thumbnail,
+            hs_set_capture_text,
+            hs_delete_capture,
+            hs_open_capture,
+            hs_rerun_capture,
+            lk_add_card,
+            lk_save_word,
+            rv_next_card,
+            rv_answer,
+            rv_counts,
+            rv_words,
+            rv_set_status,
+            rv_delete_word,
+            rv_import_word_list,
+            rv_import_anki,
         ]) // Interaction between Tauri and Rust
         .plugin(tauri_plugin_opener::init()) //shared state
         .plugin(tauri_plugin_clipboard_manager::init()) //clipboard
+        .plugin(tauri_plugin_dialog::init()) //file pickers
         .setup(|app| {
             //init services
             ui::tray::init_tray(app)?; // initialize tray from ui module
 
-            // Register global hotkey Ctrl+Shift+R to initialize
-            // region selection and reactive overlay
-            use tauri_plugin_global_shortcut::{
-                Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState,
-            };
-            let ctrl_shift_r =
-                Shortcut::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyR);
+            // Register the global hotkeys from the settings: region selection
+            // (Ctrl+Shift+R by default), capturing the last region again
+            // (Ctrl+Shift+L) and the optional ones (watch mode, history,
+            // closing overlays, clipboard lookup)
+            use tauri_plugin_global_shortcut::ShortcutState;
 
             app.handle().plugin(
                 tauri_plugin_global_shortcut::Builder::new()
                     .with_handler(move |app, shortcut, event| {
-                        if shortcut == &ctrl_shift_r {
-                            match event.state() {
-                                ShortcutState::Pressed => {
-
-                                    // println!("Ctrl-Shift-R Pressed!");
-                                }
-                                ShortcutState::Released => {
-                                    // allow user to enter RS if they are not in it
-                                    if !app.state::<AppState>().is_selecting_region() {
-                                        ui::reactive_overlay::OCROverlayController::close_overlay(&app);
-                                        app.state::<AppState>().enter_selecting_region();
-                                        println!("Region Selection activated...");
-
-                                        AppMediator::start_region_capture(app);
-                                    }
-                                }
+                        if !matches!(event.state(), ShortcutState::Released) {
+                            return;
+                        }
+                        if let Some(action) = AppMediator::hotkey_action(app, shortcut) {
+                            AppMediator::run_hotkey_action(app, action);
+                        } else if let Some(name) =
+                            AppMediator::profile_for_shortcut(app, shortcut)
+                        {
+                            if let Err(e) = AppMediator::capture_profile(app, &name) {
+                                eprintln!("Profile capture failed: {e}");
                             }
                         }
                     })
                     .build(),
             )?;
 
-            app.global_shortcut().register(ctrl_shift_r)?;
+            let hotkeys = app.state::<AppState>().settings.get().hotkeys;
+            AppMediator::register_hotkeys(app.handle(), &hotkeys);
+            AppMediator::register_profile_hotkeys(app.handle());
+            AppMediator::watch_settings(app.handle());
+            AppMediator::flush_ocr_cache_periodically(app.handle());
+            AppMediator::prune_history(app.handle());
 
             Ok(())
         })
@@ -3,30 +3,33 @@ Contains the canonical data-object representation of the term entries formatted
 This is project focused code which is allowed to populate upwards. Changes to the dictionary adapter should maintain
 this format.
 */
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UmodTermEntries {
     pub query: String,
     pub original_text_length: usize,
     pub entries: Vec<UmodDictionaryEntry>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UmodDictionaryEntry {
     pub headwords: Vec<UmodHeadword>,
     pub definitions: Vec<UmodDefinition>,
     pub frequencies: Vec<UmodFrequency>,
+    /// Absent from lookups recorded before pitch accents were kept.
+    #[serde(default)]
+    pub pitch_accents: Vec<UmodPitchAccent>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UmodHeadword {
     pub term: String,
     pub reading: Option<String>,
     pub word_classes: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UmodDefinition {
     pub dictionary: String,
     pub priority: bool,
@@ -36,7 +39,7 @@ pub struct UmodDefinition {
     pub variants: Vec<String>,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UmodGrammar {
     pub transitivity: Option<String>,
     pub kana_only: bool,
@@ -51,15 +54,22 @@ impl UmodGrammar {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UmodSense {
     pub number: usize,
     pub glosses: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UmodFrequency {
     pub dictionary: String,
     pub display_value: Option<String>,
     pub frequency: Option<u64>,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UmodPitchAccent {
+    pub dictionary: String,
+    /// Mora after which the pitch drops; 0 is heiban (no drop).
+    pub position: u32,
+}
@@ -1 +1,2 @@
-pub mod dictionary;
\ No newline at end of file
+pub mod dictionary;
+pub mod ocr;
@@ -0,0 +1,110 @@
+/* shared/models/ocr.rs
+Canonical OCR output passed from the OCR pipeline up to the app layer and the overlays.
+Coordinates are physical screen pixels so downstream features can position text over the capture.
+*/
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+impl Orientation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Orientation::Horizontal => "horizontal",
+            Orientation::Vertical => "vertical",
+        }
+    }
+}
+
+/// Where OCR'd text was captured from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CaptureSource {
+    Region,
+    Window { title: String },
+    Profile { name: String },
+    Watch,
+    Clipboard,
+    File { path: String },
+}
+
+impl fmt::Display for CaptureSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureSource::Region => write!(f, "Region"),
+            CaptureSource::Window { title } => write!(f, "Window \"{title}\""),
+            CaptureSource::Profile { name } => write!(f, "Profile \"{name}\""),
+            CaptureSource::Watch => write!(f, "Watch mode"),
+            CaptureSource::Clipboard => write!(f, "Clipboard image"),
+            CaptureSource::File { path } => write!(f, "File {path}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ScreenRect {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrBlock {
+    pub text: String,
+    pub lines: Vec<String>,
+    pub bounds: ScreenRect,
+    pub orientation: Orientation,
+    pub confidence: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrResult {
+    /// All block texts joined in reading order.
+    pub text: String,
+    pub blocks: Vec<OcrBlock>,
+    pub orientation: Orientation,
+    pub engine: String,
+    /// Lowest block confidence, if the engine reports one.
+    pub confidence: Option<f32>,
+    pub elapsed_ms: u64,
+}
+
+/// An OCR result as the overlay shows it, with the history id of its
+/// capture so lookups from the overlay can refer to it. Fresh captures are
+/// recorded after the overlay opens; their id is found through `key` then.
+#[derive(Debug, Clone, Serialize)]
+pub struct OverlayResult {
+    #[serde(flatten)]
+    pub result: OcrResult,
+    pub capture_id: Option<i64>,
+    pub key: usize,
+    /// Whether the overlay should warn that the text is likely wrong.
+    pub low_confidence: bool,
+}
+
+impl OverlayResult {
+    pub fn new(result: OcrResult, capture_id: Option<i64>, key: usize) -> Self {
+        Self {
+            low_confidence: result.is_low_confidence(),
+            result,
+            capture_id,
+            key,
+        }
+    }
+}
+
+impl OcrResult {
+    /// Below this the overlay warns that the text is likely wrong. Engines
+    /// that report no confidence never trigger the warning.
+    pub const LOW_CONFIDENCE: f32 = 0.6;
+
+    pub fn is_low_confidence(&self) -> bool {
+        self.confidence.is_some_and(|c| c < Self::LOW_CONFIDENCE)
+    }
+}
@@ -1,20 +1,112 @@
 // src-tauri/src/state.rs
+use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::sync::{Mutex, atomic::{AtomicBool, AtomicUsize, Ordering}};
 use tauri::LogicalPosition;
 
+use crate::infra::anki::NoteContent;
+use crate::infra::change_detection::WatchOptions;
+use crate::infra::dictionary::LemmaCache;
+use crate::infra::history::HistoryStore;
+use crate::infra::hotkeys::HotkeyRegistry;
+use crate::infra::ocr::OcrOptions;
+use crate::infra::ocr::cache::{self, OcrCache};
+use crate::infra::preprocess::{PreprocessPipeline, PreprocessPreset};
+use crate::infra::profiles::ProfileStore;
+use crate::infra::screenshot::window::WindowTarget;
+use crate::infra::settings::SettingsStore;
+use crate::infra::words::{NewWord, WordStore};
+use crate::shared::models::ocr::ScreenRect;
+
+/// How many recorded captures `AppState::recorded_capture` remembers.
+const RECORDED_CAPTURES: usize = 64;
+
 pub struct AppState {
     next_id: AtomicUsize,
     is_selecting_region: AtomicBool,
+    split_text_blocks: AtomicBool,
+    /// Select regions on a still snapshot of the screen instead of the live one.
+    freeze_screen: AtomicBool,
+    watching: AtomicBool,
+    /// Bumped on every watch start so a previous watch thread notices it is stale.
+    watch_generation: AtomicUsize,
+    result_keys: AtomicUsize,
+    /// Result key -> history id of the captures recorded most recently.
+    recorded_captures: Mutex<VecDeque<(usize, i64)>>,
     pub current_lookup: Mutex<Option<String>>,
     pub last_lookup_window_pos: Mutex<Option<LogicalPosition<f64>>>,
+    pub preprocess: Mutex<PreprocessPipeline>,
+    pub ocr_cache: OcrCache,
+    /// Where captures are saved, from the settings; `None` keeps them in
+    /// memory only.
+    pub captures_dir: Mutex<Option<PathBuf>>,
+    /// Window captured by "Recapture Window".
+    pub window_target: Mutex<Option<WindowTarget>>,
+    /// Window whose sub-area is being chosen with the region selection overlay.
+    pub pending_window_area: Mutex<Option<WindowTarget>>,
+    pub profiles: ProfileStore,
+    /// Regions of the last confirmed selection, in capture order and
+    /// physical desktop pixels.
+    last_regions: Mutex<Vec<ScreenRect>>,
+    /// Profile of the last profile capture; cleared by a plain region capture.
+    pub last_profile: Mutex<Option<String>>,
+    pub watch_options: Mutex<WatchOptions>,
+    pub settings: SettingsStore,
+    /// Hotkeys currently registered from the settings.
+    pub hotkeys: Mutex<HotkeyRegistry>,
+    pub history: HistoryStore,
+    /// Card content of the lookup in the lookup window.
+    pub lookup_note: Mutex<Option<NoteContent>>,
+    /// Word list entry of the lookup in the lookup window.
+    pub lookup_word: Mutex<Option<NewWord>>,
+    pub words: WordStore,
+    /// Lemmas of words segmented for the OCR overlay.
+    pub lemmas: LemmaCache,
 }
 impl AppState {
-    pub fn new() -> Self {
+    pub fn new(settings: SettingsStore) -> Self {
+        let initial = settings.get();
+        let ocr = initial.ocr;
+
         Self {
             next_id: AtomicUsize::new(0),
             is_selecting_region: AtomicBool::new(false),
+            split_text_blocks: AtomicBool::new(ocr.split_blocks),
+            freeze_screen: AtomicBool::new(false),
+            watching: AtomicBool::new(false),
+            watch_generation: AtomicUsize::new(0),
+            result_keys: AtomicUsize::new(0),
+            recorded_captures: Mutex::new(VecDeque::new()),
             current_lookup: Mutex::new(None),
             last_lookup_window_pos: Mutex::new(None),
+            preprocess: Mutex::new(PreprocessPipeline::from_preset(ocr.preprocess)),
+            ocr_cache: match OcrCache::default_path() {
+                Some(path) => OcrCache::open(path, cache::DEFAULT_TOLERANCE),
+                None => OcrCache::in_memory(cache::DEFAULT_TOLERANCE),
+            },
+            captures_dir: Mutex::new(initial.captures_dir),
+            window_target: Mutex::new(None),
+            pending_window_area: Mutex::new(None),
+            profiles: match ProfileStore::default_path() {
+                Some(path) => ProfileStore::open(path),
+                None => ProfileStore::in_memory(),
+            },
+            last_regions: Mutex::new(load_last_regions()),
+            last_profile: Mutex::new(None),
+            watch_options: Mutex::new(WatchOptions::default()),
+            settings,
+            hotkeys: Mutex::new(HotkeyRegistry::default()),
+            history: match HistoryStore::default_path() {
+                Some(path) => HistoryStore::open(path),
+                None => HistoryStore::in_memory(),
+            },
+            lookup_note: Mutex::new(None),
+            lookup_word: Mutex::new(None),
+            words: match WordStore::default_path() {
+                Some(path) => WordStore::open(path),
+                None => WordStore::in_memory(),
+            },
+            lemmas: LemmaCache::default(),
         }
     }
     pub fn next_window_id(&self) -> usize {
@@ -32,4 +124,125 @@ impl AppState {
     pub fn exit_selecting_region(&self) {
         self.is_selecting_region.store(false, Ordering::Relaxed);
     }
+
+    pub fn split_text_blocks(&self) -> bool {
+        self.split_text_blocks.load(Ordering::Relaxed)
+    }
+
+    pub fn set_split_text_blocks(&self, enabled: bool) {
+        self.split_text_blocks.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn freeze_screen(&self) -> bool {
+        self.freeze_screen.load(Ordering::Relaxed)
+    }
+
+    pub fn set_freeze_screen(&self, enabled: bool) {
+        self.freeze_screen.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Switch to `preset`, keeping the debug dump directory.
+    pub fn set_preprocess_preset(&self, preset: PreprocessPreset) {
+        let mut pipeline = self.preprocess.lock().unwrap();
+        let debug_dir = pipeline.debug_dir.take();
+        *pipeline = PreprocessPipeline {
+            debug_dir,
+            ..PreprocessPipeline::from_preset(preset)
+        };
+    }
+
+    /// Snapshot of the current OCR settings for one capture.
+    pub fn ocr_options(&self) -> OcrOptions {
+        OcrOptions {
+            preprocess: self.preprocess.lock().unwrap().clone(),
+            split_blocks: self.split_text_blocks(),
+        }
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watching.load(Ordering::Relaxed)
+    }
+
+    /// Start a new watch session and return its generation.
+    pub fn begin_watch(&self) -> usize {
+        self.watching.store(true, Ordering::Relaxed);
+        self.watch_generation.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub fn end_watch(&self) {
+        self.watching.store(false, Ordering::Relaxed);
+    }
+
+    /// True while the watch session `generation` should keep running.
+    pub fn watch_active(&self, generation: usize) -> bool {
+        self.is_watching() && self.watch_generation.load(Ordering::Relaxed) == generation
+    }
+
+    /// Key identifying one shown OCR result until its capture is recorded.
+    pub fn next_result_key(&self) -> usize {
+        self.result_keys.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Remember that the result `key` was recorded as history capture `id`.
+    pub fn set_recorded_capture(&self, key: usize, id: i64) {
+        let mut recorded = self.recorded_captures.lock().unwrap();
+        if recorded.len() == RECORDED_CAPTURES {
+            recorded.pop_front();
+        }
+        recorded.push_back((key, id));
+    }
+
+    /// History id of the result `key`, unless it is not recorded (yet) or
+    /// was shown too long ago.
+    pub fn recorded_capture(&self, key: usize) -> Option<i64> {
+        let recorded = self.recorded_captures.lock().unwrap();
+        recorded.iter().find(|(k, _)| *k == key).map(|(_, id)| *id)
+    }
+
+    /// Every region of the last selection; empty before the first one.
+    pub fn last_regions(&self) -> Vec<ScreenRect> {
+        self.last_regions.lock().unwrap().clone()
+    }
+
+    /// The last selection, if it was a single region.
+    pub fn last_region(&self) -> Result<ScreenRect, String> {
+        match self.last_regions().as_slice() {
+            [] => Err("No region has been captured yet".into()),
+            [rect] => Ok(*rect),
+            _ => Err("The last capture had several regions; capture a single one first".into()),
+        }
+    }
+
+    /// Remember `rects` for "Repeat Last Region", also across restarts.
+    pub fn set_last_regions(&self, rects: Vec<ScreenRect>) {
+        if let Err(e) = save_last_regions(&rects) {
+            eprintln!("Failed to save last region: {e}");
+        }
+        *self.last_regions.lock().unwrap() = rects;
+        self.last_profile.lock().unwrap().take();
+    }
+}
+
+/// `<platform data dir>/umod/last_region.json`
+fn last_region_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("umod").join("last_region.json"))
+}
+
+fn save_last_regions(rects: &[ScreenRect]) -> anyhow::Result<()> {
+    let path = last_region_path().ok_or_else(|| anyhow::anyhow!("no data directory"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_vec(rects)?)?;
+    Ok(())
+}
+
+/// Older versions saved a single region instead of a list.
+fn load_last_regions() -> Vec<ScreenRect> {
+    let Some(bytes) = last_region_path().and_then(|path| std::fs::read(path).ok()) else {
+        return Vec::new();
+    };
+    serde_json::from_slice(&bytes)
+        .or_else(|_| serde_json::from_slice(&bytes).map(|rect| vec![rect]))
+        .unwrap_or_default()
 }
@@ -0,0 +1,126 @@
+use crate::app::AppMediator;
+use crate::infra::history::{CaptureEntry, HistoryFilter, LookupEntry};
+use crate::infra::preprocess::PreprocessPreset;
+use crate::state::AppState;
+use tauri::ipc::Response;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+//
+// ----------------------------------------------------------------
+//   Tauri Commands Exposed to JS
+// ----------------------------------------------------------------
+//
+
+/// Recorded lookups matching `filter`, newest first.
+#[tauri::command]
+pub fn hs_search_lookups(
+    app: AppHandle,
+    filter: HistoryFilter,
+) -> Result<Vec<LookupEntry>, String> {
+    app.state::<AppState>()
+        .history
+        .search_lookups(&filter)
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[tauri::command]
+pub fn hs_delete_lookup(app: AppHandle, id: i64) -> Result<bool, String> {
+    app.state::<AppState>()
+        .history
+        .delete_lookup(id)
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// Show a recorded lookup in the lookup window again.
+// async: the lookup window must not be created on the main thread
+#[tauri::command]
+pub async fn hs_open_lookup(app: AppHandle, id: i64) -> Result<(), String> {
+    AppMediator::reopen_lookup(&app, id)
+}
+
+/// Export cards for the given lookups to an `.apkg` file the user picks.
+/// Returns how many notes were written, `None` if no file was picked.
+// async: the save dialog and the export block
+#[tauri::command]
+pub async fn hs_export_apkg(app: AppHandle, ids: Vec<i64>) -> Result<Option<usize>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let file = app
+            .dialog()
+            .file()
+            .add_filter("Anki deck package", &["apkg"])
+            .set_file_name("umod.apkg")
+            .blocking_save_file();
+        let Some(path) = file.and_then(|f| f.into_path().ok()) else {
+            return Ok(None);
+        };
+        AppMediator::export_apkg(&app, &ids, &path).map(Some)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Recorded captures matching `filter`, newest first.
+#[tauri::command]
+pub fn hs_search_captures(
+    app: AppHandle,
+    filter: HistoryFilter,
+) -> Result<Vec<CaptureEntry>, String> {
+    app.state::<AppState>()
+        .history
+        .search_captures(&filter)
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// PNG thumbnail of a capture; empty if the capture is gone.
+#[tauri::command]
+pub fn hs_capture_thumbnail(app: AppHandle, id: i64) -> Result<Response, String> {
+    let png = app
+        .state::<AppState>()
+        .history
+        .capture_thumbnail(id)
+        .map_err(|e| format!("{e:#}"))?;
+    Ok(Response::new(png.unwrap_or_default()))
+}
+
+/// Replace a capture's OCR text with the user's correction.
+#[tauri::command]
+pub fn hs_set_capture_text(app: AppHandle, id: i64, text: String) -> Result<bool, String> {
+    app.state::<AppState>()
+        .history
+        .set_capture_text(id, &text)
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[tauri::command]
+pub fn hs_delete_capture(app: AppHandle, id: i64) -> Result<bool, String> {
+    app.state::<AppState>()
+        .history
+        .delete_capture(id)
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// Show a recorded capture in the OCR overlay again.
+// async: the overlay must not be created on the main thread
+#[tauri::command]
+pub async fn hs_open_capture(app: AppHandle, id: i64) -> Result<(), String> {
+    AppMediator::reopen_capture(&app, id)
+}
+
+/// OCR a recorded capture again with other settings and show the new text.
+/// Returns the updated entry.
+// async: opens the overlay; OCR runs on a blocking thread
+#[tauri::command]
+pub async fn hs_rerun_capture(
+    app: AppHandle,
+    id: i64,
+    engine: String,
+    preset: PreprocessPreset,
+    split_blocks: bool,
+) -> Result<CaptureEntry, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        AppMediator::rerun_capture(&app, id, &engine, preset, split_blocks)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,25 @@
+use crate::app::AppMediator;
+use crate::infra::anki::AddOutcome;
+use tauri::AppHandle;
+
+//
+// ----------------------------------------------------------------
+//   Tauri Commands Exposed to JS
+// ----------------------------------------------------------------
+//
+
+/// Add a card for the lookup in the lookup window to Anki.
+// async: AnkiConnect is called on a blocking thread
+#[tauri::command]
+pub async fn lk_add_card(app: AppHandle) -> Result<AddOutcome, String> {
+    tauri::async_runtime::spawn_blocking(move || AppMediator::add_anki_card(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Save the word in the lookup window to the word list for review. Returns
+/// whether it was new to the list.
+#[tauri::command]
+pub fn lk_save_word(app: AppHandle) -> Result<bool, String> {
+    AppMediator::save_lookup_word(&app)
+}
@@ -1,2 +1,7 @@
+pub mod history;
+pub mod lookup;
 pub mod reactive_overlay;
+pub mod review;
+pub mod settings;
 pub mod tray;
+pub mod window_picker;
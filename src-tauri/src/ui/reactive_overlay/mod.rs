@@ -1,5 +1,5 @@
 use crate::app::AppMediator;
-use crate::state::AppState;
+use crate::infra::words::WordToken;
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Manager, State};
 #[derive(Default)]
@@ -70,37 +70,34 @@ pub fn ocr_get_text(ctrl: State<'_, OCROverlayController>) -> String {
 }
 
 #[tauri::command]
-pub fn lookup_selected_text(app: tauri::AppHandle, text: String) {
-    let state = app.state::<AppState>();
-
-    // Decide what to do (and update state) under lock,
-    // but do NOT close windows while holding the lock.
-    let should_close_existing = {
-        let mut current = state.current_lookup.lock().unwrap();
-
-        // Same lookup -> do nothing (or focus existing window)
-        if current.as_deref() == Some(text.as_str()) {
-            // Optional: just focus existing window
-            if let Some(win) = app.get_webview_window("dictionary-lookup") {
-                let _ = win.set_focus();
-                let _ = win.show();
-            }
-            return;
-        }
+/// `context` is the overlay text the selection was made in, `capture_id` the
+/// history id of the capture that text was read from and `result_key` the
+/// key of the shown result, for captures recorded after the overlay opened.
+pub fn lookup_selected_text(
+    app: tauri::AppHandle,
+    text: String,
+    context: Option<String>,
+    capture_id: Option<i64>,
+    result_key: Option<usize>,
+) {
+    AppMediator::lookup_overlay_text(&app, text, context.as_deref(), capture_id, result_key);
+}
 
-        // New lookup -> set current now (prevents duplicate lookups immediately)
-        *current = Some(text.clone());
-        true
-    };
+/// `text` cut into words with their status in the word list.
+// async: the dictionary backend is called on a blocking thread
+#[tauri::command]
+pub async fn ocr_word_tokens(app: AppHandle, text: String) -> Result<Vec<WordToken>, String> {
+    tauri::async_runtime::spawn_blocking(move || AppMediator::word_tokens(&app, &text))
+        .await
+        .map_err(|e| e.to_string())?
+}
 
-    if should_close_existing {
-        if let Some(win) = app.get_webview_window("dictionary-lookup") {
-            let _ = win.close();
-        }
-    }
+#[tauri::command]
+pub fn ocr_clipboard_image(app: AppHandle) -> Result<(), String> {
+    AppMediator::ocr_clipboard_image(&app)
+}
 
-    // fire-and-forget
-    std::thread::spawn(move || {
-        let _ = AppMediator::lookup_and_open(&app, &text);
-    });
+#[tauri::command]
+pub fn ocr_image_file(app: AppHandle, path: String) -> Result<(), String> {
+    AppMediator::ocr_image_file(&app, std::path::Path::new(&path))
 }
@@ -0,0 +1,103 @@
+use crate::app::AppMediator;
+use crate::infra::history;
+use crate::infra::words::{
+    Grade, ImportCounts, ReviewCard, WordCounts, WordEntry, WordFilter, WordStatus,
+};
+use crate::state::AppState;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+//
+// ----------------------------------------------------------------
+//   Tauri Commands Exposed to JS
+// ----------------------------------------------------------------
+//
+
+/// The next word to review, `None` when nothing is due.
+#[tauri::command]
+pub fn rv_next_card(app: AppHandle) -> Result<Option<ReviewCard>, String> {
+    app.state::<AppState>()
+        .words
+        .next_review(history::unix_now())
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// Answer the review of a word. Returns the word with its next review.
+#[tauri::command]
+pub fn rv_answer(app: AppHandle, id: i64, grade: Grade) -> Result<Option<WordEntry>, String> {
+    app.state::<AppState>()
+        .words
+        .answer(id, grade, history::unix_now())
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[tauri::command]
+pub fn rv_counts(app: AppHandle) -> Result<WordCounts, String> {
+    app.state::<AppState>()
+        .words
+        .counts(history::unix_now())
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// Saved words matching `filter`, most recently added first.
+#[tauri::command]
+pub fn rv_words(app: AppHandle, filter: WordFilter) -> Result<Vec<WordEntry>, String> {
+    app.state::<AppState>()
+        .words
+        .words(&filter)
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[tauri::command]
+pub fn rv_set_status(app: AppHandle, id: i64, status: WordStatus) -> Result<bool, String> {
+    app.state::<AppState>()
+        .words
+        .set_status(id, status, history::unix_now())
+        .map_err(|e| format!("{e:#}"))
+}
+
+#[tauri::command]
+pub fn rv_delete_word(app: AppHandle, id: i64) -> Result<bool, String> {
+    app.state::<AppState>()
+        .words
+        .delete_word(id)
+        .map_err(|e| format!("{e:#}"))
+}
+
+/// Import a word list file the user picks, with `status` for words the file
+/// gives none. `None` if no file was picked.
+// async: the file dialog blocks
+#[tauri::command]
+pub async fn rv_import_word_list(
+    app: AppHandle,
+    status: WordStatus,
+) -> Result<Option<ImportCounts>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let file = app
+            .dialog()
+            .file()
+            .add_filter("Word list", &["txt", "tsv"])
+            .blocking_pick_file();
+        let Some(path) = file.and_then(|f| f.into_path().ok()) else {
+            return Ok(None);
+        };
+        AppMediator::import_word_list(&app, &path, status).map(Some)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Import the words of the Anki cards matching `query`, read from `field`.
+// async: AnkiConnect is called on a blocking thread
+#[tauri::command]
+pub async fn rv_import_anki(
+    app: AppHandle,
+    query: String,
+    field: String,
+) -> Result<ImportCounts, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        AppMediator::import_anki_words(&app, &query, &field)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,91 @@
+use crate::app::AppMediator;
+use crate::infra::OCR_ENGINES;
+use crate::infra::preprocess::PreprocessPreset;
+use crate::infra::profiles::RegionProfile;
+use crate::infra::settings::{FieldError, Settings};
+use crate::state::AppState;
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+/// Options for the settings window's select boxes.
+#[derive(Serialize)]
+pub struct SettingsChoices {
+    ocr_engines: Vec<Choice>,
+    ocr_devices: Vec<Choice>,
+    preprocess_presets: Vec<Choice>,
+    dictionary_backends: Vec<Choice>,
+}
+
+#[derive(Serialize)]
+pub struct Choice {
+    id: &'static str,
+    label: &'static str,
+}
+
+impl Choice {
+    fn new(id: &'static str, label: &'static str) -> Self {
+        Self { id, label }
+    }
+}
+
+//
+// ----------------------------------------------------------------
+//   Tauri Commands Exposed to JS
+// ----------------------------------------------------------------
+//
+
+#[tauri::command]
+pub fn get_settings(app: AppHandle) -> Settings {
+    app.state::<AppState>().settings.get()
+}
+
+#[tauri::command]
+pub fn settings_choices() -> SettingsChoices {
+    SettingsChoices {
+        ocr_engines: OCR_ENGINES.iter().map(|e| Choice::new(e, e)).collect(),
+        ocr_devices: vec![Choice::new("cpu", "CPU"), Choice::new("cuda", "CUDA (GPU)")],
+        preprocess_presets: PreprocessPreset::ALL
+            .iter()
+            .map(|p| Choice::new(p.id(), p.label()))
+            .collect(),
+        dictionary_backends: vec![Choice::new("yomitan", "Yomitan API")],
+    }
+}
+
+/// Rejected settings come back as one error per offending field.
+#[tauri::command]
+pub fn update_settings(app: AppHandle, settings: Settings) -> Result<Settings, Vec<FieldError>> {
+    AppMediator::update_settings(&app, settings)
+}
+
+/// Folder picked for saving captures, `None` if the dialog was cancelled.
+/// Only filled into the form; it is saved with the other settings.
+// async: the folder dialog blocks
+#[tauri::command]
+pub async fn pick_captures_dir(app: AppHandle) -> Result<Option<PathBuf>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app.dialog()
+            .file()
+            .blocking_pick_folder()
+            .and_then(|dir| dir.into_path().ok())
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Vec<RegionProfile> {
+    app.state::<AppState>().profiles.list()
+}
+
+/// An empty `hotkey` unbinds the profile.
+#[tauri::command]
+pub fn set_profile_hotkey(
+    app: AppHandle,
+    name: String,
+    hotkey: String,
+) -> Result<RegionProfile, String> {
+    AppMediator::set_profile_hotkey(&app, &name, &hotkey)
+}
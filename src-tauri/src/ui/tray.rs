@@ -1,10 +1,15 @@
 // src/ui/tray.rs
 
 use tauri::{
-    App, Manager, menu::{Menu, MenuItem}, tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent}
+    App, Listener, Manager, Runtime, menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu}, tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent}
 };
 
-use crate::{app::AppMediator, state::AppState, ui::{self}};
+use crate::{app::AppMediator, infra::{change_detection::WatchOptions, preprocess::{PreprocessPipeline, PreprocessPreset}, screenshot::ScreenshotService, settings::Settings}, state::AppState, ui::{self}};
+
+const PREPROCESS_PREFIX: &str = "preprocess:";
+const PROFILE_PREFIX: &str = "profile:";
+const WATCH_INTERVAL_PREFIX: &str = "watch_interval:";
+const WATCH_INTERVALS_MS: [u64; 4] = [250, 500, 1000, 2000];
 
 /// Initialize the tray icon + menu and wire up actions
 /// Call this from your Tauri `.setup(...)` in app.rs
@@ -12,7 +17,170 @@ pub fn init_tray(app: &App) -> tauri::Result<()> {
     // menu items
     let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
     let capture_item = MenuItem::with_id(app, "capture", "Capture Region", true, None::<&str>)?;
-    let tray_menu = Menu::with_items(app, &[&capture_item, &quit_item])?;
+    let repeat_item =
+        MenuItem::with_id(app, "repeat_region", "Repeat Last Region", true, None::<&str>)?;
+    let window_item =
+        MenuItem::with_id(app, "capture_window", "Capture Window…", true, None::<&str>)?;
+    let recapture_window_item =
+        MenuItem::with_id(app, "recapture_window", "Recapture Window", true, None::<&str>)?;
+    let clipboard_item =
+        MenuItem::with_id(app, "ocr_clipboard", "OCR Clipboard Image", true, None::<&str>)?;
+    let file_item = MenuItem::with_id(app, "ocr_file", "OCR Image File…", true, None::<&str>)?;
+    let history_item = MenuItem::with_id(app, "history", "History…", true, None::<&str>)?;
+    let review_item = MenuItem::with_id(app, "review", "Review Words…", true, None::<&str>)?;
+    let settings_item = MenuItem::with_id(app, "settings", "Settings…", true, None::<&str>)?;
+    let settings = app.state::<AppState>().settings.get();
+    let save_captures_item = CheckMenuItem::with_id(
+        app,
+        "save_captures",
+        "Save Captures",
+        true,
+        settings.captures_dir.is_some(),
+        None::<&str>,
+    )?;
+    let freeze_item = CheckMenuItem::with_id(
+        app,
+        "freeze_screen",
+        "Freeze Screen While Selecting",
+        true,
+        false,
+        None::<&str>,
+    )?;
+    let split_item = CheckMenuItem::with_id(
+        app,
+        "split_blocks",
+        "Split Text Blocks",
+        true,
+        settings.ocr.split_blocks,
+        None::<&str>,
+    )?;
+
+    // preprocessing presets (one checked at a time) + debug dumps toggle
+    let preset_items = PreprocessPreset::ALL
+        .iter()
+        .map(|p| {
+            CheckMenuItem::with_id(
+                app,
+                format!("{PREPROCESS_PREFIX}{}", p.id()),
+                p.label(),
+                true,
+                *p == settings.ocr.preprocess,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let debug_item = CheckMenuItem::with_id(
+        app,
+        "preprocess_debug",
+        "Save Debug Images",
+        true,
+        false,
+        None::<&str>,
+    )?;
+    let clear_cache_item =
+        MenuItem::with_id(app, "clear_ocr_cache", "Clear OCR Cache", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+
+    let preprocess_menu = Submenu::with_id(app, "preprocess", "Preprocessing", true)?;
+    for item in &preset_items {
+        preprocess_menu.append(item)?;
+    }
+    preprocess_menu.append(&separator)?;
+    preprocess_menu.append(&debug_item)?;
+    preprocess_menu.append(&clear_cache_item)?;
+
+    // saved region profiles, then actions to create new ones
+    let profiles = app.state::<AppState>().profiles.list();
+    let profiles_menu = Submenu::with_id(app, "profiles", "Profiles", true)?;
+    for profile in &profiles {
+        profiles_menu.append(&profile_item(app, &profile.name)?)?;
+    }
+    profiles_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    profiles_menu.append(&MenuItem::with_id(
+        app,
+        "save_region_profile",
+        "Save Last Region as Profile",
+        true,
+        None::<&str>,
+    )?)?;
+    profiles_menu.append(&MenuItem::with_id(
+        app,
+        "save_window_profile",
+        "Save Window as Profile",
+        true,
+        None::<&str>,
+    )?)?;
+
+    // watch mode: re-capture the last region/profile and OCR it on change
+    let watch_item =
+        CheckMenuItem::with_id(app, "watch", "Watch Mode", true, false, None::<&str>)?;
+    let default_interval = WatchOptions::default().interval_ms;
+    let interval_items = WATCH_INTERVALS_MS
+        .iter()
+        .map(|ms| {
+            CheckMenuItem::with_id(
+                app,
+                format!("{WATCH_INTERVAL_PREFIX}{ms}"),
+                format!("{ms} ms"),
+                true,
+                *ms == default_interval,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let interval_menu = Submenu::with_id(app, "watch_interval", "Watch Interval", true)?;
+    for item in &interval_items {
+        interval_menu.append(item)?;
+    }
+
+    let tray_menu = Menu::with_items(
+        app,
+        &[
+            &capture_item,
+            &repeat_item,
+            &window_item,
+            &recapture_window_item,
+            &freeze_item,
+            &profiles_menu,
+            &watch_item,
+            &interval_menu,
+            &clipboard_item,
+            &file_item,
+            &split_item,
+            &save_captures_item,
+            &preprocess_menu,
+            &history_item,
+            &review_item,
+            &settings_item,
+            &quit_item,
+        ],
+    )?;
+
+    // keep the checks in line with changes made outside the menu (settings
+    // window or file, hotkeys)
+    {
+        let split_item = split_item.clone();
+        let save_captures_item = save_captures_item.clone();
+        let preset_items = preset_items.clone();
+        app.listen("settings-changed", move |event| {
+            let Ok(settings) = serde_json::from_str::<Settings>(event.payload()) else {
+                return;
+            };
+            let _ = split_item.set_checked(settings.ocr.split_blocks);
+            let _ = save_captures_item.set_checked(settings.captures_dir.is_some());
+            for (item, p) in preset_items.iter().zip(PreprocessPreset::ALL) {
+                let _ = item.set_checked(p == settings.ocr.preprocess);
+            }
+        });
+
+        // watch mode can also be toggled with a hotkey
+        let watch_item = watch_item.clone();
+        app.listen("watch-changed", move |event| {
+            if let Ok(watching) = serde_json::from_str::<bool>(event.payload()) {
+                let _ = watch_item.set_checked(watching);
+            }
+        });
+    }
 
     // build tray
     TrayIconBuilder::new()
@@ -29,9 +197,9 @@ pub fn init_tray(app: &App) -> tauri::Result<()> {
             {}
         })
         // menu items
-        .on_menu_event(|app_handle, ev| match ev.id.as_ref() {
+        .on_menu_event(move |app_handle, ev| match ev.id.as_ref() {
             "quit" => {
-                // same as before
+                app_handle.state::<AppState>().ocr_cache.flush();
                 std::process::exit(0);
             }
             "capture" => {
@@ -44,9 +212,156 @@ pub fn init_tray(app: &App) -> tauri::Result<()> {
                     AppMediator::start_region_capture(app_handle);
                 }
             }
-            _ => {}
+            "repeat_region" => {
+                if let Err(e) = AppMediator::repeat_last_region(app_handle) {
+                    eprintln!("Repeat capture failed: {e}");
+                }
+            }
+            "capture_window" => {
+                AppMediator::open_window_picker(app_handle);
+            }
+            "recapture_window" => {
+                if let Err(e) = AppMediator::capture_window_target(app_handle) {
+                    eprintln!("Window capture failed: {e}");
+                }
+            }
+            "save_region_profile" | "save_window_profile" => {
+                let saved = if ev.id.as_ref() == "save_region_profile" {
+                    AppMediator::save_region_profile(app_handle)
+                } else {
+                    AppMediator::save_window_profile(app_handle)
+                };
+                let profile = match saved {
+                    Ok(profile) => profile,
+                    Err(e) => {
+                        eprintln!("Profile not saved: {e}");
+                        return;
+                    }
+                };
+
+                // new profiles are appended, so it goes right above the separator
+                let position = app_handle.state::<AppState>().profiles.list().len() - 1;
+                let added = profile_item(app_handle, &profile.name)
+                    .and_then(|item| profiles_menu.insert(&item, position));
+                if let Err(e) = added {
+                    eprintln!("Failed to add profile to the tray: {e}");
+                }
+            }
+            "watch" => {
+                if !watch_item.is_checked().unwrap_or(false) {
+                    AppMediator::stop_watch(app_handle);
+                } else if let Err(e) = AppMediator::start_watch(app_handle) {
+                    eprintln!("Watch mode not started: {e}");
+                    let _ = watch_item.set_checked(false);
+                }
+            }
+            "ocr_clipboard" => {
+                if let Err(e) = AppMediator::ocr_clipboard_image(app_handle) {
+                    eprintln!("Clipboard OCR failed: {e}");
+                }
+            }
+            "ocr_file" => {
+                AppMediator::pick_image_file(app_handle);
+            }
+            "history" => {
+                AppMediator::open_history_window(app_handle);
+            }
+            "review" => {
+                AppMediator::open_review_window(app_handle);
+            }
+            "settings" => {
+                AppMediator::open_settings_window(app_handle);
+            }
+            "save_captures" => {
+                // the folder is set in the settings window; the tray turns
+                // saving on with the default one
+                let dir = save_captures_item
+                    .is_checked()
+                    .unwrap_or(false)
+                    .then(ScreenshotService::default_captures_dir)
+                    .flatten();
+                if let Err(e) = AppMediator::change_settings(app_handle, |s| s.captures_dir = dir) {
+                    eprintln!("Setting not saved: {e:?}");
+                }
+                let saving = app_handle.state::<AppState>().settings.get().captures_dir;
+                let _ = save_captures_item.set_checked(saving.is_some());
+            }
+            "freeze_screen" => {
+                let enabled = freeze_item.is_checked().unwrap_or(false);
+                app_handle.state::<AppState>().set_freeze_screen(enabled);
+            }
+            "split_blocks" => {
+                let enabled = split_item.is_checked().unwrap_or(false);
+                if let Err(e) =
+                    AppMediator::change_settings(app_handle, |s| s.ocr.split_blocks = enabled)
+                {
+                    eprintln!("Setting not saved: {e:?}");
+                }
+            }
+            "clear_ocr_cache" => {
+                app_handle.state::<AppState>().ocr_cache.clear();
+                println!("OCR cache cleared");
+            }
+            "preprocess_debug" => {
+                let enabled = debug_item.is_checked().unwrap_or(false);
+                let state = app_handle.state::<AppState>();
+                let mut pipeline = state.preprocess.lock().unwrap();
+                pipeline.debug_dir = enabled
+                    .then(PreprocessPipeline::default_debug_dir)
+                    .flatten();
+                if let Some(dir) = &pipeline.debug_dir {
+                    println!("Saving preprocessing debug images to {}", dir.display());
+                }
+            }
+            id if id.starts_with(WATCH_INTERVAL_PREFIX) => {
+                let Ok(ms) = id[WATCH_INTERVAL_PREFIX.len()..].parse::<u64>() else {
+                    return;
+                };
+                for (item, interval) in interval_items.iter().zip(WATCH_INTERVALS_MS) {
+                    let _ = item.set_checked(interval == ms);
+                }
+                app_handle.state::<AppState>().watch_options.lock().unwrap().interval_ms = ms;
+            }
+            id if id.starts_with(PROFILE_PREFIX) => {
+                let name = &id[PROFILE_PREFIX.len()..];
+                if let Err(e) = AppMediator::capture_profile(app_handle, name) {
+                    eprintln!("Profile capture failed: {e}");
+                }
+            }
+            id => {
+                let Some(preset) = id
+                    .strip_prefix(PREPROCESS_PREFIX)
+                    .and_then(PreprocessPreset::from_id)
+                else {
+                    return;
+                };
+
+                // behave like a radio group
+                for (item, p) in preset_items.iter().zip(PreprocessPreset::ALL) {
+                    let _ = item.set_checked(p == preset);
+                }
+
+                if let Err(e) =
+                    AppMediator::change_settings(app_handle, |s| s.ocr.preprocess = preset)
+                {
+                    eprintln!("Setting not saved: {e:?}");
+                }
+            }
         })
         .build(app)?;
 
     Ok(())
 }
+
+fn profile_item<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    name: &str,
+) -> tauri::Result<MenuItem<R>> {
+    MenuItem::with_id(
+        manager,
+        format!("{PROFILE_PREFIX}{name}"),
+        name,
+        true,
+        None::<&str>,
+    )
+}
@@ -0,0 +1,26 @@
+use crate::app::AppMediator;
+use crate::infra::screenshot::window::{self, WindowInfo};
+use tauri::{AppHandle, Manager};
+
+//
+// ----------------------------------------------------------------
+//   Tauri Commands Exposed to JS
+// ----------------------------------------------------------------
+//
+
+#[tauri::command]
+pub fn wp_list_windows() -> Result<Vec<WindowInfo>, String> {
+    window::list_windows().map_err(|e| e.to_string())
+}
+
+/// async: may create the region selection window, which deadlocks on
+/// Windows when done from a synchronous command.
+#[tauri::command]
+pub async fn wp_select_window(app: AppHandle, id: u32, pick_area: bool) -> Result<(), String> {
+    // picker gets out of the way of the capture
+    if let Some(win) = app.get_webview_window("window-picker") {
+        let _ = win.close();
+    }
+
+    AppMediator::select_capture_window(&app, id, pick_area)
+}
@@ -0,0 +1,311 @@
+// ---------------------- TAURI GLOBAL API ----------------------
+const { invoke } = window.__TAURI__.core;
+
+const lookupsEl = document.getElementById("lookups");
+const capturesEl = document.getElementById("captures");
+const statusEl = document.getElementById("status");
+const searchEl = document.getElementById("search");
+const fromEl = document.getElementById("from");
+const toEl = document.getElementById("to");
+const rerunOptionsEl = document.getElementById("rerun-options");
+const engineEl = document.getElementById("engine");
+const presetEl = document.getElementById("preset");
+const splitEl = document.getElementById("split");
+const exportOptionsEl = document.getElementById("export-options");
+const selectAllEl = document.getElementById("select-all");
+const selectedCountEl = document.getElementById("selected-count");
+const exportEl = document.getElementById("export-apkg");
+
+// ---------------------- TABS ----------------------
+
+const TABS = {
+    lookups: {
+        list: lookupsEl,
+        placeholder: "Search word, headword or sentence",
+        empty: "No lookups found.",
+        search: "hs_search_lookups",
+        render: renderLookup,
+    },
+    captures: {
+        list: capturesEl,
+        placeholder: "Search OCR text",
+        empty: "No captures found.",
+        search: "hs_search_captures",
+        render: renderCapture,
+    },
+};
+let currentTab = "lookups";
+
+function showTab(name) {
+    currentTab = name;
+    for (const tabEl of document.querySelectorAll(".tab")) {
+        tabEl.classList.toggle("active", tabEl.dataset.tab === name);
+    }
+    for (const [tabName, tab] of Object.entries(TABS)) {
+        tab.list.hidden = tabName !== name;
+    }
+    rerunOptionsEl.hidden = name !== "captures";
+    exportOptionsEl.hidden = name !== "lookups";
+    searchEl.placeholder = TABS[name].placeholder;
+    refresh();
+}
+
+// ---------------------- FILTER ----------------------
+// hs_search_*({ text, from, to }) with Unix seconds; `to` is exclusive,
+// so the "To" day is included by searching up to the start of the next day
+
+function dayStart(value, addDays = 0) {
+    if (!value) return null;
+    const date = new Date(`${value}T00:00:00`);
+    date.setDate(date.getDate() + addDays);
+    return Math.floor(date.getTime() / 1000);
+}
+
+function currentFilter() {
+    return {
+        text: searchEl.value.trim() || null,
+        from: dayStart(fromEl.value),
+        to: dayStart(toEl.value, 1),
+    };
+}
+
+// ---------------------- LIST ----------------------
+
+async function refresh() {
+    const tab = TABS[currentTab];
+    statusEl.innerText = "";
+
+    let entries = [];
+    try {
+        entries = await invoke(tab.search, { filter: currentFilter() });
+    } catch (err) {
+        statusEl.innerText = `Could not load the history: ${err}`;
+        return;
+    }
+
+    releaseThumbnails();
+    tab.list.replaceChildren(...entries.map(tab.render));
+    updateSelection();
+    if (!entries.length) {
+        statusEl.innerText = tab.empty;
+    }
+}
+
+function metaLine(entry, ...parts) {
+    const meta = document.createElement("div");
+    meta.className = "meta";
+    meta.textContent = [new Date(entry.created_at * 1000).toLocaleString(), ...parts].join(" · ");
+    return meta;
+}
+
+function actionButton(label, onClick) {
+    const btn = document.createElement("div");
+    btn.className = "action-btn";
+    btn.textContent = label;
+    btn.onclick = async () => {
+        try {
+            await onClick();
+        } catch (err) {
+            statusEl.innerText = `${label} failed: ${err}`;
+        }
+    };
+    return btn;
+}
+
+// ---------------------- LOOKUPS ----------------------
+// [{ id, created_at, query, headword, sentence, source, capture_id }]
+
+// ids of the lookups selected for export; kept across refreshes
+const selected = new Set();
+
+function renderLookup(entry) {
+    const li = document.createElement("li");
+
+    const select = document.createElement("input");
+    select.type = "checkbox";
+    select.className = "select";
+    select.checked = selected.has(entry.id);
+    select.onchange = () => {
+        if (select.checked) selected.add(entry.id);
+        else selected.delete(entry.id);
+        updateSelection();
+    };
+
+    const word = document.createElement("div");
+    word.className = "word";
+    word.textContent = entry.headword && entry.headword !== entry.query
+        ? `${entry.query} → ${entry.headword}`
+        : entry.query;
+
+    const sentence = document.createElement("div");
+    sentence.className = "sentence";
+    sentence.textContent = entry.sentence ?? "";
+
+    const actions = document.createElement("div");
+    actions.className = "actions";
+    actions.append(
+        actionButton("Open", () => invoke("hs_open_lookup", { id: entry.id })),
+        actionButton("Delete", async () => {
+            await invoke("hs_delete_lookup", { id: entry.id });
+            selected.delete(entry.id);
+            li.remove();
+            updateSelection();
+        }),
+    );
+
+    li.append(select, word, sentence, metaLine(entry, entry.source), actions);
+    return li;
+}
+
+function updateSelection() {
+    const boxes = [...lookupsEl.querySelectorAll(".select")];
+    selectAllEl.checked = boxes.length > 0 && boxes.every((box) => box.checked);
+    selectedCountEl.textContent = selected.size ? `${selected.size} selected` : "";
+}
+
+function selectAll(checked) {
+    for (const box of lookupsEl.querySelectorAll(".select")) {
+        box.checked = checked;
+        box.onchange();
+    }
+}
+
+async function exportApkg() {
+    if (!selected.size) {
+        statusEl.innerText = "Select the lookups to export first.";
+        return;
+    }
+    statusEl.innerText = "Exporting…";
+    try {
+        // hs_export_apkg -> number of notes written, null if no file was picked
+        const written = await invoke("hs_export_apkg", { ids: [...selected] });
+        statusEl.innerText = written === null ? "" : `Exported ${written} card(s).`;
+    } catch (err) {
+        statusEl.innerText = `Export failed: ${err}`;
+    }
+}
+
+// ---------------------- CAPTURES ----------------------
+// [{ id, created_at, source, source_label, engine, region: { x, y, w, h },
+//    text, edited }]
+
+// object URLs of the thumbnails on screen, released on every refresh
+let thumbnailUrls = [];
+
+function releaseThumbnails() {
+    thumbnailUrls.forEach((url) => URL.revokeObjectURL(url));
+    thumbnailUrls = [];
+}
+
+async function loadThumbnail(img, id) {
+    // hs_capture_thumbnail returns the PNG bytes
+    const buf = await invoke("hs_capture_thumbnail", { id });
+    if (!buf.byteLength) return;
+    const url = URL.createObjectURL(new Blob([buf], { type: "image/png" }));
+    thumbnailUrls.push(url);
+    img.src = url;
+}
+
+function captureMeta(entry) {
+    const { w, h } = entry.region;
+    const parts = [entry.source_label, entry.engine, `${w}×${h}`];
+    if (entry.edited) parts.push("edited");
+    return metaLine(entry, ...parts);
+}
+
+function renderCapture(entry) {
+    const li = document.createElement("li");
+    li.className = "capture";
+
+    const img = document.createElement("img");
+    img.className = "thumbnail";
+    img.alt = "";
+    loadThumbnail(img, entry.id).catch((err) => console.error("thumbnail error:", err));
+
+    const text = document.createElement("textarea");
+    text.className = "capture-text";
+    text.value = entry.text;
+    text.rows = Math.min(6, Math.max(2, entry.text.split("\n").length));
+
+    let meta = captureMeta(entry);
+
+    const actions = document.createElement("div");
+    actions.className = "actions";
+    actions.append(
+        actionButton("Save", async () => {
+            await invoke("hs_set_capture_text", { id: entry.id, text: text.value });
+            entry = { ...entry, text: text.value, edited: true };
+            meta.replaceWith((meta = captureMeta(entry)));
+        }),
+        actionButton("Open", () => invoke("hs_open_capture", { id: entry.id })),
+        actionButton("Re-run", async () => {
+            statusEl.innerText = "Running OCR…";
+            entry = await invoke("hs_rerun_capture", {
+                id: entry.id,
+                engine: engineEl.value,
+                preset: presetEl.value,
+                splitBlocks: splitEl.checked,
+            });
+            statusEl.innerText = "";
+            text.value = entry.text;
+            meta.replaceWith((meta = captureMeta(entry)));
+        }),
+        actionButton("Delete", async () => {
+            await invoke("hs_delete_capture", { id: entry.id });
+            li.remove();
+        }),
+    );
+
+    li.append(img, text, meta, actions);
+    return li;
+}
+
+// Re-run options default to the current OCR settings
+async function initRerunOptions() {
+    // settings_choices -> { ocr_engines: [{ id, label }], ... }
+    const choices = await invoke("settings_choices");
+    for (const select of rerunOptionsEl.querySelectorAll("select[data-choices]")) {
+        for (const { id, label } of choices[select.dataset.choices]) {
+            const option = document.createElement("option");
+            option.value = id;
+            option.textContent = label;
+            select.append(option);
+        }
+    }
+
+    const { ocr } = await invoke("get_settings");
+    engineEl.value = ocr.engine;
+    presetEl.value = ocr.preprocess;
+    splitEl.checked = ocr.split_blocks;
+}
+
+// ---------------------- EVENTS ----------------------
+
+for (const tabEl of document.querySelectorAll(".tab")) {
+    tabEl.addEventListener("click", () => showTab(tabEl.dataset.tab));
+}
+
+let searchTimer = null;
+searchEl.addEventListener("input", () => {
+    clearTimeout(searchTimer);
+    searchTimer = setTimeout(refresh, 200);
+});
+fromEl.addEventListener("change", refresh);
+toEl.addEventListener("change", refresh);
+selectAllEl.addEventListener("change", () => selectAll(selectAllEl.checked));
+exportEl.addEventListener("click", exportApkg);
+
+// new entries show up when the window is focused again, unless a capture's
+// text is being edited
+window.addEventListener("focus", () => {
+    if (document.activeElement?.tagName !== "TEXTAREA") refresh();
+});
+
+window.addEventListener("keydown", (e) => {
+    if (e.key === "Escape") {
+        window.__TAURI__.window.getCurrentWindow().close();
+    }
+});
+
+initRerunOptions().catch((err) => console.error("re-run options error:", err));
+showTab("lookups");
@@ -2,12 +2,99 @@
 const { getCurrentWindow } = window.__TAURI__.window;
 const { writeText } = window.__TAURI__.clipboardManager;
 const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
 
 const appWindow = getCurrentWindow();
 
 // ---------------------- OCR TEXT ----------------------
-const text = window.__OCR_TEXT ?? "(No OCR result)";
-document.getElementById("ocr-text").innerText = text;
+// __OCR_RESULTS: one { text, blocks, orientation, engine, confidence, elapsed_ms,
+// capture_id, key, low_confidence } per captured region, in capture order;
+// capture_id is the capture's history id, null if it is recorded after the
+// overlay opened (then the backend finds it by key) or not at all
+
+const textEl = document.getElementById("ocr-text");
+const warningEl = document.getElementById("ocr-warning");
+
+let text = "";
+let shownResults = null;
+
+function renderResults(results) {
+    shownResults = results;
+    results = results ?? [];
+    text = results.length
+        ? results.map(r => r.text).join("\n\n")
+        : window.__OCR_TEXT ?? "(No OCR result)";
+
+    textEl.replaceChildren();
+    if (results.length > 1) {
+        // one numbered section per region
+        const regions = results.map((result, i) => {
+            const section = document.createElement("div");
+            section.className = "ocr-region";
+            section.dataset.index = i + 1;
+            section.innerText = result.text;
+            textEl.appendChild(section);
+            return { el: section, text: result.text };
+        });
+        colorWords(regions);
+    } else {
+        textEl.innerText = text;
+        colorWords(results.length ? [{ el: textEl, text }] : []);
+    }
+
+    const confidences = results
+        .filter(r => r.low_confidence)
+        .map(r => r.confidence);
+
+    if (confidences.length) {
+        const lowest = Math.min(...confidences);
+        warningEl.innerText =
+            `Low OCR confidence (${Math.round(lowest * 100)}%), text may be wrong`;
+        warningEl.style.display = "block";
+    } else {
+        warningEl.style.display = "none";
+    }
+}
+
+// ---------------------- WORD STATUS ----------------------
+// ocr_word_tokens -> [{ text, lemma, status }]: lemma is null for text that
+// is no word, status null for words not in the word list
+
+let highlightWords = false;
+let colorRun = 0;
+
+async function colorWords(regions) {
+    const run = ++colorRun;
+    if (!highlightWords) return;
+
+    for (const { el, text } of regions) {
+        let tokens;
+        try {
+            tokens = await invoke("ocr_word_tokens", { text });
+        } catch (err) {
+            console.error("Word status failed:", err);
+            return;
+        }
+        // newer text was shown meanwhile
+        if (run !== colorRun) return;
+        el.replaceChildren(...tokens.map(renderToken));
+    }
+}
+
+function renderToken({ text, lemma, status }) {
+    if (!lemma) return document.createTextNode(text);
+
+    const span = document.createElement("span");
+    span.className = `word ${status ?? "unknown"}`;
+    span.textContent = text;
+    if (lemma !== text) span.title = lemma;
+    return span;
+}
+
+// backend calls this to refresh an already open overlay
+window.__setOcrResults = renderResults;
+
+renderResults(window.__OCR_RESULTS ?? null);
 
 // ---------------------- BUTTONS ----------------------
 const copyBtn = document.getElementById("copy");
@@ -31,18 +118,52 @@ copyAllBtn.onclick = async () => {
 };
 
 // ---------------------- LOOKUP ACTION ----------------------
+
+// result of the region the selection starts in
+function selectedResult() {
+    const node = window.getSelection().anchorNode;
+    const el = node instanceof Element ? node : node?.parentElement;
+    const section = el?.closest(".ocr-region");
+    const index = section ? Number(section.dataset.index) - 1 : 0;
+    return shownResults?.[index] ?? null;
+}
+
 lookupBtn.onclick = async () => {
     const selection = window.getSelection().toString().trim();
     if (!selection) return;
 
+    const result = selectedResult();
     try {
-        await invoke("lookup_selected_text", { text: selection });
+        await invoke("lookup_selected_text", {
+            text: selection,
+            context: result?.text ?? text,
+            captureId: result?.capture_id ?? null,
+            resultKey: result?.key ?? null,
+        });
     } catch (err) {
         console.error("Lookup failed:", err);
         // Optional: surface a toast / inline error later
     }
 };
 
+// ---------------------- APPEARANCE ----------------------
+// settings.overlay: { font_size, text_color, background_opacity, highlight_words }
+
+function applyAppearance({ font_size, text_color, background_opacity, highlight_words }) {
+    const style = document.documentElement.style;
+    style.setProperty("--ocr-font-size", `${font_size}px`);
+    style.setProperty("--ocr-text-color", text_color);
+    style.setProperty("--ocr-panel-opacity", background_opacity);
+
+    if (highlight_words !== highlightWords) {
+        highlightWords = highlight_words;
+        renderResults(shownResults);
+    }
+}
+
+invoke("get_settings").then(settings => applyAppearance(settings.overlay));
+listen("settings-changed", event => applyAppearance(event.payload.overlay));
+
 // ---------------------- SELECTION VISIBILITY ----------------------
 
 // helper: does the user have selected text?
@@ -8,10 +8,33 @@ const leftS = document.getElementById("shade-left");
 const rightS = document.getElementById("shade-right");
 const hintTop = document.getElementById("hint-top");
 const hintBottom = document.getElementById("hint-bottom");
+const regionsEl = document.getElementById("regions");
+const frozenEl = document.getElementById("frozen");
 
 
-//show overlay
-invoke("rs_ready");
+// frozen mode: paint the snapshot taken before the overlay opened, then show
+// the overlay. rs_snapshot returns a PNG, or nothing when selecting on the
+// live screen.
+async function loadSnapshot() {
+  try {
+    const buf = await invoke("rs_snapshot");
+    if (buf.byteLength > 0) {
+      const bitmap = await createImageBitmap(new Blob([buf], { type: "image/png" }));
+
+      frozenEl.width = bitmap.width;
+      frozenEl.height = bitmap.height;
+      frozenEl.getContext("2d").drawImage(bitmap, 0, 0);
+      bitmap.close();
+      frozenEl.style.display = "block";
+    }
+  } catch (err) {
+    console.error("rs_snapshot error:", err);
+  }
+
+  //show overlay
+  invoke("rs_ready");
+}
+loadSnapshot();
 
 // -------- input → backend --------
 
@@ -28,9 +51,14 @@ window.addEventListener("mousemove", (e) => {
 window.addEventListener("mousedown", (e) => {
   if (e.button === 0) {
     invoke("rs_mousedown", { button: "left" });
+  } else if (e.button === 2) {
+    // right click removes the region under the cursor
+    invoke("rs_mousedown", { button: "right" });
   }
 });
 
+window.addEventListener("contextmenu", (e) => e.preventDefault());
+
 window.addEventListener("mouseup", (e) => {
   if (e.button === 0) {
     invoke("rs_mouseup", { button: "left" });
@@ -43,11 +71,27 @@ invoke("rs_set_window_size", {
   height: window.innerHeight,
 });
 
+const ARROWS = {
+  ArrowLeft: "left",
+  ArrowRight: "right",
+  ArrowUp: "up",
+  ArrowDown: "down",
+};
+
 window.addEventListener("keydown", (e) => {
   if (e.key === "Enter") {
-    invoke("rs_key_enter");
+    // refused e.g. with several regions while picking a window's area
+    invoke("rs_key_enter").catch((err) => (hintBottom.innerText = err));
   } else if (e.key === "Escape") {
     invoke("rs_key_escape");
+  } else if (ARROWS[e.key]) {
+    e.preventDefault();
+    invoke("rs_key_arrow", { key: ARROWS[e.key], shift: e.shiftKey, alt: e.altKey });
+  } else if (e.key === "a" || e.key === "A") {
+    invoke("rs_toggle_aspect_lock");
+  } else if (e.key === "[" || e.key === "]") {
+    // move the active region earlier / later in the capture order
+    invoke("rs_reorder", { delta: e.key === "[" ? -1 : 1 });
   }
 });
 
@@ -71,10 +115,31 @@ cursorLoop();
 // -------- state → render --------
 //
 // rs_get_state must return:
-// { phase: "Idle" | "Drawing" | "Confirmed" | "Moving" | "Capturing",
-//   bounds: { x, y, w, h } | null,
+// { phase: "Idle" | "Drawing" | "Confirmed" | "Moving" | "Resizing" | "Capturing",
+//   bounds: { x, y, w, h } | null,              (active region)
+//   regions: [{ x, y, w, h }],                  (all regions, capture order)
+//   active: number | null,                      (index of bounds in regions)
+//   handle: "top" | "top_left" | ... | null,   (dragged or hovered handle)
+//   aspect_locked: bool,
 //   screen: { w, h } }
 
+const HANDLE_CURSORS = {
+  top: "ns-resize",
+  bottom: "ns-resize",
+  left: "ew-resize",
+  right: "ew-resize",
+  top_left: "nwse-resize",
+  bottom_right: "nwse-resize",
+  top_right: "nesw-resize",
+  bottom_left: "nesw-resize",
+};
+
+function cursorFor(phase, handle) {
+  if (handle) return HANDLE_CURSORS[handle];
+  if (phase === "Moving") return "move";
+  return "crosshair";
+}
+
 function setShadeRect(el, x, y, w, h) {
   el.style.left = x + "px";
   el.style.top = y + "px";
@@ -83,7 +148,25 @@ function setShadeRect(el, x, y, w, h) {
   el.style.display = w > 0 && h > 0 ? "block" : "none";
 }
 
+// outlines and order numbers for every region; the active one is drawn by #selection-box
+function renderRegions(regions, active) {
+  regionsEl.replaceChildren();
+  if (regions.length < 2) return;
+
+  regions.forEach((r, i) => {
+    const el = document.createElement("div");
+    el.className = i === active ? "region active" : "region";
+    el.style.transform = `translate(${r.x}px, ${r.y}px)`;
+    el.style.width = r.w + "px";
+    el.style.height = r.h + "px";
+    el.dataset.index = i + 1;
+    regionsEl.appendChild(el);
+  });
+}
+
 function applyState(state) {
+  renderRegions(state?.phase === "Capturing" ? [] : state?.regions ?? [], state?.active);
+
   if (!state) {
     hintBottom.innerText = "";
     box.style.display = "none";
@@ -92,10 +175,14 @@ function applyState(state) {
     return;
   }
 
-  const { phase, bounds, screen } = state;
+  const { phase, bounds, screen, handle, aspect_locked, regions } = state;
 
   // update bottom hint
-  hintBottom.innerText = phase;
+  let status = phase;
+  if (regions.length > 1) status += ` · ${regions.length} regions`;
+  if (aspect_locked) status += " · aspect locked";
+  hintBottom.innerText = status;
+  document.body.style.cursor = cursorFor(phase, handle);
 
   const scrW = screen?.w ?? window.innerWidth;
   const scrH = screen?.h ?? window.innerHeight;
@@ -131,7 +218,7 @@ function applyState(state) {
   box.classList.remove("drawing", "marching");
   if (phase === "Drawing") {
     box.classList.add("drawing");
-  } else if (phase === "Confirmed" || phase === "Moving") {
+  } else if (phase === "Confirmed" || phase === "Moving" || phase === "Resizing") {
     box.classList.add("marching");
   }
 
@@ -0,0 +1,62 @@
+// ---------------------- TAURI GLOBAL API ----------------------
+const { invoke } = window.__TAURI__.core;
+
+const listEl = document.getElementById("windows");
+const statusEl = document.getElementById("status");
+const pickAreaEl = document.getElementById("pick-area");
+
+// ---------------------- WINDOW LIST ----------------------
+// wp_list_windows -> [{ id, title, app_name, bounds: { x, y, w, h } }]
+
+async function refresh() {
+    listEl.innerHTML = "";
+    statusEl.innerText = "";
+
+    let windows = [];
+    try {
+        windows = await invoke("wp_list_windows");
+    } catch (err) {
+        statusEl.innerText = `Could not list windows: ${err}`;
+        return;
+    }
+
+    if (!windows.length) {
+        statusEl.innerText = "No capturable windows found.";
+        return;
+    }
+
+    for (const w of windows) {
+        const li = document.createElement("li");
+
+        const title = document.createElement("div");
+        title.className = "title";
+        title.textContent = w.title;
+
+        const meta = document.createElement("div");
+        meta.className = "meta";
+        meta.textContent = `${w.app_name} · ${w.bounds.w}×${w.bounds.h}`;
+
+        li.appendChild(title);
+        li.appendChild(meta);
+        li.onclick = () => select(w.id);
+        listEl.appendChild(li);
+    }
+}
+
+async function select(id) {
+    try {
+        await invoke("wp_select_window", { id, pickArea: pickAreaEl.checked });
+    } catch (err) {
+        statusEl.innerText = `Capture failed: ${err}`;
+    }
+}
+
+document.getElementById("refresh").onclick = refresh;
+
+window.addEventListener("keydown", (e) => {
+    if (e.key === "Escape") {
+        window.__TAURI__.window.getCurrentWindow().close();
+    }
+});
+
+refresh();
@@ -0,0 +1,315 @@
+// ---------------------- TAURI GLOBAL API ----------------------
+const { invoke } = window.__TAURI__.core;
+
+const countsEl = document.getElementById("counts");
+const statusEl = document.getElementById("status");
+const reviewEl = document.getElementById("review");
+const wordsEl = document.getElementById("words");
+const cardEl = document.getElementById("card");
+const termEl = document.getElementById("term");
+const sentenceEl = document.getElementById("sentence");
+const answerEl = document.getElementById("answer");
+const readingEl = document.getElementById("reading");
+const entriesEl = document.getElementById("entries");
+const showAnswerEl = document.getElementById("show-answer");
+const gradesEl = document.getElementById("grades");
+const searchEl = document.getElementById("search");
+const statusFilterEl = document.getElementById("status-filter");
+const wordListEl = document.getElementById("word-list");
+const importStatusEl = document.getElementById("import-status");
+const ankiQueryEl = document.getElementById("anki-query");
+const ankiFieldEl = document.getElementById("anki-field");
+
+const GRADES = ["again", "hard", "good", "easy"];
+const STATUS_LABELS = { new: "New", learning: "Learning", known: "Known" };
+
+// ---------------------- TABS ----------------------
+
+let currentTab = "review";
+
+function showTab(name) {
+    currentTab = name;
+    for (const tabEl of document.querySelectorAll(".tab")) {
+        tabEl.classList.toggle("active", tabEl.dataset.tab === name);
+    }
+    reviewEl.hidden = name !== "review";
+    wordsEl.hidden = name !== "words";
+    refresh();
+}
+
+function refresh() {
+    statusEl.innerText = "";
+    refreshCounts();
+    if (currentTab === "review") {
+        nextCard();
+    } else {
+        refreshWords();
+    }
+}
+
+// rv_counts -> { new, learning, known, due }
+async function refreshCounts() {
+    try {
+        const counts = await invoke("rv_counts");
+        countsEl.textContent = `${counts.due} due · ${counts.new} new · ` +
+            `${counts.learning} learning · ${counts.known} known`;
+    } catch (err) {
+        countsEl.textContent = "";
+        statusEl.innerText = `Could not load the word list: ${err}`;
+    }
+}
+
+// ---------------------- REVIEW ----------------------
+// rv_next_card -> { id, term, reading, sentence, status, ..., entries, intervals }
+// with `intervals` in seconds for again / hard / good / easy, or null
+
+let card = null;
+
+function formatInterval(seconds) {
+    const minutes = seconds / 60;
+    if (minutes < 60) return `${Math.round(minutes)}m`;
+    const days = seconds / 86400;
+    if (days < 1) return `${Math.round(minutes / 60)}h`;
+    if (days < 30) return `${Math.round(days)}d`;
+    if (days < 365) return `${(days / 30).toFixed(1)}mo`;
+    return `${(days / 365).toFixed(1)}y`;
+}
+
+// the sentence with the word marked
+function renderSentence(sentence, term) {
+    sentenceEl.replaceChildren();
+    if (!sentence) return;
+    const at = sentence.indexOf(term);
+    if (at < 0) {
+        sentenceEl.textContent = sentence;
+        return;
+    }
+    const mark = document.createElement("mark");
+    mark.textContent = term;
+    sentenceEl.append(sentence.slice(0, at), mark, sentence.slice(at + term.length));
+}
+
+async function nextCard() {
+    try {
+        card = await invoke("rv_next_card");
+    } catch (err) {
+        statusEl.innerText = `Could not load the next card: ${err}`;
+        return;
+    }
+
+    cardEl.hidden = !card;
+    answerEl.hidden = true;
+    gradesEl.hidden = true;
+    showAnswerEl.hidden = !card;
+    if (!card) {
+        statusEl.innerText = "Nothing to review right now.";
+        return;
+    }
+
+    termEl.textContent = card.term;
+    renderSentence(card.sentence, card.term);
+    readingEl.textContent = card.reading ?? "";
+    entriesEl.replaceChildren(...(card.entries?.entries ?? []).map(renderEntry));
+    if (!card.entries) {
+        entriesEl.textContent = "No dictionary entries saved for this word.";
+    }
+    gradesEl.querySelectorAll(".grade").forEach((btn, i) => {
+        btn.querySelector(".interval").textContent = formatInterval(card.intervals[i]);
+    });
+}
+
+function showAnswer() {
+    if (!card) return;
+    answerEl.hidden = false;
+    showAnswerEl.hidden = true;
+    gradesEl.hidden = false;
+}
+
+async function answer(grade) {
+    if (!card || answerEl.hidden) return;
+    try {
+        await invoke("rv_answer", { id: card.id, grade });
+    } catch (err) {
+        statusEl.innerText = `Answer not saved: ${err}`;
+        return;
+    }
+    refreshCounts();
+    nextCard();
+}
+
+// ---------------------- WORDS ----------------------
+// rv_words({ text, status }) -> [{ id, term, reading, sentence, status,
+//   added_at, due, interval_days, reps, lapses }]
+
+async function refreshWords() {
+    let words = [];
+    try {
+        words = await invoke("rv_words", {
+            filter: {
+                text: searchEl.value.trim() || null,
+                status: statusFilterEl.value || null,
+            },
+        });
+    } catch (err) {
+        statusEl.innerText = `Could not load the word list: ${err}`;
+        return;
+    }
+
+    wordListEl.replaceChildren(...words.map(renderWord));
+    if (!words.length) {
+        statusEl.innerText = "No saved words found. Save words from the lookup window.";
+    }
+}
+
+function formatDate(seconds) {
+    return new Date(seconds * 1000).toLocaleDateString();
+}
+
+function wordMeta(word) {
+    const parts = [`added ${formatDate(word.added_at)}`];
+    if (word.due !== null) parts.push(`next review ${formatDate(word.due)}`);
+    if (word.lapses) parts.push(`forgotten ${word.lapses}×`);
+    return parts.join(" · ");
+}
+
+function renderWord(word) {
+    const li = document.createElement("li");
+
+    const term = document.createElement("div");
+    term.className = "word";
+    term.textContent = word.reading ? `${word.term}【${word.reading}】` : word.term;
+
+    const sentence = document.createElement("div");
+    sentence.className = "word-sentence";
+    sentence.textContent = word.sentence ?? "";
+
+    const meta = document.createElement("div");
+    meta.className = "meta";
+    meta.textContent = wordMeta(word);
+
+    const status = document.createElement("select");
+    for (const [value, label] of Object.entries(STATUS_LABELS)) {
+        const option = document.createElement("option");
+        option.value = value;
+        option.textContent = label;
+        status.append(option);
+    }
+    status.value = word.status;
+    status.onchange = async () => {
+        try {
+            await invoke("rv_set_status", { id: word.id, status: status.value });
+            refresh();
+        } catch (err) {
+            statusEl.innerText = `Status not changed: ${err}`;
+        }
+    };
+
+    const remove = document.createElement("div");
+    remove.className = "action-btn";
+    remove.textContent = "Delete";
+    remove.onclick = async () => {
+        try {
+            await invoke("rv_delete_word", { id: word.id });
+            li.remove();
+            refreshCounts();
+        } catch (err) {
+            statusEl.innerText = `Delete failed: ${err}`;
+        }
+    };
+
+    const actions = document.createElement("div");
+    actions.className = "actions";
+    actions.append(status, remove);
+
+    li.append(term, sentence, meta, actions);
+    return li;
+}
+
+// ---------------------- IMPORT ----------------------
+// rv_import_word_list({ status }) -> { added, updated }, null if no file
+// was picked; rv_import_anki({ query, field }) -> { added, updated }
+
+let importing = false;
+
+async function importWords(command, args) {
+    if (importing) return;
+    importing = true;
+    statusEl.innerText = "Importing…";
+    try {
+        const counts = await invoke(command, args);
+        statusEl.innerText = "";
+        if (counts) {
+            await refreshWords();
+            refreshCounts();
+            statusEl.innerText =
+                `Imported ${counts.added} new words, ${counts.updated} moved on`;
+        }
+    } catch (err) {
+        statusEl.innerText = `Import failed: ${err}`;
+    } finally {
+        importing = false;
+    }
+}
+
+// the cards of the deck UMOD adds to, read from the field the headword goes in
+async function fillAnkiDefaults() {
+    try {
+        const { anki } = await invoke("get_settings");
+        ankiQueryEl.value = `deck:"${anki.deck}"`;
+        const headwordField = Object.entries(anki.fields)
+            .find(([, template]) => template.includes("{headword}"));
+        ankiFieldEl.value = headwordField?.[0] ?? "";
+    } catch (err) {
+        console.error("Settings not loaded:", err);
+    }
+}
+
+// ---------------------- EVENTS ----------------------
+
+for (const tabEl of document.querySelectorAll(".tab")) {
+    tabEl.addEventListener("click", () => showTab(tabEl.dataset.tab));
+}
+
+showAnswerEl.addEventListener("click", showAnswer);
+for (const btn of gradesEl.querySelectorAll(".grade")) {
+    btn.addEventListener("click", () => answer(btn.dataset.grade));
+}
+
+let searchTimer = null;
+searchEl.addEventListener("input", () => {
+    clearTimeout(searchTimer);
+    searchTimer = setTimeout(refreshWords, 200);
+});
+statusFilterEl.addEventListener("change", refreshWords);
+
+document.getElementById("import-list").addEventListener("click", () =>
+    importWords("rv_import_word_list", { status: importStatusEl.value }));
+document.getElementById("import-anki").addEventListener("click", () =>
+    importWords("rv_import_anki", {
+        query: ankiQueryEl.value.trim(),
+        field: ankiFieldEl.value.trim(),
+    }));
+
+// Space shows the answer, 1-4 answer it
+window.addEventListener("keydown", (e) => {
+    if (e.key === "Escape") {
+        window.__TAURI__.window.getCurrentWindow().close();
+        return;
+    }
+    if (currentTab !== "review" || e.target.tagName === "INPUT") return;
+    if (e.key === " ") {
+        e.preventDefault();
+        showAnswer();
+    } else if (["1", "2", "3", "4"].includes(e.key)) {
+        answer(GRADES[Number(e.key) - 1]);
+    }
+});
+
+// words saved while the window was open show up when it is focused again,
+// unless a card is being answered
+window.addEventListener("focus", () => {
+    if (currentTab === "words" || !card) refresh();
+});
+
+showTab("review");
+fillAnkiDefaults();
@@ -0,0 +1,225 @@
+// ---------------------- TAURI GLOBAL API ----------------------
+const { invoke } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+const formEl = document.getElementById("settings");
+const statusEl = document.getElementById("status");
+const profilesEl = document.getElementById("profile-hotkeys");
+const noProfilesEl = document.getElementById("no-profiles");
+
+// last settings from Rust; fields not shown in the form (e.g. version) are
+// sent back unchanged
+let current = null;
+
+// ---------------------- PATH HELPERS ----------------------
+// data-path="ocr.device" -> settings.ocr.device
+
+function getPath(obj, path) {
+    return path.split(".").reduce((o, key) => o?.[key], obj);
+}
+
+function setPath(obj, path, value) {
+    const keys = path.split(".");
+    const last = keys.pop();
+    keys.reduce((o, key) => o[key], obj)[last] = value;
+}
+
+function boundInputs() {
+    return formEl.querySelectorAll("[data-path]");
+}
+
+// data-format="optional": null edited as an empty field
+
+// data-format="map": { key: value } edited as "key: value" lines
+
+function mapToLines(map) {
+    return Object.entries(map ?? {})
+        .map(([key, value]) => `${key}: ${value}`)
+        .join("\n");
+}
+
+function linesToMap(text) {
+    const map = {};
+    for (const line of text.split("\n")) {
+        if (!line.trim()) continue;
+        const colon = line.indexOf(":");
+        // a line without a colon becomes a key with an empty name for Rust to reject
+        const [key, value] = colon < 0 ? ["", line] : [line.slice(0, colon), line.slice(colon + 1)];
+        map[key.trim()] = value.trim();
+    }
+    return map;
+}
+
+// ---------------------- FORM <-> SETTINGS ----------------------
+
+function fillForm(settings) {
+    current = settings;
+    for (const el of boundInputs()) {
+        const value = getPath(settings, el.dataset.path);
+        if (el.type === "checkbox") {
+            el.checked = value;
+        } else if (el.dataset.format === "map") {
+            el.value = mapToLines(value);
+        } else if (el.dataset.format === "optional") {
+            el.value = value ?? "";
+        } else {
+            el.value = value;
+        }
+    }
+}
+
+function readForm() {
+    const settings = structuredClone(current);
+    for (const el of boundInputs()) {
+        let value = el.value;
+        if (el.type === "checkbox") {
+            value = el.checked;
+        } else if (el.dataset.format === "map") {
+            value = linesToMap(el.value);
+        } else if (el.dataset.format === "optional") {
+            value = el.value.trim() || null;
+        } else if (el.type === "number") {
+            // left as text when not a number so Rust rejects it for this field
+            value = el.value === "" ? el.value : Number(el.value);
+        }
+        setPath(settings, el.dataset.path, value);
+    }
+    return settings;
+}
+
+async function fillChoices() {
+    // settings_choices -> { ocr_engines: [{ id, label }], ... }
+    const choices = await invoke("settings_choices");
+    for (const select of formEl.querySelectorAll("select[data-choices]")) {
+        for (const { id, label } of choices[select.dataset.choices]) {
+            const option = document.createElement("option");
+            option.value = id;
+            option.textContent = label;
+            select.appendChild(option);
+        }
+    }
+}
+
+// ---------------------- PROFILE HOTKEYS ----------------------
+// list_profiles -> [{ name, hotkey, ... }]; profiles are not part of the
+// settings, each changed hotkey is saved with set_profile_hotkey
+
+function fillProfiles(profiles) {
+    profilesEl.replaceChildren();
+    noProfilesEl.style.display = profiles.length ? "none" : "block";
+
+    for (const profile of profiles) {
+        const label = document.createElement("label");
+        label.textContent = profile.name;
+
+        const input = document.createElement("input");
+        input.type = "text";
+        input.placeholder = "unbound";
+        input.value = profile.hotkey ?? "";
+        input.dataset.profile = profile.name;
+        input.dataset.saved = input.value;
+        label.appendChild(input);
+
+        const error = document.createElement("div");
+        error.className = "error";
+        profilesEl.append(label, error);
+    }
+}
+
+// true when every changed profile hotkey was saved
+async function saveProfileHotkeys() {
+    let saved = true;
+    for (const input of profilesEl.querySelectorAll("input[data-profile]")) {
+        if (input.value.trim() === input.dataset.saved) continue;
+
+        const error = input.parentElement.nextElementSibling;
+        try {
+            const profile = await invoke("set_profile_hotkey", {
+                name: input.dataset.profile,
+                hotkey: input.value,
+            });
+            input.value = input.dataset.saved = profile.hotkey ?? "";
+        } catch (e) {
+            error.textContent = e;
+            saved = false;
+        }
+    }
+    return saved;
+}
+
+// ---------------------- ERRORS ----------------------
+// update_settings rejects with [{ field, message }]
+
+function clearErrors() {
+    statusEl.innerText = "";
+    for (const el of formEl.querySelectorAll(".error")) {
+        el.textContent = "";
+    }
+}
+
+function showErrors(errors) {
+    if (!Array.isArray(errors)) {
+        statusEl.innerText = `Not saved: ${errors}`;
+        return;
+    }
+
+    const unplaced = [];
+    for (const { field, message } of errors) {
+        const el = formEl.querySelector(`.error[data-for="${field}"]`);
+        if (el) {
+            el.textContent = el.textContent ? `${el.textContent}; ${message}` : message;
+        } else {
+            unplaced.push(field ? `${field}: ${message}` : message);
+        }
+    }
+    statusEl.innerText = unplaced.length ? unplaced.join("\n") : "Please fix the marked fields.";
+}
+
+// ---------------------- ACTIONS ----------------------
+
+async function save() {
+    clearErrors();
+    try {
+        fillForm(await invoke("update_settings", { settings: readForm() }));
+    } catch (errors) {
+        showErrors(errors);
+        return;
+    }
+    // after the settings, so a hotkey moved from an action to a profile is free
+    statusEl.innerText = (await saveProfileHotkeys())
+        ? "Saved."
+        : "Please fix the marked fields.";
+}
+
+async function revert() {
+    clearErrors();
+    fillForm(await invoke("get_settings"));
+    fillProfiles(await invoke("list_profiles"));
+}
+
+// pick_captures_dir -> folder path, null if the dialog was cancelled
+async function pickCapturesDir() {
+    const dir = await invoke("pick_captures_dir");
+    if (dir != null) {
+        formEl.querySelector('[data-path="captures_dir"]').value = dir;
+    }
+}
+
+document.getElementById("pick-captures-dir").onclick = pickCapturesDir;
+document.getElementById("save").onclick = save;
+document.getElementById("reset").onclick = revert;
+formEl.addEventListener("submit", (e) => {
+    e.preventDefault();
+    save();
+});
+
+window.addEventListener("keydown", (e) => {
+    if (e.key === "Escape") {
+        window.__TAURI__.window.getCurrentWindow().close();
+    }
+});
+
+// changes from the tray or the settings file
+listen("settings-changed", (event) => fillForm(event.payload));
+
+fillChoices().then(revert);